@@ -1 +1,198 @@
+use anchor_lang::{prelude::*, AnchorDeserialize};
 
+use crate::constant::*;
+use crate::errors::AuctionHouseError;
+use crate::state::{AuctionHouse, Auctioneer, AuthorityScope};
+
+/// Accounts for the [`delegate_auctioneer` handler](auction_house/fn.delegate_auctioneer.html).
+#[derive(Accounts)]
+pub struct DelegateAuctioneer<'info> {
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// Authority key for the Auction House, paying for the auctioneer PDA.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: The auction house authority can set this to whatever external address they wish.
+    /// The external auctioneer authority.
+    pub auctioneer_authority: UncheckedAccount<'info>,
+
+    /// The auctioneer PDA owned by Auction House storing scopes.
+    #[account(
+        init,
+        payer = authority,
+        space = AUCTIONEER_SIZE,
+        seeds = [
+            AUCTIONEER.as_bytes(),
+            auction_house.key().as_ref(),
+            auctioneer_authority.key().as_ref()
+        ],
+        bump
+    )]
+    pub ah_auctioneer_pda: Box<Account<'info, Auctioneer>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for the [`update_auctioneer` handler](auction_house/fn.update_auctioneer.html).
+#[derive(Accounts)]
+pub struct UpdateAuctioneer<'info> {
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// Authority key for the Auction House.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Validated by the auctioneer PDA seeds.
+    /// The external auctioneer authority.
+    pub auctioneer_authority: UncheckedAccount<'info>,
+
+    /// The auctioneer PDA owned by Auction House storing scopes.
+    #[account(
+        mut,
+        seeds = [
+            AUCTIONEER.as_bytes(),
+            auction_house.key().as_ref(),
+            auctioneer_authority.key().as_ref()
+        ],
+        bump = auction_house.auctioneer_pda_bump,
+        has_one = auctioneer_authority
+    )]
+    pub ah_auctioneer_pda: Box<Account<'info, Auctioneer>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for the [`revoke_auctioneer` handler](auction_house/fn.revoke_auctioneer.html).
+#[derive(Accounts)]
+pub struct RevokeAuctioneer<'info> {
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// Authority key for the Auction House, receiving the auctioneer PDA rent.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Validated by the auctioneer PDA seeds.
+    /// The external auctioneer authority.
+    pub auctioneer_authority: UncheckedAccount<'info>,
+
+    /// The auctioneer PDA owned by Auction House storing scopes.
+    #[account(
+        mut,
+        close = authority,
+        seeds = [
+            AUCTIONEER.as_bytes(),
+            auction_house.key().as_ref(),
+            auctioneer_authority.key().as_ref()
+        ],
+        bump = auction_house.auctioneer_pda_bump,
+        has_one = auctioneer_authority
+    )]
+    pub ah_auctioneer_pda: Box<Account<'info, Auctioneer>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// NOTE: Turn a list of scopes into the fixed-size flag array stored on the auctioneer PDA.
+fn scopes_to_flags(scopes: Vec<AuthorityScope>) -> Result<[bool; MAX_NUM_SCOPES]> {
+    if scopes.len() > MAX_NUM_SCOPES {
+        return Err(AuctionHouseError::TooManyScopes.into());
+    }
+
+    let mut flags = [false; MAX_NUM_SCOPES];
+    for scope in scopes {
+        flags[scope as usize] = true;
+    }
+
+    Ok(flags)
+}
+
+/// NOTE: Create the auctioneer PDA and route every trade of the house through the `auctioneer_*` handlers.
+pub fn delegate_auctioneer<'info>(
+    ctx: Context<'_, '_, '_, 'info, DelegateAuctioneer<'info>>,
+    scopes: Vec<AuthorityScope>,
+) -> Result<()> {
+    let auction_house = &mut ctx.accounts.auction_house;
+    let auctioneer_authority = &ctx.accounts.auctioneer_authority;
+
+    // NOTE: the house only tracks a single auctioneer PDA bump
+    if auction_house.has_auctioneer {
+        return Err(AuctionHouseError::AuctioneerAlreadyDelegated.into());
+    }
+
+    let flags = scopes_to_flags(scopes)?;
+
+    auction_house.has_auctioneer = true;
+    auction_house.auctioneer_pda_bump = *ctx
+        .bumps
+        .get("ah_auctioneer_pda")
+        .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?;
+
+    let auctioneer = &mut ctx.accounts.ah_auctioneer_pda;
+    auctioneer.auctioneer_authority = auctioneer_authority.key();
+    auctioneer.auction_house = auction_house.key();
+    auctioneer.scopes = flags;
+
+    Ok(())
+}
+
+/// NOTE: Replace the scopes granted to the delegated auctioneer.
+pub fn update_auctioneer<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateAuctioneer<'info>>,
+    scopes: Vec<AuthorityScope>,
+) -> Result<()> {
+    if !ctx.accounts.auction_house.has_auctioneer {
+        return Err(AuctionHouseError::AuctionHouseNotDelegated.into());
+    }
+
+    ctx.accounts.ah_auctioneer_pda.scopes = scopes_to_flags(scopes)?;
+
+    Ok(())
+}
+
+/// NOTE: Close the auctioneer PDA and let the house accept the non-auctioneer handlers again.
+pub fn revoke_auctioneer<'info>(ctx: Context<'_, '_, '_, 'info, RevokeAuctioneer<'info>>) -> Result<()> {
+    let auction_house = &mut ctx.accounts.auction_house;
+
+    if !auction_house.has_auctioneer {
+        return Err(AuctionHouseError::AuctionHouseNotDelegated.into());
+    }
+
+    auction_house.has_auctioneer = false;
+    auction_house.auctioneer_pda_bump = 0;
+
+    Ok(())
+}
@@ -7,8 +7,8 @@ use anchor_spl::token::{Mint, Token, TokenAccount};
 use solana_program::{program_memory::sol_memset, unchecked_div_by_const};
 
 use crate::{
-    constant::*, errors::AuctionHouseError, utils::*, AuctionHouse, AuthorityScope,
-    TRADE_STATE_SIZE,
+    constant::*, errors::AuctionHouseError, state::{TradeSide, TradeStateData}, utils::*,
+    AuctionHouse, Auctioneer, AuthorityScope, TRADE_STATE_SIZE,
 };
 
 #[derive(Accounts)]
@@ -18,11 +18,240 @@ buyer_price: u64,
 token_size: u64)]
 pub struct Buy<'info> {
     /// User wallet account
-    wallet: Signer<'info>,
+    pub wallet: Signer<'info>,
 
     /// CHECK: Validated in big_logic
     /// User SOL or SPL account to transfer funds from.
     #[account(mut)]
+    pub payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// SPL token account transfer authority.
+    pub transfer_authority: UncheckedAccount<'info>,
+
+    /// Auction House instance treasurt mint account
+    pub treasury_mint: Account<'info, Mint>,
+
+    /// SPL token account.
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated in bid_logic
+    /// SPL token account metadata.
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer escrow payment account PDA.
+    #[account(mut, seeds = [PREFIX.as_bytes(), auction_house.key().as_ref(), wallet.key().as_ref()], bump = escrow_payment_bump)]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    // Auction House instance PDA account
+    #[account(seeds = [PREFIX.as_bytes(), auction_house.creator.as_ref(), auction_house.treasury_mint.as_ref()], bump = auction_house.bump, has_one = authority, has_one = treasury_mint, has_one = auction_house_fee_account)]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account
+    #[account(mut, seeds = [PREFIX.as_bytes(), auction_house.key().as_ref(), FEE_PAYER.as_bytes()], bump = auction_house.fee_payer_bump)]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer trade state PDA.
+    #[account(
+        mut, 
+        seeds = [
+            PREFIX.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            treasury_mint.key().as_ref(),
+            token_account.mint.as_ref(),
+            buyer_price.to_le_bytes().as_ref(),
+            token_size.to_le_bytes().as_ref()
+        ],
+        bump = trade_state_bump)
+    ]
+    pub buyer_trade_state: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>
+}
+
+pub fn private_bid<'info> (
+    ctx: Context<'_, '_, '_, 'info, Buy<'info>>,
+    trade_state_bump: u8,
+    escrow_payment_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+    expiry: Option<i64>
+) -> Result<()> {
+    // If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if ctx.accounts.auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    // NOTE: an approved referrer passed as the only remaining account is paid its share of the house fee on the fill
+    let referrer = match ctx.remaining_accounts {
+        [] => None,
+        [referrer] => Some(get_approved_referrer(referrer, &ctx.accounts.auction_house.key())?.wallet),
+        _ => return Err(AuctionHouseError::InvalidReferrer.into()),
+    };
+
+    bid_logic(
+        ctx.accounts.wallet.to_owned(),
+        ctx.accounts.payment_account.to_owned(),
+        ctx.accounts.transfer_authority.to_owned(),
+        ctx.accounts.treasury_mint.to_owned(),
+        *ctx.accounts.token_account.to_owned(),
+        ctx.accounts.metadata.to_owned(),
+        ctx.accounts.escrow_payment_account.to_owned(),
+        ctx.accounts.authority.to_owned(),
+        *ctx.accounts.auction_house.to_owned(),
+        ctx.accounts.auction_house_fee_account.to_owned(),
+        ctx.accounts.buyer_trade_state.to_owned(),
+        ctx.accounts.token_program.to_owned(),
+        ctx.accounts.system_program.to_owned(),
+        ctx.accounts.rent.to_owned(),
+        trade_state_bump,
+        escrow_payment_bump,
+        buyer_price,
+        token_size,
+        false,
+        expiry,
+        referrer,
+    )
+    
+}
+
+#[derive(Accounts)]
+#[instruction(trade_state_bump: u8,
+escrow_payment_bump: u8,
+buyer_price: u64,
+token_size: u64)]
+pub struct PublicBuy<'info> {
+    /// User wallet account
+    wallet: Signer<'info>,
+
+    /// CHECK: Validated in bid_logic
+    /// User SOL or SPL account to transfer funds from.
+    #[account(mut)]
+    payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// SPL token account transfer authority.
+    transfer_authority: UncheckedAccount<'info>,
+
+    /// Auction House instance treasurt mint account
+    treasury_mint: Account<'info, Mint>,
+
+    /// SPL token account.
+    token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated in bid_logic
+    /// SPL token account metadata.
+    metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic
+    /// Auction House instance authority account.
+    authority: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer escrow payment account PDA.
+    #[account(mut, seeds = [PREFIX.as_bytes(), auction_house.key().as_ref(), wallet.key().as_ref()], bump = escrow_payment_bump)]
+    escrow_payment_account: UncheckedAccount<'info>,
+
+    // Auction House instance PDA account
+    #[account(seeds = [PREFIX.as_bytes(), auction_house.creator.as_ref(), auction_house.treasury_mint.as_ref()], bump = auction_house.bump, has_one = authority, has_one = treasury_mint, has_one = auction_house_fee_account)]
+    auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account
+    #[account(mut, seeds = [PREFIX.as_bytes(), auction_house.key().as_ref(), FEE_PAYER.as_bytes()], bump = auction_house.fee_payer_bump)]
+    auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer trade state PDA. Public bids are not tied to a specific token account.
+    #[account(
+        mut, 
+        seeds = [
+            PREFIX.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            treasury_mint.key().as_ref(),
+            token_account.mint.as_ref(),
+            buyer_price.to_le_bytes().as_ref(),
+            token_size.to_le_bytes().as_ref()
+        ],
+        bump = trade_state_bump)
+    ]
+    buyer_trade_state: UncheckedAccount<'info>,
+
+    token_program: Program<'info, Token>,
+    system_program: Program<'info, System>,
+    rent: Sysvar<'info, Rent>
+}
+
+pub fn public_bid<'info> (
+    ctx: Context<'_, '_, '_, 'info, PublicBuy<'info>>,
+    trade_state_bump: u8,
+    escrow_payment_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+    expiry: Option<i64>
+) -> Result<()> {
+    // If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if ctx.accounts.auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    // NOTE: an approved referrer passed as the only remaining account is paid its share of the house fee on the fill
+    let referrer = match ctx.remaining_accounts {
+        [] => None,
+        [referrer] => Some(get_approved_referrer(referrer, &ctx.accounts.auction_house.key())?.wallet),
+        _ => return Err(AuctionHouseError::InvalidReferrer.into()),
+    };
+
+    bid_logic(
+        ctx.accounts.wallet.to_owned(),
+        ctx.accounts.payment_account.to_owned(),
+        ctx.accounts.transfer_authority.to_owned(),
+        ctx.accounts.treasury_mint.to_owned(),
+        *ctx.accounts.token_account.to_owned(),
+        ctx.accounts.metadata.to_owned(),
+        ctx.accounts.escrow_payment_account.to_owned(),
+        ctx.accounts.authority.to_owned(),
+        *ctx.accounts.auction_house.to_owned(),
+        ctx.accounts.auction_house_fee_account.to_owned(),
+        ctx.accounts.buyer_trade_state.to_owned(),
+        ctx.accounts.token_program.to_owned(),
+        ctx.accounts.system_program.to_owned(),
+        ctx.accounts.rent.to_owned(),
+        trade_state_bump,
+        escrow_payment_bump,
+        buyer_price,
+        token_size,
+        true,
+        expiry,
+        referrer,
+    )
+    
+}
+
+#[derive(Accounts)]
+#[instruction(trade_state_bump: u8,
+escrow_payment_bump: u8,
+buyer_price: u64,
+token_size: u64)]
+pub struct AuctioneerBuy<'info> {
+    /// User wallet account
+    wallet: Signer<'info>,
+
+    /// CHECK: Validated in bid_logic
+    /// User SOL or SPL account to transfer funds from.
+    #[account(mut)]
     payment_account: UncheckedAccount<'info>,
 
     /// CHECK: Validated in bid_logic.
@@ -77,16 +306,38 @@ pub struct Buy<'info> {
 
     token_program: Program<'info, Token>,
     system_program: Program<'info, System>,
-    rent: Sysvar<'info, Rent>
+    rent: Sysvar<'info, Rent>,
+
+    /// The auctioneer authority - typically a PDA of the Auctioneer program running this action.
+    auctioneer_authority: Signer<'info>,
+
+    /// The auctioneer PDA owned by Auction House storing scopes.
+    #[account(
+        seeds = [
+            AUCTIONEER.as_bytes(),
+            auction_house.key().as_ref(),
+            auctioneer_authority.key().as_ref()
+        ],
+        bump = auction_house.auctioneer_pda_bump
+    )]
+    ah_auctioneer_pda: Box<Account<'info, Auctioneer>>,
 }
 
-pub fn private_bid<'info> (
-    ctx: Context<'_, '_, '_, 'info, Buy<'info>>,
+pub fn auctioneer_private_bid<'info> (
+    ctx: Context<'_, '_, '_, 'info, AuctioneerBuy<'info>>,
     trade_state_bump: u8,
     escrow_payment_bump: u8,
     buyer_price: u64,
-    token_size: u64
+    token_size: u64,
+    expiry: Option<i64>
 ) -> Result<()> {
+    assert_valid_auctioneer_and_scope(
+        &ctx.accounts.auction_house,
+        &ctx.accounts.auctioneer_authority.key(),
+        &ctx.accounts.ah_auctioneer_pda,
+        AuthorityScope::Buy,
+    )?;
+
     bid_logic(
         ctx.accounts.wallet.to_owned(),
         ctx.accounts.payment_account.to_owned(),
@@ -107,8 +358,131 @@ pub fn private_bid<'info> (
         buyer_price,
         token_size,
         false,
+        expiry,
+        None,
+    )
+}
+
+#[derive(Accounts)]
+#[instruction(trade_state_bump: u8,
+escrow_payment_bump: u8,
+buyer_price: u64,
+token_size: u64)]
+pub struct AuctioneerPublicBuy<'info> {
+    /// User wallet account
+    wallet: Signer<'info>,
+
+    /// CHECK: Validated in bid_logic
+    /// User SOL or SPL account to transfer funds from.
+    #[account(mut)]
+    payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// SPL token account transfer authority.
+    transfer_authority: UncheckedAccount<'info>,
+
+    /// Auction House instance treasurt mint account
+    treasury_mint: Account<'info, Mint>,
+
+    /// SPL token account.
+    token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated in bid_logic
+    /// SPL token account metadata.
+    metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic
+    /// Auction House instance authority account.
+    authority: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer escrow payment account PDA.
+    #[account(mut, seeds = [PREFIX.as_bytes(), auction_house.key().as_ref(), wallet.key().as_ref()], bump = escrow_payment_bump)]
+    escrow_payment_account: UncheckedAccount<'info>,
+
+    // Auction House instance PDA account
+    #[account(seeds = [PREFIX.as_bytes(), auction_house.creator.as_ref(), auction_house.treasury_mint.as_ref()], bump = auction_house.bump, has_one = authority, has_one = treasury_mint, has_one = auction_house_fee_account)]
+    auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account
+    #[account(mut, seeds = [PREFIX.as_bytes(), auction_house.key().as_ref(), FEE_PAYER.as_bytes()], bump = auction_house.fee_payer_bump)]
+    auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer trade state PDA. Public bids are not tied to a specific token account.
+    #[account(
+        mut, 
+        seeds = [
+            PREFIX.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            treasury_mint.key().as_ref(),
+            token_account.mint.as_ref(),
+            buyer_price.to_le_bytes().as_ref(),
+            token_size.to_le_bytes().as_ref()
+        ],
+        bump = trade_state_bump)
+    ]
+    buyer_trade_state: UncheckedAccount<'info>,
+
+    token_program: Program<'info, Token>,
+    system_program: Program<'info, System>,
+    rent: Sysvar<'info, Rent>,
+
+    /// The auctioneer authority - typically a PDA of the Auctioneer program running this action.
+    auctioneer_authority: Signer<'info>,
+
+    /// The auctioneer PDA owned by Auction House storing scopes.
+    #[account(
+        seeds = [
+            AUCTIONEER.as_bytes(),
+            auction_house.key().as_ref(),
+            auctioneer_authority.key().as_ref()
+        ],
+        bump = auction_house.auctioneer_pda_bump
+    )]
+    ah_auctioneer_pda: Box<Account<'info, Auctioneer>>,
+}
+
+pub fn auctioneer_public_bid<'info> (
+    ctx: Context<'_, '_, '_, 'info, AuctioneerPublicBuy<'info>>,
+    trade_state_bump: u8,
+    escrow_payment_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+    expiry: Option<i64>
+) -> Result<()> {
+    assert_valid_auctioneer_and_scope(
+        &ctx.accounts.auction_house,
+        &ctx.accounts.auctioneer_authority.key(),
+        &ctx.accounts.ah_auctioneer_pda,
+        AuthorityScope::PublicBuy,
+    )?;
+
+    bid_logic(
+        ctx.accounts.wallet.to_owned(),
+        ctx.accounts.payment_account.to_owned(),
+        ctx.accounts.transfer_authority.to_owned(),
+        ctx.accounts.treasury_mint.to_owned(),
+        *ctx.accounts.token_account.to_owned(),
+        ctx.accounts.metadata.to_owned(),
+        ctx.accounts.escrow_payment_account.to_owned(),
+        ctx.accounts.authority.to_owned(),
+        *ctx.accounts.auction_house.to_owned(),
+        ctx.accounts.auction_house_fee_account.to_owned(),
+        ctx.accounts.buyer_trade_state.to_owned(),
+        ctx.accounts.token_program.to_owned(),
+        ctx.accounts.system_program.to_owned(),
+        ctx.accounts.rent.to_owned(),
+        trade_state_bump,
+        escrow_payment_bump,
+        buyer_price,
+        token_size,
+        true,
+        expiry,
+        None,
     )
-    
 }
 
 pub fn bid_logic<'info>( // Basically every thing in `Buy` struct and arguments
@@ -130,12 +504,15 @@ pub fn bid_logic<'info>( // Basically every thing in `Buy` struct and arguments
     escrow_payment_bump: u8,
     buyer_price: u64,
     token_size: u64,
-    public: bool
-
+    public: bool,
+    expiry: Option<i64>,
+    referrer: Option<Pubkey>
 ) -> Result<()> {
-     // If it has an auctioneer authority delegated must use auctioneer_* handler.
-     if auction_house.has_auctioneer {
-        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    assert_valid_expiry(expiry)?;
+
+    // NOTE: an existing trade state keeps the expiry and referrer it was placed with, so new ones would be dropped
+    if (expiry.is_some() || referrer.is_some()) && !buyer_trade_state.data_is_empty() {
+        return Err(AuctionHouseError::TradeStateAlreadyExists.into());
     }
 
     assert_valid_trade_state(
@@ -188,12 +565,15 @@ pub fn bid_logic<'info>( // Basically every thing in `Buy` struct and arguments
         is_native,
     )?;
 
+    // NOTE: the bid may end up on either side of the trade, so escrow covers the larger buyer fee
+    let escrow_amount = bid_escrow_amount(&auction_house, buyer_price)?;
+
     if is_native {
         assert_keys_equal(wallet.key(), payment_account.key())?;
         
-        // NOTE: check whether the lamport in escrow account is enough for buyer_price + rent
-        if escrow_payment_account.lamports() < buyer_price.checked_add(rent.minimum_balance(escrow_payment_account.data_len())).ok_or(AuctionHouseError::NumericalOverflow)?{
-            let diff = buyer_price
+        // NOTE: check whether the lamport in escrow account is enough for escrow_amount + rent
+        if escrow_payment_account.lamports() < escrow_amount.checked_add(rent.minimum_balance(escrow_payment_account.data_len())).ok_or(AuctionHouseError::NumericalOverflow)?{
+            let diff = escrow_amount
                 .checked_add(rent.minimum_balance(escrow_payment_account.data_len()))
                 .ok_or(AuctionHouseError::NumericalOverflow)?
                 .checked_sub(escrow_payment_account.lamports())
@@ -213,8 +593,8 @@ pub fn bid_logic<'info>( // Basically every thing in `Buy` struct and arguments
         let escrow_payment_loaded : spl_token::state::Account = assert_initialized(&escrow_payment_account)?;
 
         // NOTE: if the lamport inside escrow account is not enough, transfer more lamport to it
-        if escrow_payment_loaded.amount < buyer_price {
-            let diff = buyer_price.checked_sub(escrow_payment_loaded.amount).ok_or(AuctionHouseError::NumericalOverflow)?;
+        if escrow_payment_loaded.amount < escrow_amount {
+            let diff = escrow_amount.checked_sub(escrow_payment_loaded.amount).ok_or(AuctionHouseError::NumericalOverflow)?;
 
             invoke(
                 &spl_token::instruction::transfer(
@@ -274,7 +654,23 @@ pub fn bid_logic<'info>( // Basically every thing in `Buy` struct and arguments
             )?;
         }
 
-        sol_memset(*ts_info.try_borrow_mut_data()?, trade_state_bump, TRADE_STATE_SIZE);
+        let clock = Clock::get()?;
+        write_trade_state(&ts_info, trade_state_bump, &TradeStateData {
+            version: TRADE_STATE_VERSION,
+            side: TradeSide::Buy,
+            flags: if public { TRADE_STATE_FLAG_PUBLIC } else { 0 },
+            auction_house: auction_house_key,
+            wallet: wallet_key,
+            token_account: if public { Pubkey::default() } else { token_account_key },
+            mint: token_account.mint,
+            price: buyer_price,
+            token_size,
+            created_slot: clock.slot,
+            created_at: clock.unix_timestamp,
+            expiry: expiry.unwrap_or(0),
+            fee_payer: fee_payer.key(),
+            referrer: referrer.unwrap_or_default(),
+        })?;
     }
 
     Ok(())
@@ -0,0 +1,655 @@
+use anchor_lang::{prelude::*, AnchorDeserialize};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::bid::bid_logic;
+use crate::cancel::{cancel_order_logic, Cancel};
+use crate::constant::*;
+use crate::errors::AuctionHouseError;
+use crate::execute_sale::{execute_sale_logic, ExecuteSale, ExecuteSaleOptions};
+use crate::sell::{sell_logic, Sell};
+use crate::state::{AuctionHouse, Bundle, BundleItem};
+use crate::utils::{
+    assert_derivation, assert_keys_equal, bundle_trade_state, collect_trade_state_rent, get_approved_referrer,
+    get_creator_accounts_len, get_fee_payer,
+};
+
+/// Accounts for the [`list_bundle` handler](auction_house/fn.list_bundle.html).
+#[derive(Accounts)]
+#[instruction(program_as_signer_bump: u8, bundle_id: u64)]
+pub struct ListBundle<'info> {
+    /// Seller wallet account, paying for the bundle account.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the auction house.
+    /// Auction House authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// Bundle account listing the items and their price allocations.
+    #[account(
+        init,
+        payer = wallet,
+        space = BUNDLE_SIZE,
+        seeds = [
+            BUNDLE.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref(),
+            &bundle_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub bundle: Box<Account<'info, Bundle>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    #[account(seeds = [PREFIX.as_bytes(), SIGNER.as_bytes()], bump = program_as_signer_bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: List several NFTs together for `price`, optionally until `expiry`. The remaining accounts hold a
+/// `[token_account, metadata, seller_trade_state, free_seller_trade_state]` group per item. Each item is listed
+/// through sell_logic at its allocation, which must not be 0, and flagged so it is only sold with the whole bundle.
+/// `allocations` split the price across the items for their fees and royalties and must sum to it.
+pub fn list_bundle<'info>(
+    ctx: Context<'_, '_, '_, 'info, ListBundle<'info>>,
+    program_as_signer_bump: u8,
+    _bundle_id: u64,
+    price: u64,
+    allocations: Vec<u64>,
+    expiry: Option<i64>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let wallet = &accounts.wallet;
+    let authority = &accounts.authority;
+    let auction_house = &accounts.auction_house;
+    let wallet_key = wallet.key();
+    let auction_house_key = auction_house.key();
+
+    // NOTE: If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    if auction_house.requires_sign_off && !authority.is_signer {
+        return Err(AuctionHouseError::CannotTakeThisActionWithoutAuctionHouseSignOff.into());
+    }
+
+    if allocations.is_empty()
+        || allocations.len() > MAX_BUNDLE_ITEMS
+        || ctx.remaining_accounts.len() != allocations.len() * BUNDLE_LIST_GROUP_SIZE
+    {
+        return Err(AuctionHouseError::InvalidBundle.into());
+    }
+
+    let mut allocated: u64 = 0;
+    let mut items: Vec<BundleItem> = Vec::with_capacity(allocations.len());
+
+    for (group, allocation) in ctx
+        .remaining_accounts
+        .chunks(BUNDLE_LIST_GROUP_SIZE)
+        .zip(allocations)
+    {
+        let token_account: Account<TokenAccount> = Account::try_from(&group[0])?;
+        let seller_trade_state = &group[2];
+        let free_seller_trade_state = &group[3];
+
+        // NOTE: a free item could only be sold with the authority or seller signing, and the top price is reserved
+        // for auctions
+        if allocation == 0
+            || allocation == AUCTION_LISTING_PRICE
+            || items.iter().any(|item| item.mint == token_account.mint)
+        {
+            return Err(AuctionHouseError::InvalidBundle.into());
+        }
+
+        // NOTE: a standing listing at the allocation would otherwise be taken over by the bundle
+        if !seller_trade_state.data_is_empty() {
+            return Err(AuctionHouseError::TradeStateAlreadyExists.into());
+        }
+
+        let trade_state_bump = assert_derivation(
+            &crate::id(),
+            seller_trade_state,
+            &[
+                PREFIX.as_bytes(),
+                wallet_key.as_ref(),
+                auction_house_key.as_ref(),
+                group[0].key.as_ref(),
+                auction_house.treasury_mint.as_ref(),
+                token_account.mint.as_ref(),
+                &allocation.to_le_bytes(),
+                &1u64.to_le_bytes(),
+            ],
+        )?;
+
+        let free_trade_state_bump = assert_derivation(
+            &crate::id(),
+            free_seller_trade_state,
+            &[
+                PREFIX.as_bytes(),
+                wallet_key.as_ref(),
+                auction_house_key.as_ref(),
+                group[0].key.as_ref(),
+                auction_house.treasury_mint.as_ref(),
+                token_account.mint.as_ref(),
+                &0u64.to_le_bytes(),
+                &1u64.to_le_bytes(),
+            ],
+        )?;
+
+        let item = BundleItem {
+            token_account: token_account.key(),
+            mint: token_account.mint,
+            allocation,
+        };
+
+        let mut sell_accounts = Sell {
+            wallet: UncheckedAccount::try_from(wallet.to_account_info()),
+            token_account: Box::new(token_account),
+            metadata: UncheckedAccount::try_from(group[1].clone()),
+            authority: authority.clone(),
+            auction_house: auction_house.clone(),
+            auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+            seller_trade_state: UncheckedAccount::try_from(seller_trade_state.clone()),
+            free_seller_trade_state: UncheckedAccount::try_from(free_seller_trade_state.clone()),
+            token_program: accounts.token_program.clone(),
+            system_program: accounts.system_program.clone(),
+            program_as_signer: accounts.program_as_signer.clone(),
+            rent: accounts.rent.clone(),
+        };
+
+        sell_logic(
+            &mut sell_accounts,
+            ctx.program_id,
+            trade_state_bump,
+            free_trade_state_bump,
+            program_as_signer_bump,
+            allocation,
+            1,
+            expiry,
+        )?;
+
+        bundle_trade_state(seller_trade_state)?;
+
+        allocated = allocated
+            .checked_add(allocation)
+            .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+        items.push(item);
+    }
+
+    if allocated != price {
+        return Err(AuctionHouseError::InvalidBundle.into());
+    }
+
+    let bundle = &mut ctx.accounts.bundle;
+    bundle.auction_house = ctx.accounts.auction_house.key();
+    bundle.seller = ctx.accounts.wallet.key();
+    bundle.price = price;
+    bundle.items = items;
+    bundle.bump = *ctx
+        .bumps
+        .get("bundle")
+        .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?;
+
+    Ok(())
+}
+
+/// Accounts for the [`buy_bundle` handler](auction_house/fn.buy_bundle.html).
+#[derive(Accounts)]
+#[instruction(escrow_payment_bump: u8, program_as_signer_bump: u8, bundle_id: u64)]
+pub struct BuyBundle<'info> {
+    /// Buyer's wallet account.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// User SOL or SPL account to transfer the bundle price from.
+    #[account(mut)]
+    pub payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// SPL token account transfer authority.
+    pub transfer_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the bundle.
+    /// Seller's wallet account, receiving the rent of the bundle account.
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    /// Auction House treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer escrow payment account PDA.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Seller SOL or SPL account to receive payment at.
+    #[account(mut)]
+    pub seller_payment_receipt_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the auction house.
+    /// Auction House instance authority.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_treasury,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance treasury account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            TREASURY.as_bytes()
+        ],
+        bump = auction_house.treasury_bump
+    )]
+    pub auction_house_treasury: UncheckedAccount<'info>,
+
+    /// Bundle account being bought, closed back to the seller.
+    #[account(
+        mut,
+        close = seller,
+        seeds = [
+            BUNDLE.as_bytes(),
+            auction_house.key().as_ref(),
+            seller.key().as_ref(),
+            &bundle_id.to_le_bytes()
+        ],
+        bump = bundle.bump,
+        has_one = auction_house,
+        has_one = seller
+    )]
+    pub bundle: Box<Account<'info, Bundle>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub ata_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    #[account(seeds = [PREFIX.as_bytes(), SIGNER.as_bytes()], bump = program_as_signer_bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: Buy a whole bundle atomically. Each item is bought at its allocation like a sweep buys a listing, through
+/// bid_logic and execute_sale_logic, so the item listings' expiry, the sale fees and the wallet stats apply per item.
+/// The remaining accounts hold, per item and in bundle order, `[token_account, token_mint, metadata,
+/// seller_trade_state, free_trade_state, buyer_trade_state, buyer_receipt_token_account]` followed by the creator
+/// accounts pay_creator_fees expects for that item, the buyer and seller wallet stats addresses and, with a
+/// `referrer`, its referral accounts as for execute_sale.
+pub fn buy_bundle<'info>(
+    ctx: Context<'_, '_, '_, 'info, BuyBundle<'info>>,
+    escrow_payment_bump: u8,
+    program_as_signer_bump: u8,
+    _bundle_id: u64,
+    referrer: Option<Pubkey>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let wallet = &accounts.wallet;
+    let auction_house = &accounts.auction_house;
+    let bundle = &accounts.bundle;
+    let remaining_accounts = ctx.remaining_accounts;
+
+    // NOTE: If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    let is_native = accounts.treasury_mint.key() == spl_token::native_mint::id();
+    let wallet_key = wallet.key();
+    let seller_key = accounts.seller.key();
+    let auction_house_key = auction_house.key();
+    let referral_accounts = if referrer.is_some() { REFERRAL_GROUP_SIZE } else { 0 };
+
+    let mut cursor = 0;
+    let mut emptied_trade_states = Vec::new();
+
+    for item in bundle.items.iter() {
+        let group = remaining_accounts
+            .get(cursor..cursor + BUNDLE_BUY_GROUP_SIZE)
+            .ok_or(AuctionHouseError::InvalidBundle)?;
+        let token_account = &group[0];
+        let token_mint = &group[1];
+        let metadata = &group[2];
+        let seller_trade_state = &group[3];
+        let free_trade_state = &group[4];
+        let buyer_trade_state = &group[5];
+        let buyer_receipt_token_account = &group[6];
+
+        assert_keys_equal(token_account.key(), item.token_account)?;
+        assert_keys_equal(token_mint.key(), item.mint)?;
+
+        let creator_accounts = get_creator_accounts_len(metadata, is_native)?;
+        let sale_accounts_start = cursor + BUNDLE_BUY_GROUP_SIZE;
+        let sale_accounts_end =
+            sale_accounts_start + creator_accounts + WALLET_STATS_GROUP_SIZE + referral_accounts;
+        let sale_accounts = remaining_accounts
+            .get(sale_accounts_start..sale_accounts_end)
+            .ok_or(AuctionHouseError::InvalidBundle)?;
+        cursor = sale_accounts_end;
+
+        // NOTE: the buyer trade state only lives for this purchase, a standing bid must not be consumed by it
+        if !buyer_trade_state.data_is_empty() {
+            return Err(AuctionHouseError::TradeStateAlreadyExists.into());
+        }
+
+        if let Some(referrer) = referrer {
+            let approved = get_approved_referrer(
+                &sale_accounts[creator_accounts + WALLET_STATS_GROUP_SIZE],
+                &auction_house_key,
+            )?;
+            assert_keys_equal(approved.wallet, referrer)?;
+        }
+
+        let buyer_trade_state_bump = assert_derivation(
+            &crate::id(),
+            buyer_trade_state,
+            &[
+                PREFIX.as_bytes(),
+                wallet_key.as_ref(),
+                auction_house_key.as_ref(),
+                token_account.key.as_ref(),
+                auction_house.treasury_mint.as_ref(),
+                item.mint.as_ref(),
+                &item.allocation.to_le_bytes(),
+                &1u64.to_le_bytes(),
+            ],
+        )?;
+
+        let free_trade_state_bump = assert_derivation(
+            &crate::id(),
+            free_trade_state,
+            &[
+                PREFIX.as_bytes(),
+                seller_key.as_ref(),
+                auction_house_key.as_ref(),
+                token_account.key.as_ref(),
+                auction_house.treasury_mint.as_ref(),
+                item.mint.as_ref(),
+                &0u64.to_le_bytes(),
+                &1u64.to_le_bytes(),
+            ],
+        )?;
+
+        bid_logic(
+            wallet.to_owned(),
+            accounts.payment_account.to_owned(),
+            accounts.transfer_authority.to_owned(),
+            *accounts.treasury_mint.to_owned(),
+            Account::try_from(token_account)?,
+            UncheckedAccount::try_from(metadata.clone()),
+            accounts.escrow_payment_account.to_owned(),
+            accounts.authority.to_owned(),
+            *auction_house.to_owned(),
+            accounts.auction_house_fee_account.to_owned(),
+            UncheckedAccount::try_from(buyer_trade_state.clone()),
+            accounts.token_program.to_owned(),
+            accounts.system_program.to_owned(),
+            accounts.rent.to_owned(),
+            buyer_trade_state_bump,
+            escrow_payment_bump,
+            item.allocation,
+            1,
+            false,
+            None,
+            referrer,
+        )?;
+
+        let mut execute_sale_accounts = ExecuteSale {
+            buyer: UncheckedAccount::try_from(wallet.to_account_info()),
+            seller: accounts.seller.clone(),
+            token_account: UncheckedAccount::try_from(token_account.clone()),
+            token_mint: UncheckedAccount::try_from(token_mint.clone()),
+            metadata: UncheckedAccount::try_from(metadata.clone()),
+            treasury_mint: UncheckedAccount::try_from(accounts.treasury_mint.to_account_info()),
+            escrow_payment_account: accounts.escrow_payment_account.clone(),
+            seller_payment_receipt_account: accounts.seller_payment_receipt_account.clone(),
+            buyer_receipt_token_account: UncheckedAccount::try_from(
+                buyer_receipt_token_account.clone(),
+            ),
+            authority: accounts.authority.clone(),
+            auction_house: auction_house.clone(),
+            auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+            auction_house_treasury: accounts.auction_house_treasury.clone(),
+            buyer_trade_state: UncheckedAccount::try_from(buyer_trade_state.clone()),
+            seller_trade_state: UncheckedAccount::try_from(seller_trade_state.clone()),
+            free_trade_state: UncheckedAccount::try_from(free_trade_state.clone()),
+            token_program: accounts.token_program.clone(),
+            system_program: accounts.system_program.clone(),
+            ata_program: accounts.ata_program.clone(),
+            program_as_signer: accounts.program_as_signer.clone(),
+            rent: accounts.rent.clone(),
+        };
+
+        execute_sale_logic(
+            Context::new(
+                ctx.program_id,
+                &mut execute_sale_accounts,
+                sale_accounts,
+                ctx.bumps.clone(),
+            ),
+            escrow_payment_bump,
+            free_trade_state_bump,
+            program_as_signer_bump,
+            item.allocation,
+            1,
+            ExecuteSaleOptions {
+                bundle: true,
+                defer_trade_state_rent: true,
+                ..Default::default()
+            },
+        )?;
+
+        emptied_trade_states.extend([
+            seller_trade_state.clone(),
+            free_trade_state.clone(),
+            buyer_trade_state.clone(),
+        ]);
+    }
+
+    if cursor != remaining_accounts.len() {
+        return Err(AuctionHouseError::InvalidBundle.into());
+    }
+
+    // NOTE: the rent of the emptied trade states is only collected once every sale ran its CPIs
+    if !emptied_trade_states.is_empty() {
+        let seeds = [
+            PREFIX.as_bytes(),
+            auction_house_key.as_ref(),
+            FEE_PAYER.as_bytes(),
+            &[auction_house.fee_payer_bump],
+        ];
+        let (fee_payer, _) = get_fee_payer(
+            &accounts.authority,
+            auction_house,
+            wallet.to_account_info(),
+            accounts.auction_house_fee_account.to_account_info(),
+            &seeds,
+        )?;
+
+        for trade_state in emptied_trade_states.iter() {
+            collect_trade_state_rent(trade_state, &fee_payer)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Accounts for the [`cancel_bundle` handler](auction_house/fn.cancel_bundle.html).
+#[derive(Accounts)]
+#[instruction(bundle_id: u64)]
+pub struct CancelBundle<'info> {
+    /// Seller wallet account, receiving the rent of the bundle account.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the auction house.
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// Bundle account being canceled.
+    #[account(
+        mut,
+        close = wallet,
+        seeds = [
+            BUNDLE.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref(),
+            &bundle_id.to_le_bytes()
+        ],
+        bump = bundle.bump,
+        has_one = auction_house,
+        constraint = bundle.seller == wallet.key()
+    )]
+    pub bundle: Box<Account<'info, Bundle>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// NOTE: Cancel a bundle listing. The remaining accounts hold, per item and in bundle order, `[token_account,
+/// token_mint, seller_trade_state]`, and each item listing still open is canceled through cancel_order_logic.
+pub fn cancel_bundle<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelBundle<'info>>,
+    _bundle_id: u64,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let bundle = &accounts.bundle;
+
+    if ctx.remaining_accounts.len() != bundle.items.len() * BUNDLE_CANCEL_GROUP_SIZE {
+        return Err(AuctionHouseError::InvalidBundle.into());
+    }
+
+    let mut canceled_trade_states = Vec::new();
+
+    for (item, group) in bundle
+        .items
+        .iter()
+        .zip(ctx.remaining_accounts.chunks(BUNDLE_CANCEL_GROUP_SIZE))
+    {
+        assert_keys_equal(group[0].key(), item.token_account)?;
+
+        // NOTE: an item listing already canceled on its own has nothing left to release
+        if group[2].data_is_empty() {
+            continue;
+        }
+
+        let mut cancel_accounts = Cancel {
+            wallet: UncheckedAccount::try_from(accounts.wallet.to_account_info()),
+            token_account: Box::new(Account::try_from(&group[0])?),
+            token_mint: Box::new(Account::try_from(&group[1])?),
+            authority: accounts.authority.clone(),
+            auction_house: accounts.auction_house.clone(),
+            auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+            trade_state: UncheckedAccount::try_from(group[2].clone()),
+            token_program: accounts.token_program.clone(),
+        };
+
+        let fee_payer = cancel_order_logic(&mut cancel_accounts, item.allocation, 1)?;
+        canceled_trade_states.push((group[2].clone(), fee_payer));
+    }
+
+    // NOTE: the rent of the canceled listings is only collected once every revoke ran
+    for (trade_state, fee_payer) in canceled_trade_states.iter() {
+        collect_trade_state_rent(trade_state, fee_payer)?;
+    }
+
+    Ok(())
+}
@@ -68,15 +68,130 @@ pub fn cancel<'info> (ctx: Context<'_, '_, '_, 'info, Cancel<'info>>, buyer_pric
         return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
     }
 
+    // NOTE: auction listings are released by their auction's cancel handler, which also closes the auction
+    if buyer_price == AUCTION_LISTING_PRICE {
+        return Err(AuctionHouseError::MustUseAuctionHandler.into());
+    }
+
     cancel_logic(ctx.accounts, buyer_price, token_size)
 }
 
+/// Accounts for the [`auctioneer_cancel` handler](auction_house/fn.auctioneer_cancel.html).
+#[derive(Accounts)]
+#[instruction(buyer_price : u64, token_size: u64)]
+pub struct AuctioneerCancel<'info> {
+    /// CHECK: Verified in cancel_logic
+    /// User wallet account
+    #[account(mut)]
+    pub wallet: UncheckedAccount<'info>,
+
+    /// SPL token account containing the token of the sale to be canceled
+    #[account(mut)]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Token mint account of SPL token
+    #[account(mut)]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Validated as a signer in cancel_logic.
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = auction_house_fee_account 
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in cancel_logic.
+    /// Trade state PDA account representing the bid or ask to be canceled.
+    #[account(mut)]
+    pub trade_state: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// The auctioneer authority - typically a PDA of the Auctioneer program running this action.
+    pub auctioneer_authority: Signer<'info>,
+
+    /// The auctioneer PDA owned by Auction House storing scopes.
+    #[account(
+        seeds = [
+            AUCTIONEER.as_bytes(),
+            auction_house.key().as_ref(),
+            auctioneer_authority.key().as_ref()
+        ],
+        bump = auction_house.auctioneer_pda_bump
+    )]
+    pub ah_auctioneer_pda: Box<Account<'info, Auctioneer>>,
+}
+
+pub fn auctioneer_cancel<'info> (ctx: Context<'_, '_, '_, 'info, AuctioneerCancel<'info>>, buyer_price: u64, token_size: u64) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    assert_valid_auctioneer_and_scope(
+        &accounts.auction_house,
+        &accounts.auctioneer_authority.key(),
+        &accounts.ah_auctioneer_pda,
+        AuthorityScope::Cancel,
+    )?;
+
+    // NOTE: auction listings are released by their auction's cancel handler, which also closes the auction
+    if buyer_price == AUCTION_LISTING_PRICE {
+        return Err(AuctionHouseError::MustUseAuctionHandler.into());
+    }
+
+    let mut cancel_accounts = Cancel {
+        wallet: accounts.wallet.clone(),
+        token_account: accounts.token_account.clone(),
+        token_mint: accounts.token_mint.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        trade_state: accounts.trade_state.clone(),
+        token_program: accounts.token_program.clone(),
+    };
+
+    cancel_logic(&mut cancel_accounts, buyer_price, token_size)
+}
+
 #[allow(clippy::needless_lifetimes)]
 pub fn cancel_logic<'info>(
     accounts: &mut Cancel<'info>,
     buyer_price: u64,
     token_size: u64
 ) -> Result<()> {
+    let fee_payer = cancel_order_logic(accounts, buyer_price, token_size)?;
+
+    collect_trade_state_rent(&accounts.trade_state, &fee_payer)
+}
+
+/// Cancel an order like cancel_logic but leave the trade state rent for the caller to collect after its last CPI,
+/// returning the fee payer it is owed to.
+pub fn cancel_order_logic<'info>(
+    accounts: &mut Cancel<'info>,
+    buyer_price: u64,
+    token_size: u64
+) -> Result<AccountInfo<'info>> {
     let wallet = &accounts.wallet;
     let token_account = &accounts.token_account;
     let token_mint = &accounts.token_mint;
@@ -99,6 +214,9 @@ pub fn cancel_logic<'info>(
         trade_state_bump
     )?;
 
+    // NOTE: a bid locked by an auction is released by the auction itself
+    assert_trade_state_not_locked(&trade_state.try_borrow_data()?)?;
+
     assert_keys_equal(token_mint.key(), token_account.mint)?;
 
     if !wallet.to_account_info().is_signer && !authority.to_account_info().is_signer {
@@ -139,11 +257,8 @@ pub fn cancel_logic<'info>(
         )?;
     }
 
-    let curr_lamp = trade_state.lamports();
-    **trade_state.lamports.borrow_mut() = 0;
-
-    **fee_payer.lamports.borrow_mut() = fee_payer.lamports().checked_add(curr_lamp).ok_or(AuctionHouseError::NumericalOverflow)?;
-    sol_memset(*trade_state.try_borrow_mut_data()?, 0, TRADE_STATE_SIZE);
+    let trade_state_len = trade_state.data_len();
+    sol_memset(*trade_state.try_borrow_mut_data()?, 0, trade_state_len);
 
-    Ok(())
+    Ok(fee_payer)
 }
\ No newline at end of file
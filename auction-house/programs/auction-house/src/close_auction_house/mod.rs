@@ -0,0 +1,380 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke_signed, system_instruction},
+    AccountsClose, AnchorDeserialize, Discriminator,
+};
+use anchor_spl::token::{Mint, Token};
+use solana_program::program_memory::sol_memset;
+
+use crate::constant::*;
+use crate::errors::AuctionHouseError;
+use crate::state::{
+    AuctionHouse, Bundle, CollectionOffer, DutchAuction, EnglishAuction, SealedAuction, SealedBid,
+    SwapOffer, WalletStats,
+};
+use crate::utils::{assert_initialized, assert_keys_equal, get_trade_state_data};
+
+/// Accounts for the [`close_auction_house` handler](auction_house/fn.close_auction_house.html).
+#[derive(Accounts)]
+pub struct CloseAuctionHouse<'info> {
+    /// Auction House instance treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// Authority key for the Auction House, receiving the rent of the closed accounts.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the auction house.
+    /// Account receiving the lamports left in the fee account.
+    #[account(mut)]
+    pub fee_withdrawal_destination: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the auction house.
+    /// SOL or SPL token account receiving the funds left in the treasury.
+    #[account(mut)]
+    pub treasury_withdrawal_destination: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance treasury PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            TREASURY.as_bytes()
+        ],
+        bump = auction_house.treasury_bump
+    )]
+    pub auction_house_treasury: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        close = authority,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = fee_withdrawal_destination,
+        has_one = treasury_withdrawal_destination,
+        has_one = auction_house_fee_account,
+        has_one = auction_house_treasury
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// NOTE: Sweep the treasury and fee account to their withdrawal destinations, close the SPL treasury and close the Auction House itself.
+/// Auction and sealed bid escrows can only be released through the house, so it stays open while any remain, and so
+/// do referrers, whose rent the authority reclaims with remove_referrer, and a revenue split, which the treasury is
+/// paid out through before remove_revenue_split. Buyer escrows can still be emptied with
+/// withdraw_from_closed_auction_house afterwards, and the remaining accounts of the house closed with
+/// close_account_of_closed_auction_house.
+pub fn close_auction_house<'info>(
+    ctx: Context<'_, '_, '_, 'info, CloseAuctionHouse<'info>>,
+) -> Result<()> {
+    // NOTE: extract all variables from ctx (CloseAuctionHouse struct)
+    let treasury_mint = &ctx.accounts.treasury_mint;
+    let authority = &ctx.accounts.authority;
+    let fee_withdrawal_destination = &ctx.accounts.fee_withdrawal_destination;
+    let treasury_withdrawal_destination = &ctx.accounts.treasury_withdrawal_destination;
+    let auction_house_fee_account = &ctx.accounts.auction_house_fee_account;
+    let auction_house_treasury = &ctx.accounts.auction_house_treasury;
+    let auction_house = &ctx.accounts.auction_house;
+    let token_program = &ctx.accounts.token_program;
+    let system_program = &ctx.accounts.system_program;
+
+    // NOTE: the auctioneer PDA would be orphaned, so it has to be revoked first
+    if auction_house.has_auctioneer {
+        return Err(AuctionHouseError::CannotCloseWithActiveAuctioneer.into());
+    }
+
+    if auction_house.open_auction_escrows > 0 {
+        return Err(AuctionHouseError::CannotCloseWithOpenAuctionEscrows.into());
+    }
+
+    // NOTE: referrer rent belongs to the authority, which can only reclaim it through the open house
+    if auction_house.approved_referrers > 0 {
+        return Err(AuctionHouseError::CannotCloseWithApprovedReferrers.into());
+    }
+
+    // NOTE: sweeping the treasury to its withdrawal destination would bypass the split and orphan its account
+    if auction_house.has_revenue_split {
+        return Err(AuctionHouseError::CannotCloseWithRevenueSplit.into());
+    }
+
+    let is_native = treasury_mint.key() == spl_token::native_mint::id();
+
+    let auction_house_key = auction_house.key();
+    let auction_house_seeds = [
+        PREFIX.as_bytes(),
+        auction_house.creator.as_ref(),
+        auction_house.treasury_mint.as_ref(),
+        &[auction_house.bump],
+    ];
+
+    let auction_house_treasury_seeds = [
+        PREFIX.as_bytes(),
+        auction_house_key.as_ref(),
+        TREASURY.as_bytes(),
+        &[auction_house.treasury_bump],
+    ];
+
+    let fee_payer_seeds = [
+        PREFIX.as_bytes(),
+        auction_house_key.as_ref(),
+        FEE_PAYER.as_bytes(),
+        &[auction_house.fee_payer_bump],
+    ];
+
+    // NOTE: sweep the treasury
+    if !is_native {
+        let treasury_account: spl_token::state::Account =
+            assert_initialized(&auction_house_treasury.to_account_info())?;
+
+        if treasury_account.amount > 0 {
+            invoke_signed(
+                &spl_token::instruction::transfer(
+                    token_program.key,
+                    &auction_house_treasury.key(),
+                    &treasury_withdrawal_destination.key(),
+                    &auction_house.key(),
+                    &[],
+                    treasury_account.amount,
+                )?,
+                &[
+                    auction_house_treasury.to_account_info(),
+                    treasury_withdrawal_destination.to_account_info(),
+                    token_program.to_account_info(),
+                    auction_house.to_account_info(),
+                ],
+                &[&auction_house_seeds],
+            )?;
+        }
+
+        // NOTE: the treasury token account rent goes back to the authority
+        invoke_signed(
+            &spl_token::instruction::close_account(
+                token_program.key,
+                &auction_house_treasury.key(),
+                &authority.key(),
+                &auction_house.key(),
+                &[],
+            )?,
+            &[
+                auction_house_treasury.to_account_info(),
+                authority.to_account_info(),
+                token_program.to_account_info(),
+                auction_house.to_account_info(),
+            ],
+            &[&auction_house_seeds],
+        )?;
+    } else if auction_house_treasury.lamports() > 0 {
+        invoke_signed(
+            &system_instruction::transfer(
+                &auction_house_treasury.key(),
+                &treasury_withdrawal_destination.key(),
+                auction_house_treasury.lamports(),
+            ),
+            &[
+                auction_house_treasury.to_account_info(),
+                treasury_withdrawal_destination.to_account_info(),
+                system_program.to_account_info(),
+            ],
+            &[&auction_house_treasury_seeds],
+        )?;
+    }
+
+    // NOTE: sweep the fee account
+    if auction_house_fee_account.lamports() > 0 {
+        invoke_signed(
+            &system_instruction::transfer(
+                &auction_house_fee_account.key(),
+                &fee_withdrawal_destination.key(),
+                auction_house_fee_account.lamports(),
+            ),
+            &[
+                auction_house_fee_account.to_account_info(),
+                fee_withdrawal_destination.to_account_info(),
+                system_program.to_account_info(),
+            ],
+            &[&fee_payer_seeds],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Accounts for the [`close_account_of_closed_auction_house` handler](auction_house/fn.close_account_of_closed_auction_house.html).
+#[derive(Accounts)]
+pub struct CloseAccountOfClosedAuctionHouse<'info> {
+    /// Treasury mint account of the closed Auction House.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Only used to derive the closed Auction House.
+    /// Creator of the closed Auction House.
+    pub creator: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint, and checked to be closed in close_account_of_closed_auction_house.
+    /// Closed Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            creator.key().as_ref(),
+            treasury_mint.key().as_ref()
+        ],
+        bump
+    )]
+    pub auction_house: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in close_account_of_closed_auction_house.
+    /// Trade state, wallet stats, offer or auction account of the closed Auction House.
+    #[account(mut)]
+    pub account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against the wallet recorded in the account.
+    /// Account that paid for the closed account, receiving its rent back.
+    #[account(mut)]
+    pub rent_destination: UncheckedAccount<'info>,
+}
+
+/// NOTE: Permissionlessly close an account left behind by a closed Auction House, refunding its rent to the wallet that
+/// paid for it. Versioned trade states go back to their recorded rent payer, or to their wallet when the house fee
+/// account paid, since nothing can withdraw from it anymore. Legacy trade states do not record their house and stay open.
+pub fn close_account_of_closed_auction_house<'info>(
+    ctx: Context<'_, '_, '_, 'info, CloseAccountOfClosedAuctionHouse<'info>>,
+) -> Result<()> {
+    let auction_house = &ctx.accounts.auction_house;
+    let account = ctx.accounts.account.to_account_info();
+    let rent_destination = ctx.accounts.rent_destination.to_account_info();
+
+    if !auction_house.data_is_empty() {
+        return Err(AuctionHouseError::AuctionHouseNotClosed.into());
+    }
+
+    if account.owner != ctx.program_id || account.data_is_empty() {
+        return Err(AuctionHouseError::InvalidClosedAuctionHouseAccount.into());
+    }
+
+    let auction_house_key = auction_house.key();
+    let discriminator: [u8; 8] = account.try_borrow_data()?[..]
+        .get(..8)
+        .and_then(|discriminator| discriminator.try_into().ok())
+        .unwrap_or_default();
+
+    match discriminator {
+        d if d == WalletStats::discriminator() => {
+            close_account::<WalletStats>(&account, &rent_destination, &auction_house_key, |stats| {
+                (stats.auction_house, stats.wallet)
+            })
+        }
+        d if d == CollectionOffer::discriminator() => {
+            close_account::<CollectionOffer>(&account, &rent_destination, &auction_house_key, |offer| {
+                (offer.auction_house, offer.buyer)
+            })
+        }
+        d if d == Bundle::discriminator() => {
+            close_account::<Bundle>(&account, &rent_destination, &auction_house_key, |bundle| {
+                (bundle.auction_house, bundle.seller)
+            })
+        }
+        d if d == SwapOffer::discriminator() => {
+            close_account::<SwapOffer>(&account, &rent_destination, &auction_house_key, |offer| {
+                (offer.auction_house, offer.maker)
+            })
+        }
+        d if d == DutchAuction::discriminator() => {
+            close_account::<DutchAuction>(&account, &rent_destination, &auction_house_key, |auction| {
+                (auction.auction_house, auction.seller)
+            })
+        }
+        d if d == EnglishAuction::discriminator() => {
+            close_account::<EnglishAuction>(&account, &rent_destination, &auction_house_key, |auction| {
+                (auction.auction_house, auction.seller)
+            })
+        }
+        d if d == SealedAuction::discriminator() => {
+            close_account::<SealedAuction>(&account, &rent_destination, &auction_house_key, |auction| {
+                (auction.auction_house, auction.seller)
+            })
+        }
+        d if d == SealedBid::discriminator() => {
+            close_account::<SealedBid>(&account, &rent_destination, &auction_house_key, |bid| {
+                (bid.auction_house, bid.bidder)
+            })
+        }
+        _ => {
+            let trade_state_data = get_trade_state_data(&account.try_borrow_data()?)
+                .ok_or(AuctionHouseError::InvalidClosedAuctionHouseAccount)?;
+            if trade_state_data.auction_house != auction_house_key {
+                return Err(AuctionHouseError::InvalidClosedAuctionHouseAccount.into());
+            }
+
+            let (fee_account, _) = Pubkey::find_program_address(
+                &[
+                    PREFIX.as_bytes(),
+                    auction_house_key.as_ref(),
+                    FEE_PAYER.as_bytes(),
+                ],
+                ctx.program_id,
+            );
+            let rent_recipient = if trade_state_data.fee_payer == fee_account {
+                trade_state_data.wallet
+            } else {
+                trade_state_data.fee_payer
+            };
+            assert_keys_equal(rent_destination.key(), rent_recipient)?;
+
+            // NOTE: get the current trade state lamport and set it to zero
+            let curr_lamp = account.lamports();
+            **account.lamports.borrow_mut() = 0;
+            let account_len = account.data_len();
+            sol_memset(*account.try_borrow_mut_data()?, 0, account_len);
+
+            // NOTE: add the trade state lamport to the original fee payer's account
+            **rent_destination.lamports.borrow_mut() = rent_destination
+                .lamports()
+                .checked_add(curr_lamp)
+                .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+            Ok(())
+        }
+    }
+}
+
+/// Closes an Anchor account of the closed Auction House to the wallet returned by `owners` next to its house.
+fn close_account<'info, T: AccountSerialize + AccountDeserialize + Owner + Clone>(
+    account: &AccountInfo<'info>,
+    rent_destination: &AccountInfo<'info>,
+    auction_house: &Pubkey,
+    owners: impl FnOnce(&T) -> (Pubkey, Pubkey),
+) -> Result<()> {
+    let account: Account<T> = Account::try_from(account)?;
+    let (account_auction_house, rent_recipient) = owners(&account);
+
+    if account_auction_house != *auction_house {
+        return Err(AuctionHouseError::InvalidClosedAuctionHouseAccount.into());
+    }
+    assert_keys_equal(rent_destination.key(), rent_recipient)?;
+
+    account.close(rent_destination.clone())
+}
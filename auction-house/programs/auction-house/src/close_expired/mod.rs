@@ -0,0 +1,100 @@
+use anchor_lang::{prelude::*, AnchorDeserialize};
+use solana_program::program_memory::sol_memset;
+
+use crate::constant::*;
+use crate::errors::AuctionHouseError;
+use crate::state::AuctionHouse;
+use crate::utils::{assert_keys_equal, assert_valid_trade_state, get_trade_state_expiry};
+
+/// Accounts for the [`close_expired` handler](auction_house/fn.close_expired.html).
+#[derive(Accounts)]
+#[instruction(buyer_price: u64, token_size: u64)]
+pub struct CloseExpired<'info> {
+    /// CHECK: Validated by the trade state derivation in close_expired.
+    /// Wallet that placed the expired order.
+    pub wallet: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the trade state derivation in close_expired.
+    /// SPL token account the order was placed on. Ignored for public bids.
+    pub token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the trade state derivation in close_expired.
+    /// Token mint account of the order.
+    pub token_mint: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Validated in close_expired.
+    /// Expired trade state PDA account.
+    #[account(mut)]
+    pub trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against the rent payer stored in the trade state.
+    /// Account that paid for the trade state, receiving its rent back.
+    #[account(mut)]
+    pub fee_payer: UncheckedAccount<'info>,
+}
+
+/// NOTE: Permissionlessly close an expired trade state, refunding its rent to whoever paid for it.
+pub fn close_expired<'info>(
+    ctx: Context<'_, '_, '_, 'info, CloseExpired<'info>>,
+    buyer_price: u64,
+    token_size: u64,
+) -> Result<()> {
+    let wallet = &ctx.accounts.wallet;
+    let token_account = &ctx.accounts.token_account;
+    let token_mint = &ctx.accounts.token_mint;
+    let auction_house = &ctx.accounts.auction_house;
+    let trade_state = &ctx.accounts.trade_state;
+    let fee_payer = &ctx.accounts.fee_payer;
+
+    if trade_state.data_is_empty() {
+        return Err(AuctionHouseError::UninitializedAccount.into());
+    }
+
+    let trade_state_bump = trade_state.try_borrow_data()?[0];
+
+    // NOTE: the derivation proves this is a trade state and not some other account of the program
+    assert_valid_trade_state(
+        &wallet.key(),
+        auction_house,
+        buyer_price,
+        token_size,
+        &trade_state.to_account_info(),
+        &token_mint.key(),
+        &token_account.key(),
+        trade_state_bump,
+    )?;
+
+    let (expiry, original_fee_payer) = get_trade_state_expiry(&trade_state.try_borrow_data()?)
+        .ok_or(AuctionHouseError::OrderNotExpired)?;
+
+    if Clock::get()?.unix_timestamp < expiry {
+        return Err(AuctionHouseError::OrderNotExpired.into());
+    }
+
+    assert_keys_equal(fee_payer.key(), original_fee_payer)?;
+
+    // NOTE: get the current trade state lamport and set it to zero
+    let curr_lamp = trade_state.lamports();
+    **trade_state.lamports.borrow_mut() = 0;
+    let trade_state_len = trade_state.data_len();
+    sol_memset(*trade_state.try_borrow_mut_data()?, 0, trade_state_len);
+
+    // NOTE: add the trade state lamport to the original fee payer's account
+    **fee_payer.lamports.borrow_mut() = fee_payer
+        .lamports()
+        .checked_add(curr_lamp)
+        .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+    Ok(())
+}
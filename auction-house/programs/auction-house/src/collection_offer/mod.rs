@@ -0,0 +1,575 @@
+use anchor_lang::{prelude::*, AccountsClose, AnchorDeserialize};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::constant::*;
+use crate::deposit::{deposit_logic, Deposit};
+use crate::errors::AuctionHouseError;
+use crate::execute_sale::{execute_sale_logic, ExecuteSale, ExecuteSaleOptions};
+use crate::sell::{sell_logic, Sell};
+use crate::state::{AuctionHouse, CollectionOffer, TradeSide, TradeStateData};
+use crate::utils::{
+    assert_metadata_valid, assert_verified_collection, compute_sale_fees,
+    create_or_allocate_account_raw, get_fee_payer, verify_merkle_proof, write_trade_state,
+};
+
+/// Accounts for the [`make_collection_offer` handler](auction_house/fn.make_collection_offer.html).
+#[derive(Accounts)]
+#[instruction(escrow_payment_bump: u8, collection: Pubkey, price: u64, quantity: u64, merkle_root: [u8; 32])]
+pub struct MakeCollectionOffer<'info> {
+    /// User wallet account, paying for the collection offer account.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// CHECK: Validated in deposit_logic.
+    /// User SOL or SPL account to transfer the offer funds from.
+    #[account(mut)]
+    pub payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in deposit_logic.
+    /// SPL token account transfer authority.
+    pub transfer_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer escrow payment account PDA.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// Auction House instance treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Validated in deposit_logic.
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// Collection offer account, one per buyer, collection, price and merkle root.
+    #[account(
+        init,
+        payer = wallet,
+        space = COLLECTION_OFFER_SIZE,
+        seeds = [
+            COLLECTION_OFFER.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref(),
+            collection.as_ref(),
+            &price.to_le_bytes(),
+            &merkle_root
+        ],
+        bump
+    )]
+    pub collection_offer: Box<Account<'info, CollectionOffer>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: Offer `price` for each of up to `quantity` NFTs of a verified collection, depositing `quantity` times the
+/// price and its maker fee into the escrow. A non-zero `merkle_root` restricts the offer to the mints of that tree,
+/// e.g. the items with a given trait.
+pub fn make_collection_offer<'info>(
+    ctx: Context<'_, '_, '_, 'info, MakeCollectionOffer<'info>>,
+    escrow_payment_bump: u8,
+    collection: Pubkey,
+    price: u64,
+    quantity: u64,
+    merkle_root: [u8; 32],
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    // NOTE: If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if accounts.auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    if quantity == 0 {
+        return Err(AuctionHouseError::InvalidTokenAmount.into());
+    }
+
+    // NOTE: the offer is always the maker of its fills
+    let amount = price
+        .checked_add(compute_sale_fees(&accounts.auction_house, price, false, 0, 0)?.buyer_fee)
+        .ok_or(AuctionHouseError::NumericalOverflow)?
+        .checked_mul(quantity)
+        .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+    let mut deposit_accounts = Deposit {
+        wallet: accounts.wallet.clone(),
+        payment_account: accounts.payment_account.clone(),
+        transfer_authority: accounts.transfer_authority.clone(),
+        escrow_payment_account: accounts.escrow_payment_account.clone(),
+        treasury_mint: accounts.treasury_mint.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        token_program: accounts.token_program.clone(),
+        system_program: accounts.system_program.clone(),
+        rent: accounts.rent.clone(),
+    };
+
+    deposit_logic(&mut deposit_accounts, escrow_payment_bump, amount)?;
+
+    let collection_offer = &mut ctx.accounts.collection_offer;
+    collection_offer.auction_house = ctx.accounts.auction_house.key();
+    collection_offer.buyer = ctx.accounts.wallet.key();
+    collection_offer.collection = collection;
+    collection_offer.price = price;
+    collection_offer.quantity = quantity;
+    collection_offer.merkle_root = merkle_root;
+    collection_offer.bump = *ctx
+        .bumps
+        .get("collection_offer")
+        .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?;
+
+    Ok(())
+}
+
+/// Accounts for the [`accept_collection_offer` handler](auction_house/fn.accept_collection_offer.html).
+#[derive(Accounts)]
+#[instruction(
+    trade_state_bump: u8,
+    free_trade_state_bump: u8,
+    buyer_trade_state_bump: u8,
+    escrow_payment_bump: u8,
+    program_as_signer_bump: u8
+)]
+pub struct AcceptCollectionOffer<'info> {
+    /// Seller's wallet account, paying for the trade states.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// SPL token account containing the token being sold.
+    #[account(mut)]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Token mint account for the SPL token.
+    pub token_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by assert_metadata_valid and assert_verified_collection.
+    /// Metaplex metadata account decorating SPL mint account.
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the collection offer.
+    /// Buyer's wallet account, receiving the rent of the collection offer once filled.
+    #[account(mut)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// Auction House treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer escrow payment account PDA.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            buyer.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Seller SOL or SPL account to receive payment at.
+    #[account(mut)]
+    pub seller_payment_receipt_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Buyer SOL or SPL account to receive the purchased item at.
+    #[account(mut)]
+    pub buyer_receipt_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in sell_logic and execute_sale_logic.
+    /// Auction House instance authority.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_treasury,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance treasury account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            TREASURY.as_bytes()
+        ],
+        bump = auction_house.treasury_bump
+    )]
+    pub auction_house_treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer trade state PDA account, a public bid on the mint at the offer price created for this fill.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            buyer.key().as_ref(),
+            auction_house.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_account.mint.as_ref(),
+            &collection_offer.price.to_le_bytes(),
+            &1u64.to_le_bytes()
+        ],
+        bump = buyer_trade_state_bump
+    )]
+    pub buyer_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Seller trade state PDA account encoding the listing at the offer price.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_account.mint.as_ref(),
+            &collection_offer.price.to_le_bytes(),
+            &1u64.to_le_bytes()
+        ],
+        bump = trade_state_bump
+    )]
+    pub seller_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Free seller trade state PDA account encoding a free sell order.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_account.mint.as_ref(),
+            &0u64.to_le_bytes(),
+            &1u64.to_le_bytes()
+        ],
+        bump = free_trade_state_bump
+    )]
+    pub free_seller_trade_state: UncheckedAccount<'info>,
+
+    /// Collection offer account being filled.
+    #[account(
+        mut,
+        seeds = [
+            COLLECTION_OFFER.as_bytes(),
+            auction_house.key().as_ref(),
+            buyer.key().as_ref(),
+            collection_offer.collection.as_ref(),
+            &collection_offer.price.to_le_bytes(),
+            &collection_offer.merkle_root
+        ],
+        bump = collection_offer.bump,
+        has_one = auction_house,
+        has_one = buyer
+    )]
+    pub collection_offer: Box<Account<'info, CollectionOffer>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub ata_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    #[account(seeds = [PREFIX.as_bytes(), SIGNER.as_bytes()], bump = program_as_signer_bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: Sell one NFT of the offer's collection into a collection offer, with a merkle `proof` of the mint for trait
+/// offers. The token is listed at the offer price and
+/// matched against a public bid created on the buyer's behalf, so the sale goes through execute_sale_logic with its
+/// royalties and fees.
+pub fn accept_collection_offer<'info>(
+    ctx: Context<'_, '_, '_, 'info, AcceptCollectionOffer<'info>>,
+    trade_state_bump: u8,
+    free_trade_state_bump: u8,
+    buyer_trade_state_bump: u8,
+    escrow_payment_bump: u8,
+    program_as_signer_bump: u8,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let auction_house = &accounts.auction_house;
+    let collection_offer = &accounts.collection_offer;
+    let price = collection_offer.price;
+
+    // NOTE: If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    if collection_offer.quantity == 0 {
+        return Err(AuctionHouseError::InvalidTokenAmount.into());
+    }
+
+    assert_metadata_valid(&accounts.metadata, &accounts.token_account)?;
+    assert_verified_collection(&accounts.metadata, &collection_offer.collection)?;
+
+    // NOTE: a trait offer only accepts the mints committed to by its merkle root
+    if collection_offer.merkle_root != [0; 32]
+        && !verify_merkle_proof(
+            &proof,
+            &collection_offer.merkle_root,
+            accounts.token_account.mint.as_ref(),
+        )
+    {
+        return Err(AuctionHouseError::InvalidMerkleProof.into());
+    }
+
+    let mut sell_accounts = Sell {
+        wallet: UncheckedAccount::try_from(accounts.wallet.to_account_info()),
+        token_account: accounts.token_account.clone(),
+        metadata: accounts.metadata.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        seller_trade_state: accounts.seller_trade_state.clone(),
+        free_seller_trade_state: accounts.free_seller_trade_state.clone(),
+        token_program: accounts.token_program.clone(),
+        system_program: accounts.system_program.clone(),
+        program_as_signer: accounts.program_as_signer.clone(),
+        rent: accounts.rent.clone(),
+    };
+
+    sell_logic(
+        &mut sell_accounts,
+        ctx.program_id,
+        trade_state_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        price,
+        1,
+        None,
+    )?;
+
+    // NOTE: the buyer trade state only lives for this fill, a standing public bid must not be consumed by it
+    let buyer_trade_state = &accounts.buyer_trade_state;
+    if !buyer_trade_state.data_is_empty() {
+        return Err(AuctionHouseError::TradeStateAlreadyExists.into());
+    }
+
+    let auction_house_key = auction_house.key();
+    let seeds = [
+        PREFIX.as_bytes(),
+        auction_house_key.as_ref(),
+        FEE_PAYER.as_bytes(),
+        &[auction_house.fee_payer_bump],
+    ];
+
+    let (fee_payer, fee_seeds) = get_fee_payer(
+        &accounts.authority,
+        auction_house,
+        accounts.wallet.to_account_info(),
+        accounts.auction_house_fee_account.to_account_info(),
+        &seeds,
+    )?;
+
+    let buyer_key = accounts.buyer.key();
+    let mint_key = accounts.token_account.mint;
+    create_or_allocate_account_raw(
+        crate::id(),
+        &buyer_trade_state.to_account_info(),
+        &accounts.rent.to_account_info(),
+        &accounts.system_program,
+        &fee_payer,
+        TRADE_STATE_SIZE,
+        fee_seeds,
+        &[
+            PREFIX.as_bytes(),
+            buyer_key.as_ref(),
+            auction_house_key.as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            mint_key.as_ref(),
+            &price.to_le_bytes(),
+            &1u64.to_le_bytes(),
+            &[buyer_trade_state_bump],
+        ],
+    )?;
+
+    let clock = Clock::get()?;
+    write_trade_state(
+        &buyer_trade_state.to_account_info(),
+        buyer_trade_state_bump,
+        &TradeStateData {
+            version: TRADE_STATE_VERSION,
+            side: TradeSide::Buy,
+            flags: TRADE_STATE_FLAG_PUBLIC | TRADE_STATE_FLAG_COLLECTION_OFFER,
+            auction_house: auction_house_key,
+            wallet: buyer_key,
+            token_account: Pubkey::default(),
+            mint: mint_key,
+            price,
+            token_size: 1,
+            created_slot: clock.slot,
+            created_at: clock.unix_timestamp,
+            expiry: 0,
+            fee_payer: fee_payer.key(),
+            referrer: Pubkey::default(),
+        },
+    )?;
+
+    let mut execute_sale_accounts = ExecuteSale {
+        buyer: accounts.buyer.clone(),
+        seller: UncheckedAccount::try_from(accounts.wallet.to_account_info()),
+        token_account: UncheckedAccount::try_from(accounts.token_account.to_account_info()),
+        token_mint: accounts.token_mint.clone(),
+        metadata: accounts.metadata.clone(),
+        treasury_mint: UncheckedAccount::try_from(accounts.treasury_mint.to_account_info()),
+        escrow_payment_account: accounts.escrow_payment_account.clone(),
+        seller_payment_receipt_account: accounts.seller_payment_receipt_account.clone(),
+        buyer_receipt_token_account: accounts.buyer_receipt_token_account.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        auction_house_treasury: accounts.auction_house_treasury.clone(),
+        buyer_trade_state: accounts.buyer_trade_state.clone(),
+        seller_trade_state: accounts.seller_trade_state.clone(),
+        free_trade_state: accounts.free_seller_trade_state.clone(),
+        token_program: accounts.token_program.clone(),
+        system_program: accounts.system_program.clone(),
+        ata_program: accounts.ata_program.clone(),
+        program_as_signer: accounts.program_as_signer.clone(),
+        rent: accounts.rent.clone(),
+    };
+
+    execute_sale_logic(
+        Context::new(
+            ctx.program_id,
+            &mut execute_sale_accounts,
+            ctx.remaining_accounts,
+            ctx.bumps.clone(),
+        ),
+        escrow_payment_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        price,
+        1,
+        ExecuteSaleOptions::default(),
+    )?;
+
+    let collection_offer = &mut ctx.accounts.collection_offer;
+    collection_offer.quantity = collection_offer
+        .quantity
+        .checked_sub(1)
+        .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+    // NOTE: a fully filled offer is closed back to the buyer, a revived offer has no quantity left to fill
+    if collection_offer.quantity == 0 {
+        collection_offer.close(ctx.accounts.buyer.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+/// Accounts for the [`cancel_collection_offer` handler](auction_house/fn.cancel_collection_offer.html).
+#[derive(Accounts)]
+pub struct CancelCollectionOffer<'info> {
+    /// Buyer's wallet account, receiving the rent of the collection offer.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// Collection offer account being canceled.
+    #[account(
+        mut,
+        close = wallet,
+        seeds = [
+            COLLECTION_OFFER.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref(),
+            collection_offer.collection.as_ref(),
+            &collection_offer.price.to_le_bytes(),
+            &collection_offer.merkle_root
+        ],
+        bump = collection_offer.bump,
+        has_one = auction_house,
+        constraint = collection_offer.buyer == wallet.key()
+    )]
+    pub collection_offer: Box<Account<'info, CollectionOffer>>,
+}
+
+/// NOTE: Cancel a collection offer. The deposited funds stay in the escrow and can be withdrawn.
+pub fn cancel_collection_offer<'info>(
+    _ctx: Context<'_, '_, '_, 'info, CancelCollectionOffer<'info>>,
+) -> Result<()> {
+    Ok(())
+}
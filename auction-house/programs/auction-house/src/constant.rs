@@ -7,9 +7,47 @@ pub const PURCHASE_RECEIPT_PREFIX: &str = "purchase_receipt";
 pub const BID_RECEIPT_PREFIX: &str = "bid_receipt";
 pub const LISTING_RECEIPT_PREFIX: &str = "listing_receipt";
 pub const AUCTIONEER: &str = "auctioneer";
+pub const ENGLISH_AUCTION: &str = "english_auction";
+pub const DUTCH_AUCTION: &str = "dutch_auction";
+pub const SEALED_AUCTION: &str = "sealed_auction";
+pub const SEALED_BID: &str = "sealed_bid";
+pub const AUCTION_ESCROW: &str = "auction_escrow";
+pub const COLLECTION_OFFER: &str = "collection_offer";
+pub const BUNDLE: &str = "bundle";
+pub const SWAP_OFFER: &str = "swap_offer";
+pub const WALLET_STATS: &str = "wallet_stats";
+pub const REFERRER: &str = "referrer";
+pub const REVENUE_SPLIT: &str = "revenue_split";
+
+// NOTE: auction listings are priced by their bids, so the seller trade state is seeded with this sentinel price.
+pub const AUCTION_LISTING_PRICE: u64 = u64::MAX;
 
 // NOTE: data sizes
-pub const TRADE_STATE_SIZE: usize = 1;
+pub const LEGACY_TRADE_STATE_SIZE: usize = 1;               // bump only
+pub const TRADE_STATE_VERSION: u8 = 1;
+pub const TRADE_STATE_SIZE: usize = 1 +                     // bump, first so legacy readers keep working
+1 +                                                         // version
+1 +                                                         // side
+1 +                                                         // flags
+32 +                                                        // auction_house
+32 +                                                        // wallet
+32 +                                                        // token_account
+32 +                                                        // mint
+8 +                                                         // price
+8 +                                                         // token_size
+8 +                                                         // created_slot
+8 +                                                         // created_at
+8 +                                                         // expiry
+32 +                                                        // fee_payer
+32                                                          // referrer, default when the bid has none
+;
+
+// NOTE: trade state flags
+pub const TRADE_STATE_FLAG_PUBLIC: u8 = 1 << 0;
+pub const TRADE_STATE_FLAG_MIGRATED: u8 = 1 << 1;
+pub const TRADE_STATE_FLAG_COLLECTION_OFFER: u8 = 1 << 2;
+pub const TRADE_STATE_FLAG_LOCKED: u8 = 1 << 3;
+pub const TRADE_STATE_FLAG_BUNDLE: u8 = 1 << 4;
 pub const MAX_NUM_SCOPES: usize = 7;
 pub const AUCTIONEER_SIZE: usize = 8 +                      // Anchor discriminator/sighash
 32 +                                                        // Auctioneer authority
@@ -35,5 +73,210 @@ pub const AUCTION_HOUSE_SIZE: usize = 8 +                   // key
 8 +                                                         // escrow payment bump
 1 +                                                         // has external auctioneer program as an authority
 8 +                                                         // auctioneer pda bump
-203                                                         // padding
+2 +                                                         // maker fee basis points, negative for a rebate
+2 +                                                         // taker fee basis points
+FEE_TIER_SIZE * MAX_FEE_TIERS +                             // fee tiers
+1 +                                                         // has revenue split, treasury withdrawals must go through it
+4 +                                                         // open auction escrows, the house cannot close while any remain
+4 +                                                         // approved referrers, the house cannot close while any remain
+150                                                         // padding
+;
+
+pub const LISTING_RECEIPT_SIZE: usize = 8 +                 // key
+32 +                                                        // trade_state
+32 +                                                        // bookkeeper
+32 +                                                        // auction_house
+32 +                                                        // seller
+32 +                                                        // metadata
+1 + 32 +                                                    // purchase_receipt
+8 +                                                         // price
+8 +                                                         // token_size
+1 +                                                         // bump
+1 +                                                         // trade_state_bump
+8 +                                                         // created_at
+1 + 8                                                       // canceled_at
+;
+
+pub const BID_RECEIPT_SIZE: usize = 8 +                     // key
+32 +                                                        // trade_state
+32 +                                                        // bookkeeper
+32 +                                                        // auction_house
+32 +                                                        // buyer
+32 +                                                        // metadata
+1 + 32 +                                                    // token_account
+1 + 32 +                                                    // purchase_receipt
+8 +                                                         // price
+8 +                                                         // token_size
+1 +                                                         // bump
+1 +                                                         // trade_state_bump
+8 +                                                         // created_at
+1 + 8                                                       // canceled_at
+;
+
+pub const PURCHASE_RECEIPT_SIZE: usize = 8 +                // key
+32 +                                                        // bookkeeper
+32 +                                                        // buyer
+32 +                                                        // seller
+32 +                                                        // auction_house
+32 +                                                        // metadata
+8 +                                                         // token_size
+8 +                                                         // price
+1 +                                                         // bump
+8                                                           // created_at
 ;
+
+pub const ENGLISH_AUCTION_SIZE: usize = 8 +                 // key
+32 +                                                        // auction_house
+32 +                                                        // seller
+32 +                                                        // token_account
+32 +                                                        // seller_trade_state
+8 +                                                         // token_size
+8 +                                                         // start_time
+8 +                                                         // end_time
+8 +                                                         // reserve_price
+8 +                                                         // min_bid_increment
+8 + 32 + 32 +                                               // highest_bid
+8 +                                                         // locked_amount
+1 +                                                         // bump
+1                                                           // escrow_bump
+;
+
+pub const DUTCH_AUCTION_SIZE: usize = 8 +                   // key
+32 +                                                        // auction_house
+32 +                                                        // seller
+32 +                                                        // token_account
+32 +                                                        // seller_trade_state
+8 +                                                         // token_size
+8 +                                                         // start_time
+8 +                                                         // end_time
+8 +                                                         // start_price
+8 +                                                         // floor_price
+8 +                                                         // step_interval
+1                                                           // bump
+;
+
+pub const SEALED_AUCTION_SIZE: usize = 8 +                  // key
+32 +                                                        // auction_house
+32 +                                                        // seller
+32 +                                                        // token_account
+32 +                                                        // seller_trade_state
+8 +                                                         // token_size
+8 +                                                         // bid_end_time
+8 +                                                         // reveal_end_time
+8 +                                                         // reserve_price
+1 +                                                         // vickrey
+8 + 32 + 32 +                                               // highest_bid
+8 +                                                         // second_price
+8 +                                                         // locked_amount
+1 +                                                         // bump
+1                                                           // escrow_bump
+;
+
+pub const SEALED_BID_SIZE: usize = 8 +                      // key
+32 +                                                        // auction_house
+32 +                                                        // sealed_auction
+32 +                                                        // bidder
+32 +                                                        // commitment
+8 +                                                         // collateral
+1 +                                                         // bump
+1                                                           // escrow_bump
+;
+
+pub const COLLECTION_OFFER_SIZE: usize = 8 +                // key
+32 +                                                        // auction_house
+32 +                                                        // buyer
+32 +                                                        // collection
+8 +                                                         // price
+8 +                                                         // quantity
+32 +                                                        // merkle_root
+1                                                           // bump
+;
+
+pub const MAX_BUNDLE_ITEMS: usize = 5;
+pub const BUNDLE_ITEM_SIZE: usize = 32 +                    // token_account
+32 +                                                        // mint
+8                                                           // allocation
+;
+pub const BUNDLE_SIZE: usize = 8 +                          // key
+32 +                                                        // auction_house
+32 +                                                        // seller
+8 +                                                         // price
+4 + BUNDLE_ITEM_SIZE * MAX_BUNDLE_ITEMS +                   // items
+1                                                           // bump
+;
+
+pub const SWAP_OFFER_SIZE: usize = 8 +                      // key
+32 +                                                        // auction_house
+32 +                                                        // maker
+32 +                                                        // offered_token_account
+32 +                                                        // offered_mint
+32 +                                                        // requested_mint
+8 +                                                         // cash_amount
+1                                                           // bump
+;
+
+// NOTE: volume based fee discounts, ordered by ascending minimum volume
+pub const MAX_FEE_TIERS: usize = 4;
+pub const FEE_TIER_SIZE: usize = 8 +                        // min_volume
+2                                                           // discount_basis_points
+;
+
+// NOTE: wallet volume is tracked over consecutive windows of this many seconds (30 days)
+pub const FEE_TIER_WINDOW: i64 = 30 * 24 * 60 * 60;
+
+pub const WALLET_STATS_SIZE: usize = 8 +                    // key
+32 +                                                        // auction_house
+32 +                                                        // wallet
+8 +                                                         // window_start
+8 +                                                         // current_volume
+8 +                                                         // previous_volume
+1                                                           // bump
+;
+
+pub const REFERRER_SIZE: usize = 8 +                        // key
+32 +                                                        // auction_house
+32 +                                                        // wallet
+2 +                                                         // share_basis_points
+1                                                           // bump
+;
+
+pub const MAX_REVENUE_RECIPIENTS: usize = 5;
+pub const REVENUE_RECIPIENT_SIZE: usize = 32 +              // wallet
+1                                                           // share
+;
+pub const REVENUE_SPLIT_SIZE: usize = 8 +                   // key
+32 +                                                        // auction_house
+4 + REVENUE_RECIPIENT_SIZE * MAX_REVENUE_RECIPIENTS +       // recipients
+1                                                           // bump
+;
+
+// NOTE: seller, token account, mint, metadata, seller/free/buyer trade states and both receipt accounts
+pub const SWEEP_GROUP_SIZE: usize = 9;
+
+// NOTE: token account, metadata and seller/free trade states of a listed bundle item
+pub const BUNDLE_LIST_GROUP_SIZE: usize = 4;
+
+// NOTE: token account, mint, metadata, seller/free/buyer trade states and buyer receipt account of a bought bundle item
+pub const BUNDLE_BUY_GROUP_SIZE: usize = 7;
+
+// NOTE: token account, mint and seller trade state of a canceled bundle item
+pub const BUNDLE_CANCEL_GROUP_SIZE: usize = 3;
+
+// NOTE: buyer and seller wallet stats addresses following the creator accounts of a sale
+pub const WALLET_STATS_GROUP_SIZE: usize = 2;
+
+// NOTE: referrer, referrer wallet and referrer payment account following the wallet stats addresses of a sale whose
+// bid records a referrer
+pub const REFERRAL_GROUP_SIZE: usize = 3;
+
+// NOTE: Anchor sighashes (first 8 bytes of sha256("global:<ix_name>")) used to introspect sibling instructions.
+pub const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+pub const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+pub const PUBLIC_BUY_DISCRIMINATOR: [u8; 8] = [169, 84, 218, 35, 42, 206, 16, 171];
+pub const EXECUTE_SALE_DISCRIMINATOR: [u8; 8] = [37, 74, 217, 157, 79, 49, 35, 6];
+pub const CANCEL_DISCRIMINATOR: [u8; 8] = [232, 219, 223, 41, 219, 236, 220, 190];
+pub const AUCTIONEER_SELL_DISCRIMINATOR: [u8; 8] = [251, 60, 142, 195, 121, 203, 26, 183];
+pub const AUCTIONEER_BUY_DISCRIMINATOR: [u8; 8] = [17, 106, 133, 46, 229, 48, 45, 208];
+pub const AUCTIONEER_PUBLIC_BUY_DISCRIMINATOR: [u8; 8] = [221, 239, 99, 240, 86, 46, 213, 126];
+pub const AUCTIONEER_EXECUTE_SALE_DISCRIMINATOR: [u8; 8] = [68, 125, 32, 65, 251, 43, 35, 53];
+pub const AUCTIONEER_CANCEL_DISCRIMINATOR: [u8; 8] = [197, 97, 152, 196, 115, 204, 64, 215];
@@ -0,0 +1,610 @@
+use anchor_lang::{prelude::*, AnchorDeserialize};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::bid::bid_logic;
+use crate::cancel::{cancel_logic, Cancel};
+use crate::constant::*;
+use crate::errors::AuctionHouseError;
+use crate::execute_sale::{execute_sale_logic, ExecuteSale, ExecuteSaleOptions};
+use crate::sell::{sell_logic, Sell};
+use crate::state::{AuctionHouse, DutchAuction};
+use crate::utils::{get_approved_referrer, get_creator_accounts_len};
+
+/// Accounts for the [`sell_dutch_auction` handler](auction_house/fn.sell_dutch_auction.html).
+#[derive(Accounts)]
+#[instruction(
+    trade_state_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    token_size: u64
+)]
+pub struct SellDutchAuction<'info> {
+    /// CHECK: Validated as a signer in sell_dutch_auction.
+    /// User wallet account, paying for the auction account.
+    #[account(mut)]
+    pub wallet: UncheckedAccount<'info>,
+
+    /// SPL token account containing token for sale
+    #[account(mut)]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated by assert_metadata_valid.
+    /// Metaplex metadata account decorating SPL mint account.
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated as a signer in sell_logic.
+    /// Auction House authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Seller trade state PDA account encoding the auction listing at the sentinel price.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_account.mint.as_ref(),
+            &AUCTION_LISTING_PRICE.to_le_bytes(),
+            &token_size.to_le_bytes()
+        ],
+        bump = trade_state_bump
+    )]
+    pub seller_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Free seller trade state PDA account encoding a free sell order.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_account.mint.as_ref(),
+            &0u64.to_le_bytes(),
+            &token_size.to_le_bytes()
+        ],
+        bump = free_trade_state_bump
+    )]
+    pub free_seller_trade_state: UncheckedAccount<'info>,
+
+    /// Dutch auction account holding the price schedule.
+    #[account(
+        init,
+        payer = wallet,
+        space = DUTCH_AUCTION_SIZE,
+        seeds = [
+            DUTCH_AUCTION.as_bytes(),
+            auction_house.key().as_ref(),
+            seller_trade_state.key().as_ref()
+        ],
+        bump
+    )]
+    pub dutch_auction: Box<Account<'info, DutchAuction>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    #[account(seeds = [PREFIX.as_bytes(), SIGNER.as_bytes()], bump = program_as_signer_bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: List `token_size` tokens in a Dutch auction. The listing goes through sell_logic at the sentinel price,
+/// so it can only be filled by buy_dutch_auction at the price computed from the schedule.
+pub fn sell_dutch_auction<'info>(
+    ctx: Context<'_, '_, '_, 'info, SellDutchAuction<'info>>,
+    trade_state_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    token_size: u64,
+    start_time: i64,
+    end_time: i64,
+    start_price: u64,
+    floor_price: u64,
+    step_interval: i64,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    // NOTE: If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if accounts.auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    // NOTE: the price schedule is the seller's, so the authority cannot list on its behalf
+    if !accounts.wallet.is_signer {
+        return Err(AuctionHouseError::SaleRequiresSigner.into());
+    }
+
+    let clock = Clock::get()?;
+    if start_time >= end_time || end_time <= clock.unix_timestamp || step_interval < 0 {
+        return Err(AuctionHouseError::InvalidAuctionTimes.into());
+    }
+
+    if floor_price == 0 || start_price <= floor_price {
+        return Err(AuctionHouseError::InvalidAuctionPrices.into());
+    }
+
+    // NOTE: every auction type lists at the same sentinel price, so a token account holds one auction at a time
+    if !accounts.seller_trade_state.data_is_empty() {
+        return Err(AuctionHouseError::TradeStateAlreadyExists.into());
+    }
+
+    let mut sell_accounts = Sell {
+        wallet: accounts.wallet.clone(),
+        token_account: accounts.token_account.clone(),
+        metadata: accounts.metadata.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        seller_trade_state: accounts.seller_trade_state.clone(),
+        free_seller_trade_state: accounts.free_seller_trade_state.clone(),
+        token_program: accounts.token_program.clone(),
+        system_program: accounts.system_program.clone(),
+        program_as_signer: accounts.program_as_signer.clone(),
+        rent: accounts.rent.clone(),
+    };
+
+    sell_logic(
+        &mut sell_accounts,
+        ctx.program_id,
+        trade_state_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        AUCTION_LISTING_PRICE,
+        token_size,
+        None,
+    )?;
+
+    let dutch_auction = &mut ctx.accounts.dutch_auction;
+    dutch_auction.auction_house = ctx.accounts.auction_house.key();
+    dutch_auction.seller = ctx.accounts.wallet.key();
+    dutch_auction.token_account = ctx.accounts.token_account.key();
+    dutch_auction.seller_trade_state = ctx.accounts.seller_trade_state.key();
+    dutch_auction.token_size = token_size;
+    dutch_auction.start_time = start_time;
+    dutch_auction.end_time = end_time;
+    dutch_auction.start_price = start_price;
+    dutch_auction.floor_price = floor_price;
+    dutch_auction.step_interval = step_interval;
+    dutch_auction.bump = *ctx
+        .bumps
+        .get("dutch_auction")
+        .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?;
+
+    Ok(())
+}
+
+/// Accounts for the [`buy_dutch_auction` handler](auction_house/fn.buy_dutch_auction.html).
+#[derive(Accounts)]
+#[instruction(
+    trade_state_bump: u8,
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    buyer_price: u64,
+    token_size: u64
+)]
+pub struct BuyDutchAuction<'info> {
+    /// Buyer's wallet account
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// User SOL or SPL account to transfer funds from.
+    #[account(mut)]
+    pub payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// SPL token account transfer authority.
+    pub transfer_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the Dutch auction.
+    /// Seller's wallet account, receiving the rent of the Dutch auction account.
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the Dutch auction.
+    /// Token account where the SPL token is being held
+    #[account(mut)]
+    pub token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Token mint account for the SPL token.
+    pub token_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic and execute_sale_logic.
+    /// Metaplex metaplex account holding extra infos.
+    pub metadata: UncheckedAccount<'info>,
+
+    /// Auction House treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer escrow payment account PDA.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Seller SOL or SPL account to receive payment at,
+    #[account(mut)]
+    pub seller_payment_receipt_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Buyer SOL or SPL account to receive the purchased item at.
+    #[account(mut)]
+    pub buyer_receipt_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic and execute_sale_logic.
+    /// Auction House instance authority
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_treasury,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Auction House instance fee account
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Auction House instance treasury account
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            TREASURY.as_bytes()
+        ],
+        bump = auction_house.treasury_bump
+    )]
+    pub auction_house_treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// Buyer trade state PDA account encoding the buyer's maximum price.
+    #[account(mut)]
+    pub buyer_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Seller trade state PDA account encoding the auction listing, derived from the Dutch auction.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            dutch_auction.seller.as_ref(),
+            auction_house.key().as_ref(),
+            dutch_auction.token_account.as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_mint.key().as_ref(),
+            &AUCTION_LISTING_PRICE.to_le_bytes(),
+            &dutch_auction.token_size.to_le_bytes()
+        ],
+        bump
+    )]
+    pub seller_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Free seller trade state PDA account encoding a free sell order.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            seller.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_mint.key().as_ref(),
+            &0u64.to_le_bytes(),
+            &token_size.to_le_bytes(),
+        ],
+        bump = free_trade_state_bump
+    )]
+    pub free_trade_state: UncheckedAccount<'info>,
+
+    /// Dutch auction account being filled, closed back to the seller.
+    #[account(
+        mut,
+        close = seller,
+        seeds = [
+            DUTCH_AUCTION.as_bytes(),
+            auction_house.key().as_ref(),
+            seller_trade_state.key().as_ref()
+        ],
+        bump = dutch_auction.bump,
+        has_one = auction_house,
+        has_one = seller,
+        has_one = token_account
+    )]
+    pub dutch_auction: Box<Account<'info, DutchAuction>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub ata_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            SIGNER.as_bytes()
+        ],
+        bump = program_as_signer_bump
+    )]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: Buy a Dutch auction listing at its current price in one step. The listing is derived from the seller, token
+/// account and size stored on the auction, and priced from its schedule rather than its sentinel price seed, which
+/// cannot encode a price that keeps changing. The buyer trade state encodes `buyer_price`, the most the buyer is
+/// willing to pay, so the purchase does not race the price schedule; any escrow above the current price stays
+/// withdrawable.
+pub fn buy_dutch_auction<'info>(
+    ctx: Context<'_, '_, '_, 'info, BuyDutchAuction<'info>>,
+    trade_state_bump: u8,
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let dutch_auction = &accounts.dutch_auction;
+
+    // NOTE: If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if accounts.auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    let clock = Clock::get()?;
+    if clock.unix_timestamp < dutch_auction.start_time {
+        return Err(AuctionHouseError::AuctionNotActive.into());
+    }
+
+    if token_size != dutch_auction.token_size {
+        return Err(AuctionHouseError::InvalidTokenAmount.into());
+    }
+
+    let current_price = dutch_auction.price_at(clock.unix_timestamp)?;
+    if current_price > buyer_price {
+        return Err(AuctionHouseError::AuctionPriceAboveBid.into());
+    }
+
+    let token_account: Account<TokenAccount> =
+        Account::try_from(&accounts.token_account.to_account_info())?;
+
+    // NOTE: the referral accounts of an approved referrer may follow the wallet stats addresses, as for execute_sale
+    let is_native = accounts.treasury_mint.key() == spl_token::native_mint::id();
+    let creator_accounts = get_creator_accounts_len(&accounts.metadata, is_native)?;
+    let referrer = match ctx.remaining_accounts.get(creator_accounts + WALLET_STATS_GROUP_SIZE..) {
+        None | Some([]) => None,
+        Some([referrer, _, _]) => Some(get_approved_referrer(referrer, &accounts.auction_house.key())?.wallet),
+        _ => return Err(AuctionHouseError::InvalidReferrer.into()),
+    };
+
+    bid_logic(
+        accounts.wallet.to_owned(),
+        accounts.payment_account.to_owned(),
+        accounts.transfer_authority.to_owned(),
+        *accounts.treasury_mint.to_owned(),
+        token_account,
+        accounts.metadata.to_owned(),
+        accounts.escrow_payment_account.to_owned(),
+        accounts.authority.to_owned(),
+        *accounts.auction_house.to_owned(),
+        accounts.auction_house_fee_account.to_owned(),
+        accounts.buyer_trade_state.to_owned(),
+        accounts.token_program.to_owned(),
+        accounts.system_program.to_owned(),
+        accounts.rent.to_owned(),
+        trade_state_bump,
+        escrow_payment_bump,
+        buyer_price,
+        token_size,
+        false,
+        None,
+        referrer,
+    )?;
+
+    let mut execute_sale_accounts = ExecuteSale {
+        buyer: UncheckedAccount::try_from(accounts.wallet.to_account_info()),
+        seller: accounts.seller.clone(),
+        token_account: accounts.token_account.clone(),
+        token_mint: accounts.token_mint.clone(),
+        metadata: accounts.metadata.clone(),
+        treasury_mint: UncheckedAccount::try_from(accounts.treasury_mint.to_account_info()),
+        escrow_payment_account: accounts.escrow_payment_account.clone(),
+        seller_payment_receipt_account: accounts.seller_payment_receipt_account.clone(),
+        buyer_receipt_token_account: accounts.buyer_receipt_token_account.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        auction_house_treasury: accounts.auction_house_treasury.clone(),
+        buyer_trade_state: accounts.buyer_trade_state.clone(),
+        seller_trade_state: accounts.seller_trade_state.clone(),
+        free_trade_state: accounts.free_trade_state.clone(),
+        token_program: accounts.token_program.clone(),
+        system_program: accounts.system_program.clone(),
+        ata_program: accounts.ata_program.clone(),
+        program_as_signer: accounts.program_as_signer.clone(),
+        rent: accounts.rent.clone(),
+    };
+
+    execute_sale_logic(
+        Context::new(
+            ctx.program_id,
+            &mut execute_sale_accounts,
+            ctx.remaining_accounts,
+            ctx.bumps.clone(),
+        ),
+        escrow_payment_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        AUCTION_LISTING_PRICE,
+        token_size,
+        ExecuteSaleOptions {
+            fill_size: Some(token_size),
+            fill_price: Some(current_price),
+            bid_price: Some(buyer_price),
+            ..Default::default()
+        },
+    )
+}
+
+/// Accounts for the [`cancel_dutch_auction` handler](auction_house/fn.cancel_dutch_auction.html).
+#[derive(Accounts)]
+pub struct CancelDutchAuction<'info> {
+    /// CHECK: Validated by the `constraint` on the Dutch auction.
+    /// Seller wallet account, receiving the rent of the Dutch auction account.
+    #[account(mut)]
+    pub wallet: UncheckedAccount<'info>,
+
+    /// SPL token account containing the auctioned token.
+    #[account(mut)]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Token mint account of SPL token
+    #[account(mut)]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Validated as a signer in cancel_logic.
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in cancel_logic.
+    /// Seller trade state PDA account encoding the auction listing.
+    #[account(mut)]
+    pub trade_state: UncheckedAccount<'info>,
+
+    /// Dutch auction account being canceled, closed back to the seller.
+    #[account(
+        mut,
+        close = wallet,
+        seeds = [
+            DUTCH_AUCTION.as_bytes(),
+            auction_house.key().as_ref(),
+            trade_state.key().as_ref()
+        ],
+        bump = dutch_auction.bump,
+        has_one = auction_house,
+        has_one = token_account,
+        constraint = dutch_auction.seller == wallet.key()
+    )]
+    pub dutch_auction: Box<Account<'info, DutchAuction>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// NOTE: Cancel a Dutch auction, revoking the listing and closing the auction account. Works whether or not an
+/// auctioneer was delegated since the auction started.
+pub fn cancel_dutch_auction<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelDutchAuction<'info>>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    let mut cancel_accounts = Cancel {
+        wallet: accounts.wallet.clone(),
+        token_account: accounts.token_account.clone(),
+        token_mint: accounts.token_mint.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        trade_state: accounts.trade_state.clone(),
+        token_program: accounts.token_program.clone(),
+    };
+
+    cancel_logic(
+        &mut cancel_accounts,
+        AUCTION_LISTING_PRICE,
+        accounts.dutch_auction.token_size,
+    )
+}
@@ -0,0 +1,1044 @@
+use anchor_lang::{prelude::*, AnchorDeserialize};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::bid::bid_logic;
+use crate::cancel::{cancel_logic, Cancel};
+use crate::constant::*;
+use crate::errors::AuctionHouseError;
+use crate::execute_sale::{execute_sale_logic, ExecuteSale, ExecuteSaleOptions};
+use crate::sell::{sell_logic, Sell};
+use crate::state::{AuctionHouse, EnglishAuction, HighestBid};
+use crate::utils::{
+    assert_keys_equal, bid_escrow_amount, close_auction_escrow, collect_trade_state_rent,
+    create_auction_escrow, is_listing_fillable, lock_trade_state, release_locked_bid,
+    transfer_from_escrow,
+};
+
+/// Accounts for the [`sell_english_auction` handler](auction_house/fn.sell_english_auction.html).
+#[derive(Accounts)]
+#[instruction(
+    trade_state_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    token_size: u64
+)]
+pub struct SellEnglishAuction<'info> {
+    /// CHECK: Validated as a signer in sell_english_auction.
+    /// User wallet account, paying for the auction account.
+    #[account(mut)]
+    pub wallet: UncheckedAccount<'info>,
+
+    /// SPL token account containing token for sale
+    #[account(mut)]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated by assert_metadata_valid.
+    /// Metaplex metadata account decorating SPL mint account.
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated as a signer in sell_logic.
+    /// Auction House authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Seller trade state PDA account encoding the auction listing at the sentinel price.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_account.mint.as_ref(),
+            &AUCTION_LISTING_PRICE.to_le_bytes(),
+            &token_size.to_le_bytes()
+        ],
+        bump = trade_state_bump
+    )]
+    pub seller_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Free seller trade state PDA account encoding a free sell order.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_account.mint.as_ref(),
+            &0u64.to_le_bytes(),
+            &token_size.to_le_bytes()
+        ],
+        bump = free_trade_state_bump
+    )]
+    pub free_seller_trade_state: UncheckedAccount<'info>,
+
+    /// English auction account holding the auction terms and the current highest bid.
+    #[account(
+        init,
+        payer = wallet,
+        space = ENGLISH_AUCTION_SIZE,
+        seeds = [
+            ENGLISH_AUCTION.as_bytes(),
+            auction_house.key().as_ref(),
+            seller_trade_state.key().as_ref()
+        ],
+        bump
+    )]
+    pub english_auction: Box<Account<'info, EnglishAuction>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction escrow PDA locking the funds of the highest bid, opened at the seller's expense.
+    #[account(
+        mut,
+        seeds = [
+            AUCTION_ESCROW.as_bytes(),
+            english_auction.key().as_ref()
+        ],
+        bump
+    )]
+    pub auction_escrow: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    #[account(seeds = [PREFIX.as_bytes(), SIGNER.as_bytes()], bump = program_as_signer_bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: List `token_size` tokens in a timed English auction. The listing goes through sell_logic at the sentinel price,
+/// so it can only be filled by settle_english_auction. The seller pays for the auction escrow locking the highest bid.
+pub fn sell_english_auction<'info>(
+    ctx: Context<'_, '_, '_, 'info, SellEnglishAuction<'info>>,
+    trade_state_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    token_size: u64,
+    start_time: i64,
+    end_time: i64,
+    reserve_price: u64,
+    min_bid_increment: u64,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    // NOTE: If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if accounts.auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    // NOTE: the auction terms are the seller's, so the authority cannot list on its behalf
+    if !accounts.wallet.is_signer {
+        return Err(AuctionHouseError::SaleRequiresSigner.into());
+    }
+
+    let clock = Clock::get()?;
+    if start_time >= end_time || end_time <= clock.unix_timestamp {
+        return Err(AuctionHouseError::InvalidAuctionTimes.into());
+    }
+
+    if min_bid_increment == 0 {
+        return Err(AuctionHouseError::InvalidBidIncrement.into());
+    }
+
+    // NOTE: every auction type lists at the same sentinel price, so a token account holds one auction at a time
+    if !accounts.seller_trade_state.data_is_empty() {
+        return Err(AuctionHouseError::TradeStateAlreadyExists.into());
+    }
+
+    let mut sell_accounts = Sell {
+        wallet: accounts.wallet.clone(),
+        token_account: accounts.token_account.clone(),
+        metadata: accounts.metadata.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        seller_trade_state: accounts.seller_trade_state.clone(),
+        free_seller_trade_state: accounts.free_seller_trade_state.clone(),
+        token_program: accounts.token_program.clone(),
+        system_program: accounts.system_program.clone(),
+        program_as_signer: accounts.program_as_signer.clone(),
+        rent: accounts.rent.clone(),
+    };
+
+    sell_logic(
+        &mut sell_accounts,
+        ctx.program_id,
+        trade_state_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        AUCTION_LISTING_PRICE,
+        token_size,
+        None,
+    )?;
+
+    let is_native = accounts.treasury_mint.key() == spl_token::native_mint::id();
+    let english_auction_key = accounts.english_auction.key();
+    let escrow_bump = *ctx
+        .bumps
+        .get("auction_escrow")
+        .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?;
+    let auction_escrow_seeds = [
+        AUCTION_ESCROW.as_bytes(),
+        english_auction_key.as_ref(),
+        &[escrow_bump],
+    ];
+
+    create_auction_escrow(
+        &accounts.auction_escrow,
+        &accounts.wallet.to_account_info(),
+        &accounts.treasury_mint,
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program,
+        &accounts.system_program,
+        &accounts.rent,
+        &auction_escrow_seeds,
+        is_native,
+    )?;
+    ctx.accounts.auction_house.record_open_escrow()?;
+
+    let english_auction = &mut ctx.accounts.english_auction;
+    english_auction.auction_house = ctx.accounts.auction_house.key();
+    english_auction.seller = ctx.accounts.wallet.key();
+    english_auction.token_account = ctx.accounts.token_account.key();
+    english_auction.seller_trade_state = ctx.accounts.seller_trade_state.key();
+    english_auction.token_size = token_size;
+    english_auction.start_time = start_time;
+    english_auction.end_time = end_time;
+    english_auction.reserve_price = reserve_price;
+    english_auction.min_bid_increment = min_bid_increment;
+    english_auction.highest_bid = HighestBid::default();
+    english_auction.locked_amount = 0;
+    english_auction.bump = *ctx
+        .bumps
+        .get("english_auction")
+        .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?;
+    english_auction.escrow_bump = escrow_bump;
+
+    Ok(())
+}
+
+/// Accounts for the [`bid_english_auction` handler](auction_house/fn.bid_english_auction.html).
+#[derive(Accounts)]
+#[instruction(
+    trade_state_bump: u8,
+    escrow_payment_bump: u8,
+    buyer_price: u64,
+    token_size: u64
+)]
+pub struct BidEnglishAuction<'info> {
+    /// User wallet account
+    pub wallet: Signer<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// User SOL or SPL account to transfer funds from.
+    #[account(mut)]
+    pub payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// SPL token account transfer authority.
+    pub transfer_authority: UncheckedAccount<'info>,
+
+    /// Auction House instance treasury mint account.
+    pub treasury_mint: Account<'info, Mint>,
+
+    /// SPL token account being auctioned.
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated in bid_logic.
+    /// SPL token account metadata.
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer escrow payment account PDA.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer trade state PDA.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            treasury_mint.key().as_ref(),
+            token_account.mint.as_ref(),
+            buyer_price.to_le_bytes().as_ref(),
+            token_size.to_le_bytes().as_ref()
+        ],
+        bump = trade_state_bump
+    )]
+    pub buyer_trade_state: UncheckedAccount<'info>,
+
+    /// English auction account holding the auction terms and the current highest bid.
+    #[account(
+        mut,
+        seeds = [
+            ENGLISH_AUCTION.as_bytes(),
+            auction_house.key().as_ref(),
+            english_auction.seller_trade_state.as_ref()
+        ],
+        bump = english_auction.bump,
+        has_one = auction_house,
+        has_one = token_account
+    )]
+    pub english_auction: Box<Account<'info, EnglishAuction>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction escrow PDA locking the funds of the highest bid.
+    #[account(
+        mut,
+        seeds = [
+            AUCTION_ESCROW.as_bytes(),
+            english_auction.key().as_ref()
+        ],
+        bump = english_auction.escrow_bump
+    )]
+    pub auction_escrow: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by derivation from the highest bid in bid_english_auction.
+    /// Escrow payment account of the bidder being outbid, refunded its locked funds. Ignored for the first bid.
+    #[account(mut)]
+    pub previous_bidder_escrow_payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against the highest bid in bid_english_auction.
+    /// Trade state of the bid being outbid. Ignored for the first bid.
+    #[account(mut)]
+    pub previous_bidder_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against the outbid trade state in bid_english_auction.
+    /// Account that paid for the outbid trade state, receiving its rent. Ignored for the first bid.
+    #[account(mut)]
+    pub previous_trade_state_fee_payer: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: Place a bid on a running English auction. The bid must meet the reserve price and beat the highest bid by the
+/// minimum increment. Its funds move from the buyer escrow to the auction escrow and its trade state is locked until the
+/// auction releases it, while the outbid bid is refunded to its escrow and its trade state closed.
+pub fn bid_english_auction<'info>(
+    ctx: Context<'_, '_, '_, 'info, BidEnglishAuction<'info>>,
+    trade_state_bump: u8,
+    escrow_payment_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let english_auction = &accounts.english_auction;
+
+    // NOTE: If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if accounts.auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    let clock = Clock::get()?;
+    if clock.unix_timestamp < english_auction.start_time
+        || clock.unix_timestamp >= english_auction.end_time
+    {
+        return Err(AuctionHouseError::AuctionNotActive.into());
+    }
+
+    if token_size != english_auction.token_size {
+        return Err(AuctionHouseError::InvalidTokenAmount.into());
+    }
+
+    let highest_bid = english_auction.highest_bid.clone();
+    let min_price = if highest_bid.amount == 0 {
+        std::cmp::max(english_auction.reserve_price, 1)
+    } else {
+        highest_bid
+            .amount
+            .checked_add(english_auction.min_bid_increment)
+            .ok_or(AuctionHouseError::NumericalOverflow)?
+    };
+
+    if buyer_price < min_price {
+        return Err(AuctionHouseError::BidTooLow.into());
+    }
+
+    // NOTE: the locked trade state belongs to the auction, so it cannot be an order placed beforehand
+    if !accounts.buyer_trade_state.data_is_empty() {
+        return Err(AuctionHouseError::TradeStateAlreadyExists.into());
+    }
+
+    let is_native = accounts.treasury_mint.key() == spl_token::native_mint::id();
+    let auction_house_key = accounts.auction_house.key();
+    let english_auction_key = english_auction.key();
+    let auction_escrow_seeds = [
+        AUCTION_ESCROW.as_bytes(),
+        english_auction_key.as_ref(),
+        &[english_auction.escrow_bump],
+    ];
+    let auction_house_seeds = [
+        PREFIX.as_bytes(),
+        accounts.auction_house.creator.as_ref(),
+        accounts.auction_house.treasury_mint.as_ref(),
+        &[accounts.auction_house.bump],
+    ];
+
+    if highest_bid.amount > 0 {
+        release_locked_bid(
+            &highest_bid,
+            english_auction.locked_amount,
+            &auction_house_key,
+            &accounts.auction_house.to_account_info(),
+            &accounts.auction_escrow.to_account_info(),
+            &accounts.previous_bidder_escrow_payment_account.to_account_info(),
+            &accounts.previous_bidder_trade_state.to_account_info(),
+            &accounts.previous_trade_state_fee_payer.to_account_info(),
+            &accounts.token_program.to_account_info(),
+            &accounts.system_program.to_account_info(),
+            &auction_escrow_seeds,
+            &auction_house_seeds,
+            is_native,
+        )?;
+    }
+
+    bid_logic(
+        accounts.wallet.to_owned(),
+        accounts.payment_account.to_owned(),
+        accounts.transfer_authority.to_owned(),
+        accounts.treasury_mint.to_owned(),
+        *accounts.token_account.to_owned(),
+        accounts.metadata.to_owned(),
+        accounts.escrow_payment_account.to_owned(),
+        accounts.authority.to_owned(),
+        *accounts.auction_house.to_owned(),
+        accounts.auction_house_fee_account.to_owned(),
+        accounts.buyer_trade_state.to_owned(),
+        accounts.token_program.to_owned(),
+        accounts.system_program.to_owned(),
+        accounts.rent.to_owned(),
+        trade_state_bump,
+        escrow_payment_bump,
+        buyer_price,
+        token_size,
+        false,
+        None,
+        None,
+    )?;
+
+    // NOTE: lock what bid_logic requires in escrow, so the bid stays funded until the auction releases it
+    let locked_amount = bid_escrow_amount(&accounts.auction_house, buyer_price)?;
+    let wallet_key = accounts.wallet.key();
+    let escrow_signer_seeds = [
+        PREFIX.as_bytes(),
+        auction_house_key.as_ref(),
+        wallet_key.as_ref(),
+        &[escrow_payment_bump],
+    ];
+
+    transfer_from_escrow(
+        &accounts.escrow_payment_account.to_account_info(),
+        &accounts.auction_escrow.to_account_info(),
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        &escrow_signer_seeds,
+        &auction_house_seeds,
+        locked_amount,
+        is_native,
+    )?;
+
+    lock_trade_state(&accounts.buyer_trade_state.to_account_info())?;
+
+    // NOTE: the outbid trade state pays its rent back after the CPIs above, which its fee payer may be part of
+    if highest_bid.amount > 0 {
+        collect_trade_state_rent(
+            &accounts.previous_bidder_trade_state.to_account_info(),
+            &accounts.previous_trade_state_fee_payer.to_account_info(),
+        )?;
+    }
+
+    let buyer_trade_state_key = ctx.accounts.buyer_trade_state.key();
+    let english_auction = &mut ctx.accounts.english_auction;
+    english_auction.highest_bid = HighestBid {
+        amount: buyer_price,
+        buyer: wallet_key,
+        buyer_trade_state: buyer_trade_state_key,
+    };
+    english_auction.locked_amount = locked_amount;
+
+    Ok(())
+}
+
+/// Accounts for the [`settle_english_auction` handler](auction_house/fn.settle_english_auction.html).
+#[derive(Accounts)]
+#[instruction(
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    token_size: u64
+)]
+pub struct SettleEnglishAuction<'info> {
+    /// CHECK: Validated against the highest bid in settle_english_auction.
+    /// Buyer's wallet account
+    #[account(mut)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the English auction.
+    /// Seller's wallet account, receiving the rent of the English auction account.
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the English auction.
+    /// Token account where the SPL token is being held
+    #[account(mut)]
+    pub token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Token mint account for the SPL token.
+    pub token_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Metaplex metaplex account holding extra infos.
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Auction House treasury mint account.
+    pub treasury_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. validate via seeds check.
+    /// Buyer escrow payment account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            buyer.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Seller SOL or SPL account to receive payment at,
+    #[account(mut)]
+    pub seller_payment_receipt_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Buyer SOL or SPL account to receive the purchased item at.
+    #[account(mut)]
+    pub buyer_receipt_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Auction House instance authority
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_treasury,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Auction House instance fee account
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Auction House instance treasury account
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            TREASURY.as_bytes()
+        ],
+        bump = auction_house.treasury_bump
+    )]
+    pub auction_house_treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against the highest bid in settle_english_auction.
+    /// Buyer trade state PDA account encoding the winning bid.
+    #[account(mut)]
+    pub buyer_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the English auction seeds.
+    /// Seller trade state PDA account encoding the auction listing.
+    #[account(mut)]
+    pub seller_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Free seller trade state PDA account encoding a free sell order.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            seller.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_mint.key().as_ref(),
+            &0u64.to_le_bytes(),
+            &token_size.to_le_bytes(),
+        ],
+        bump = free_trade_state_bump
+    )]
+    pub free_trade_state: UncheckedAccount<'info>,
+
+    /// English auction account being settled, closed back to the seller.
+    #[account(
+        mut,
+        close = seller,
+        seeds = [
+            ENGLISH_AUCTION.as_bytes(),
+            auction_house.key().as_ref(),
+            seller_trade_state.key().as_ref()
+        ],
+        bump = english_auction.bump,
+        has_one = auction_house,
+        has_one = seller,
+        has_one = token_account
+    )]
+    pub english_auction: Box<Account<'info, EnglishAuction>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction escrow PDA locking the funds of the highest bid, closed back to the seller.
+    #[account(
+        mut,
+        seeds = [
+            AUCTION_ESCROW.as_bytes(),
+            english_auction.key().as_ref()
+        ],
+        bump = english_auction.escrow_bump
+    )]
+    pub auction_escrow: UncheckedAccount<'info>,
+
+    /// Anyone cranking the settlement, paying for any account it creates.
+    #[account(mut)]
+    pub settler: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub ata_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            SIGNER.as_bytes()
+        ],
+        bump = program_as_signer_bump
+    )]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: Permissionlessly settle an ended English auction. The locked funds of the highest bid return to the buyer escrow
+/// and are paid out through execute_sale_logic, then the auction escrow is closed. Auctions started before an
+/// auctioneer was delegated still settle here, since the auctioneer cannot settle them.
+pub fn settle_english_auction<'info>(
+    ctx: Context<'_, '_, '_, 'info, SettleEnglishAuction<'info>>,
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    token_size: u64,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let english_auction = &accounts.english_auction;
+
+    let clock = Clock::get()?;
+    if clock.unix_timestamp < english_auction.end_time {
+        return Err(AuctionHouseError::AuctionNotEnded.into());
+    }
+
+    let highest_bid = english_auction.highest_bid.clone();
+    if highest_bid.amount == 0 {
+        return Err(AuctionHouseError::NoWinningBid.into());
+    }
+
+    assert_keys_equal(accounts.buyer.key(), highest_bid.buyer)?;
+    assert_keys_equal(
+        accounts.buyer_trade_state.key(),
+        highest_bid.buyer_trade_state,
+    )?;
+
+    if token_size != english_auction.token_size {
+        return Err(AuctionHouseError::InvalidTokenAmount.into());
+    }
+
+    let is_native = accounts.treasury_mint.key() == spl_token::native_mint::id();
+    let english_auction_key = english_auction.key();
+    let auction_escrow_seeds = [
+        AUCTION_ESCROW.as_bytes(),
+        english_auction_key.as_ref(),
+        &[english_auction.escrow_bump],
+    ];
+    let auction_house_seeds = [
+        PREFIX.as_bytes(),
+        accounts.auction_house.creator.as_ref(),
+        accounts.auction_house.treasury_mint.as_ref(),
+        &[accounts.auction_house.bump],
+    ];
+
+    transfer_from_escrow(
+        &accounts.auction_escrow.to_account_info(),
+        &accounts.escrow_payment_account.to_account_info(),
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        &auction_escrow_seeds,
+        &auction_house_seeds,
+        english_auction.locked_amount,
+        is_native,
+    )?;
+
+    let mut execute_sale_accounts = ExecuteSale {
+        buyer: accounts.buyer.clone(),
+        seller: accounts.seller.clone(),
+        token_account: accounts.token_account.clone(),
+        token_mint: accounts.token_mint.clone(),
+        metadata: accounts.metadata.clone(),
+        treasury_mint: accounts.treasury_mint.clone(),
+        escrow_payment_account: accounts.escrow_payment_account.clone(),
+        seller_payment_receipt_account: accounts.seller_payment_receipt_account.clone(),
+        buyer_receipt_token_account: accounts.buyer_receipt_token_account.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        auction_house_treasury: accounts.auction_house_treasury.clone(),
+        buyer_trade_state: accounts.buyer_trade_state.clone(),
+        seller_trade_state: accounts.seller_trade_state.clone(),
+        free_trade_state: accounts.free_trade_state.clone(),
+        token_program: accounts.token_program.clone(),
+        system_program: accounts.system_program.clone(),
+        ata_program: accounts.ata_program.clone(),
+        program_as_signer: accounts.program_as_signer.clone(),
+        rent: accounts.rent.clone(),
+    };
+
+    execute_sale_logic(
+        Context::new(
+            ctx.program_id,
+            &mut execute_sale_accounts,
+            ctx.remaining_accounts,
+            ctx.bumps.clone(),
+        ),
+        escrow_payment_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        AUCTION_LISTING_PRICE,
+        token_size,
+        ExecuteSaleOptions {
+            fill_size: Some(token_size),
+            fill_price: Some(highest_bid.amount),
+            settler: Some(accounts.settler.to_account_info()),
+            auction_settlement: true,
+            defer_trade_state_rent: true,
+            ..Default::default()
+        },
+    )?;
+
+    close_auction_escrow(
+        &accounts.auction_escrow.to_account_info(),
+        &accounts.seller.to_account_info(),
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        &auction_escrow_seeds,
+        &auction_house_seeds,
+        is_native,
+    )?;
+
+    // NOTE: the settler may be the seller, so the trade states pay their rent back once the escrow paid it
+    let settler = accounts.settler.to_account_info();
+    for trade_state in [
+        &accounts.buyer_trade_state,
+        &accounts.seller_trade_state,
+        &accounts.free_trade_state,
+    ] {
+        collect_trade_state_rent(&trade_state.to_account_info(), &settler)?;
+    }
+
+    ctx.accounts.auction_house.record_closed_escrow()
+}
+
+/// Accounts for the [`cancel_english_auction` handler](auction_house/fn.cancel_english_auction.html).
+#[derive(Accounts)]
+pub struct CancelEnglishAuction<'info> {
+    /// CHECK: Validated by the `constraint` on the English auction.
+    /// Seller wallet account, receiving the rent of the English auction account.
+    #[account(mut)]
+    pub wallet: UncheckedAccount<'info>,
+
+    /// SPL token account containing the auctioned token.
+    #[account(mut)]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Token mint account of SPL token
+    #[account(mut)]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Validated as a signer in cancel_logic.
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in cancel_logic.
+    /// Seller trade state PDA account encoding the auction listing.
+    #[account(mut)]
+    pub trade_state: UncheckedAccount<'info>,
+
+    /// English auction account being canceled, closed back to the seller.
+    #[account(
+        mut,
+        close = wallet,
+        seeds = [
+            ENGLISH_AUCTION.as_bytes(),
+            auction_house.key().as_ref(),
+            trade_state.key().as_ref()
+        ],
+        bump = english_auction.bump,
+        has_one = auction_house,
+        has_one = token_account,
+        constraint = english_auction.seller == wallet.key()
+    )]
+    pub english_auction: Box<Account<'info, EnglishAuction>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction escrow PDA locking the funds of the highest bid, closed back to the seller.
+    #[account(
+        mut,
+        seeds = [
+            AUCTION_ESCROW.as_bytes(),
+            english_auction.key().as_ref()
+        ],
+        bump = english_auction.escrow_bump
+    )]
+    pub auction_escrow: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by derivation from the highest bid in cancel_english_auction.
+    /// Escrow payment account of the highest bidder, refunded its locked funds. Ignored without bids.
+    #[account(mut)]
+    pub highest_bidder_escrow_payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against the highest bid in cancel_english_auction.
+    /// Trade state of the highest bid. Ignored without bids.
+    #[account(mut)]
+    pub highest_bidder_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against the highest bid trade state in cancel_english_auction.
+    /// Account that paid for the highest bid trade state, receiving its rent. Ignored without bids.
+    #[account(mut)]
+    pub highest_bid_trade_state_fee_payer: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// NOTE: Cancel an English auction that has not received any bid, revoking the listing and closing the auction account.
+/// Once bid on, the auction ends through settlement, unless the seller's token can no longer be delivered: the highest
+/// bid is then refunded so its funds are not locked forever. Like settlement, this works whether or not an auctioneer
+/// was delegated since the auction started.
+pub fn cancel_english_auction<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelEnglishAuction<'info>>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let english_auction = &accounts.english_auction;
+    let token_account = &accounts.token_account;
+
+    let is_native = accounts.auction_house.treasury_mint == spl_token::native_mint::id();
+    let english_auction_key = english_auction.key();
+    let auction_escrow_seeds = [
+        AUCTION_ESCROW.as_bytes(),
+        english_auction_key.as_ref(),
+        &[english_auction.escrow_bump],
+    ];
+    let auction_house_seeds = [
+        PREFIX.as_bytes(),
+        accounts.auction_house.creator.as_ref(),
+        accounts.auction_house.treasury_mint.as_ref(),
+        &[accounts.auction_house.bump],
+    ];
+
+    let highest_bid = english_auction.highest_bid.clone();
+    if highest_bid.amount > 0 {
+        if is_listing_fillable(token_account, english_auction.token_size, ctx.program_id)? {
+            return Err(AuctionHouseError::AuctionHasBids.into());
+        }
+
+        release_locked_bid(
+            &highest_bid,
+            english_auction.locked_amount,
+            &accounts.auction_house.key(),
+            &accounts.auction_house.to_account_info(),
+            &accounts.auction_escrow.to_account_info(),
+            &accounts.highest_bidder_escrow_payment_account.to_account_info(),
+            &accounts.highest_bidder_trade_state.to_account_info(),
+            &accounts.highest_bid_trade_state_fee_payer.to_account_info(),
+            &accounts.token_program.to_account_info(),
+            &accounts.system_program.to_account_info(),
+            &auction_escrow_seeds,
+            &auction_house_seeds,
+            is_native,
+        )?;
+    }
+
+    // NOTE: close the escrow before cancel_logic credits the trade state rent to the wallet outside of any CPI,
+    // which would leave the wallet unbalanced in the escrow transfer
+    close_auction_escrow(
+        &accounts.auction_escrow.to_account_info(),
+        &accounts.wallet.to_account_info(),
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        &auction_escrow_seeds,
+        &auction_house_seeds,
+        is_native,
+    )?;
+
+    let mut cancel_accounts = Cancel {
+        wallet: accounts.wallet.clone(),
+        token_account: accounts.token_account.clone(),
+        token_mint: accounts.token_mint.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        trade_state: accounts.trade_state.clone(),
+        token_program: accounts.token_program.clone(),
+    };
+
+    cancel_logic(
+        &mut cancel_accounts,
+        AUCTION_LISTING_PRICE,
+        english_auction.token_size,
+    )?;
+
+    if highest_bid.amount > 0 {
+        collect_trade_state_rent(
+            &ctx.accounts.highest_bidder_trade_state.to_account_info(),
+            &ctx.accounts.highest_bid_trade_state_fee_payer.to_account_info(),
+        )?;
+    }
+
+    ctx.accounts.auction_house.record_closed_escrow()
+}
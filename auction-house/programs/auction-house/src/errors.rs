@@ -58,4 +58,157 @@ pub enum AuctionHouseError {
 
     #[msg("No valid signer present")]
     NoValidSignerPresent,
+
+    #[msg("Invalid seller fee basis points")]
+    InvalidBasisPoints,
+
+    #[msg("The instruction does not match")]
+    InstructionMismatch,
+
+    #[msg("Too many scopes")]
+    TooManyScopes,
+
+    #[msg("The auction house already has a delegated auctioneer")]
+    AuctioneerAlreadyDelegated,
+
+    #[msg("The auction house has no delegated auctioneer")]
+    AuctionHouseNotDelegated,
+
+    #[msg("The auctioneer is missing the scope for this action")]
+    MissingAuctioneerScope,
+
+    #[msg("Partial order size must be greater than zero and less than the listed token size")]
+    InvalidPartialOrderSize,
+
+    #[msg("Partial order price does not match the pro-rated listing price")]
+    PartialPriceMismatch,
+
+    #[msg("Cannot close the auction house while an auctioneer is delegated")]
+    CannotCloseWithActiveAuctioneer,
+
+    #[msg("Auction start time must be before its end time, and the end time in the future")]
+    InvalidAuctionTimes,
+
+    #[msg("Minimum bid increment must be greater than zero")]
+    InvalidBidIncrement,
+
+    #[msg("The auction is not accepting bids")]
+    AuctionNotActive,
+
+    #[msg("The auction has not ended yet")]
+    AuctionNotEnded,
+
+    #[msg("Bid must meet the reserve price and beat the highest bid by the minimum increment")]
+    BidTooLow,
+
+    #[msg("The auction has no winning bid")]
+    NoWinningBid,
+
+    #[msg("Cannot cancel an auction that has received bids")]
+    AuctionHasBids,
+
+    #[msg("Auction start price must be above its floor price, and the floor price above zero")]
+    InvalidAuctionPrices,
+
+    #[msg("The current auction price is above the bid")]
+    AuctionPriceAboveBid,
+
+    #[msg("The auction is not in its reveal window")]
+    AuctionNotInRevealWindow,
+
+    #[msg("The revealed price and salt do not match the commitment")]
+    InvalidCommitment,
+
+    #[msg("The revealed price exceeds the committed collateral")]
+    RevealExceedsCollateral,
+
+    #[msg("Order expiry must be in the future")]
+    InvalidExpiry,
+
+    #[msg("The order has expired")]
+    OrderExpired,
+
+    #[msg("The order has not expired")]
+    OrderNotExpired,
+
+    #[msg("The trade state is not a legacy 1-byte trade state")]
+    TradeStateAlreadyMigrated,
+
+    #[msg("The token is not a verified member of the offer's collection")]
+    InvalidCollection,
+
+    #[msg("An open order already uses this trade state")]
+    TradeStateAlreadyExists,
+
+    #[msg("The token is not in the offer's merkle tree of eligible mints")]
+    InvalidMerkleProof,
+
+    #[msg("A bundle needs up to MAX_BUNDLE_ITEMS distinct items with allocations summing to its price")]
+    InvalidBundle,
+
+    #[msg("The remaining accounts do not form complete sweep groups")]
+    InvalidSweepAccounts,
+
+    #[msg("The sale would cost the buyer more than its maximum total")]
+    BuyerMaxTotalExceeded,
+
+    #[msg("The seller's proceeds after royalties and house fee are below its minimum")]
+    SellerProceedsBelowMinimum,
+
+    #[msg("Fee tiers need ascending minimum volumes and discounts of at most 10000 basis points")]
+    InvalidFeeTiers,
+
+    #[msg("Wallet stats accounts must belong to the buyer and seller of this auction house")]
+    InvalidWalletStats,
+
+    #[msg("A revenue split needs 1 to MAX_REVENUE_RECIPIENTS distinct recipients with shares summing to 100")]
+    InvalidRevenueSplit,
+
+    #[msg("The bid is locked in an auction and can only be released by it")]
+    TradeStateLocked,
+
+    #[msg("Auction listings can only be canceled through their auction")]
+    MustUseAuctionHandler,
+
+    #[msg("The referrer must be the one recorded in the buyer trade state")]
+    ReferrerMismatch,
+
+    #[msg("The treasury of an Auction House with a revenue split can only be withdrawn through the split")]
+    MustUseRevenueSplit,
+
+    #[msg("A sale limit must be signed for by the buyer or seller it protects")]
+    LimitRequiresSigner,
+
+    #[msg("The trade state cannot be migrated as this side")]
+    TradeSideMismatch,
+
+    #[msg("The Auction House cannot be closed while auction or sealed bid escrows are open")]
+    CannotCloseWithOpenAuctionEscrows,
+
+    #[msg("The Auction House is still open, use withdraw instead")]
+    AuctionHouseNotClosed,
+
+    #[msg("Only listings whose price is a whole multiple of their token size can be partially filled")]
+    PartialPriceNotDivisible,
+
+    #[msg("A new treasury withdrawal destination needs exactly the destination and its owner as remaining accounts")]
+    InvalidTreasuryWithdrawalDestinationAccounts,
+
+    #[msg("A receipt already exists for this open order")]
+    ReceiptAlreadyExists,
+
+    #[msg("The Auction House cannot be closed while referrers are approved")]
+    CannotCloseWithApprovedReferrers,
+
+    #[msg("The account does not belong to this Auction House or cannot be closed through it")]
+    InvalidClosedAuctionHouseAccount,
+
+    #[msg("A listing of a bundle can only be bought together with the rest of the bundle")]
+    BundledListing,
+
+    #[msg("The referrer must be approved by this Auction House and passed at its derived address")]
+    InvalidReferrer,
+
+    #[msg("The Auction House cannot be closed while it has a revenue split")]
+    CannotCloseWithRevenueSplit,
 }
@@ -174,9 +174,676 @@ pub fn execute_sale<'info>(
         program_as_signer_bump,
         buyer_price,
         token_size,
+        ExecuteSaleOptions::default(),
     )
 }
 
+/// Execute a sale like execute_sale, failing if the buyer would pay more than `max_buyer_total` or the seller would
+/// net less than `min_seller_proceeds` after royalties and the house fee. Each limit must be signed for by the party
+/// it protects.
+pub fn execute_sale_with_limits<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteSale<'info>>,
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+    max_buyer_total: Option<u64>,
+    min_seller_proceeds: Option<u64>,
+) -> Result<()> {
+    let auction_house = &ctx.accounts.auction_house;
+
+    if auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    if max_buyer_total.is_some() && !ctx.accounts.buyer.is_signer {
+        return Err(AuctionHouseError::LimitRequiresSigner.into());
+    }
+    if min_seller_proceeds.is_some() && !ctx.accounts.seller.is_signer {
+        return Err(AuctionHouseError::LimitRequiresSigner.into());
+    }
+
+    execute_sale_logic(
+        ctx,
+        escrow_payment_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        buyer_price,
+        token_size,
+        ExecuteSaleOptions {
+            max_buyer_total,
+            min_seller_proceeds,
+            ..Default::default()
+        },
+    )
+}
+
+/// Accounts for the [`auctioneer_execute_sale` handler](auction_house/fn.auctioneer_execute_sale.html).
+#[derive(Accounts)]
+#[instruction(
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    seller_trade_state_bump: u8,
+    buyer_price: u64,
+    token_size: u64
+)]
+pub struct AuctioneerExecuteSale<'info> {
+    /// CHECK: Validated in execute_sale_logic.
+    /// Buyer's wallet account
+    #[account(mut)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Seller's wallet account
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    /// CHECK : Validated in execute_sale_logic.
+    /// Token account where the SPL token is being held
+    #[account(mut)]
+    pub token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Token mint account for the SPL token.
+    pub token_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Metaplex metaplex account holding extra infos.
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Auction House treasury mint account.
+    pub treasury_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. validate via seeds check.
+    /// Buyer escrow payment account.
+    #[account (
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            buyer.key().as_ref()
+        ],
+        bump=escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Seller SOL or SPL account to receive payment at,
+    #[account(mut)]
+    pub seller_payment_receipt_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Buyer SOL or SPL account to receive the purchased item at.
+    #[account(mut)]
+    pub buyer_receipt_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Auction House instance authority
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump=auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_treasury,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Auction House instance fee account
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Auction House instance treasury account
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            TREASURY.as_bytes()
+        ],
+        bump = auction_house.treasury_bump
+    )]
+    pub auction_house_treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Validate in execute_sale_logic.
+    /// Buyer trade state PDA account encoding the buy order.
+    #[account(mut)]
+    pub buyer_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Seller trade state PDA account encoding the sell order.
+    #[account(mut)]
+    pub seller_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Free seller trade state Pad account encoding a free sell order.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            seller.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_mint.key().as_ref(),
+            &0u64.to_le_bytes(),
+            &token_size.to_le_bytes(),
+        ],
+        bump = free_trade_state_bump
+    )]
+    pub free_trade_state: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub ata_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            SIGNER.as_bytes()
+        ],
+        bump = program_as_signer_bump
+    )]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+
+    /// The auctioneer authority - typically a PDA of the Auctioneer program running this action.
+    pub auctioneer_authority: Signer<'info>,
+
+    /// The auctioneer PDA owned by Auction House storing scopes.
+    #[account(
+        seeds = [
+            AUCTIONEER.as_bytes(),
+            auction_house.key().as_ref(),
+            auctioneer_authority.key().as_ref()
+        ],
+        bump = auction_house.auctioneer_pda_bump
+    )]
+    pub ah_auctioneer_pda: Box<Account<'info, Auctioneer>>,
+}
+
+pub fn auctioneer_execute_sale<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuctioneerExecuteSale<'info>>,
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    assert_valid_auctioneer_and_scope(
+        &accounts.auction_house,
+        &accounts.auctioneer_authority.key(),
+        &accounts.ah_auctioneer_pda,
+        AuthorityScope::ExecuteSale,
+    )?;
+
+    let mut execute_sale_accounts = ExecuteSale {
+        buyer: accounts.buyer.clone(),
+        seller: accounts.seller.clone(),
+        token_account: accounts.token_account.clone(),
+        token_mint: accounts.token_mint.clone(),
+        metadata: accounts.metadata.clone(),
+        treasury_mint: accounts.treasury_mint.clone(),
+        escrow_payment_account: accounts.escrow_payment_account.clone(),
+        seller_payment_receipt_account: accounts.seller_payment_receipt_account.clone(),
+        buyer_receipt_token_account: accounts.buyer_receipt_token_account.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        auction_house_treasury: accounts.auction_house_treasury.clone(),
+        buyer_trade_state: accounts.buyer_trade_state.clone(),
+        seller_trade_state: accounts.seller_trade_state.clone(),
+        free_trade_state: accounts.free_trade_state.clone(),
+        token_program: accounts.token_program.clone(),
+        system_program: accounts.system_program.clone(),
+        ata_program: accounts.ata_program.clone(),
+        program_as_signer: accounts.program_as_signer.clone(),
+        rent: accounts.rent.clone(),
+    };
+
+    execute_sale_logic(
+        Context::new(
+            ctx.program_id,
+            &mut execute_sale_accounts,
+            ctx.remaining_accounts,
+            ctx.bumps.clone(),
+        ),
+        escrow_payment_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        buyer_price,
+        token_size,
+        ExecuteSaleOptions::default(),
+    )
+}
+
+/// Accounts for the [`execute_partial_sale` handler](auction_house/fn.execute_partial_sale.html).
+#[derive(Accounts)]
+#[instruction(
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    seller_trade_state_bump: u8,
+    buyer_price: u64,
+    token_size: u64
+)]
+pub struct ExecutePartialSale<'info> {
+    /// CHECK: Validated in execute_sale_logic.
+    /// Buyer's wallet account
+    #[account(mut)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Seller's wallet account
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    /// CHECK : Validated in execute_sale_logic.
+    /// Token account where the SPL token is being held
+    #[account(mut)]
+    pub token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Token mint account for the SPL token.
+    pub token_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Metaplex metaplex account holding extra infos.
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Auction House treasury mint account.
+    pub treasury_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. validate via seeds check.
+    /// Buyer escrow payment account.
+    #[account (
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            buyer.key().as_ref()
+        ],
+        bump=escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Seller SOL or SPL account to receive payment at,
+    #[account(mut)]
+    pub seller_payment_receipt_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Buyer SOL or SPL account to receive the purchased item at.
+    #[account(mut)]
+    pub buyer_receipt_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Auction House instance authority
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump=auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_treasury,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Auction House instance fee account
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Auction House instance treasury account
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            TREASURY.as_bytes()
+        ],
+        bump = auction_house.treasury_bump
+    )]
+    pub auction_house_treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Validate in execute_sale_logic.
+    /// Buyer trade state PDA account encoding the buy order.
+    #[account(mut)]
+    pub buyer_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Seller trade state PDA account encoding the sell order.
+    #[account(mut)]
+    pub seller_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Free seller trade state Pad account encoding a free sell order.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            seller.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_mint.key().as_ref(),
+            &0u64.to_le_bytes(),
+            &token_size.to_le_bytes(),
+        ],
+        bump = free_trade_state_bump
+    )]
+    pub free_trade_state: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub ata_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            SIGNER.as_bytes()
+        ],
+        bump = program_as_signer_bump
+    )]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+
+    /// CHECK: Validated in execute_partial_sale.
+    /// Seller trade state PDA account encoding the remaining, unfilled part of the sell order.
+    #[account(mut)]
+    pub new_seller_trade_state: UncheckedAccount<'info>,
+}
+
+/// Execute the sale of `partial_order_size` units out of a `token_size` listing at the pro-rated price, and re-list the remaining units under a new seller trade state.
+/// The listing price must be a whole multiple of `token_size`.
+pub fn execute_partial_sale<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecutePartialSale<'info>>,
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+    partial_order_size: u64,
+    partial_order_price: u64,
+    new_seller_trade_state_bump: u8,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let buyer = &accounts.buyer;
+    let seller = &accounts.seller;
+    let token_account = &accounts.token_account;
+    let token_mint = &accounts.token_mint;
+    let authority = &accounts.authority;
+    let auction_house = &accounts.auction_house;
+    let auction_house_fee_account = &accounts.auction_house_fee_account;
+    let seller_trade_state = &accounts.seller_trade_state;
+    let buyer_trade_state = &accounts.buyer_trade_state;
+    let free_trade_state = &accounts.free_trade_state;
+    let new_seller_trade_state = &accounts.new_seller_trade_state;
+    let system_program = &accounts.system_program;
+    let rent = &accounts.rent;
+
+    if auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    // NOTE: filling the whole listing must go through execute_sale
+    if partial_order_size == 0 || partial_order_size >= token_size {
+        return Err(AuctionHouseError::InvalidPartialOrderSize.into());
+    }
+
+    // NOTE: only a whole unit price keeps both the fill and the re-listed remainder at the listing's unit price
+    if buyer_price % token_size != 0 {
+        return Err(AuctionHouseError::PartialPriceNotDivisible.into());
+    }
+
+    // NOTE: the buyer pays the listing's unit price for the units it takes
+    let expected_partial_price = (buyer_price / token_size)
+        .checked_mul(partial_order_size)
+        .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+    if partial_order_price != expected_partial_price {
+        return Err(AuctionHouseError::PartialPriceMismatch.into());
+    }
+
+    let remaining_size = token_size
+        .checked_sub(partial_order_size)
+        .ok_or(AuctionHouseError::NumericalOverflow)?;
+    let remaining_price = buyer_price
+        .checked_sub(partial_order_price)
+        .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+    if seller_trade_state.data_is_empty() {
+        return Err(AuctionHouseError::BothPartiesNeedToAgreeToSale.into());
+    }
+
+    let seller_trade_state_bump = seller_trade_state.try_borrow_data()?[0];
+
+    assert_valid_trade_state(
+        &seller.key(),
+        auction_house,
+        buyer_price,
+        token_size,
+        seller_trade_state,
+        &token_mint.key(),
+        &token_account.key(),
+        seller_trade_state_bump,
+    )?;
+
+    assert_valid_trade_state(
+        &seller.key(),
+        auction_house,
+        remaining_price,
+        remaining_size,
+        new_seller_trade_state,
+        &token_mint.key(),
+        &token_account.key(),
+        new_seller_trade_state_bump,
+    )?;
+
+    // NOTE: a separate listing of the remainder keeps its own order, so it cannot absorb this one
+    if !new_seller_trade_state.data_is_empty() {
+        return Err(AuctionHouseError::TradeStateAlreadyExists.into());
+    }
+
+    let mut execute_sale_accounts = ExecuteSale {
+        buyer: accounts.buyer.clone(),
+        seller: accounts.seller.clone(),
+        token_account: accounts.token_account.clone(),
+        token_mint: accounts.token_mint.clone(),
+        metadata: accounts.metadata.clone(),
+        treasury_mint: accounts.treasury_mint.clone(),
+        escrow_payment_account: accounts.escrow_payment_account.clone(),
+        seller_payment_receipt_account: accounts.seller_payment_receipt_account.clone(),
+        buyer_receipt_token_account: accounts.buyer_receipt_token_account.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        auction_house_treasury: accounts.auction_house_treasury.clone(),
+        buyer_trade_state: accounts.buyer_trade_state.clone(),
+        seller_trade_state: accounts.seller_trade_state.clone(),
+        free_trade_state: accounts.free_trade_state.clone(),
+        token_program: accounts.token_program.clone(),
+        system_program: accounts.system_program.clone(),
+        ata_program: accounts.ata_program.clone(),
+        program_as_signer: accounts.program_as_signer.clone(),
+        rent: accounts.rent.clone(),
+    };
+
+    execute_sale_logic(
+        Context::new(
+            ctx.program_id,
+            &mut execute_sale_accounts,
+            ctx.remaining_accounts,
+            ctx.bumps.clone(),
+        ),
+        escrow_payment_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        buyer_price,
+        token_size,
+        ExecuteSaleOptions {
+            fill_size: Some(partial_order_size),
+            fill_price: Some(partial_order_price),
+            defer_trade_state_rent: true,
+            ..Default::default()
+        },
+    )?;
+
+    // NOTE: swap the filled seller trade state for one encoding the remaining units
+    let auction_house_key = auction_house.key();
+    let seeds = [
+        PREFIX.as_bytes(),
+        auction_house_key.as_ref(),
+        FEE_PAYER.as_bytes(),
+        &[auction_house.fee_payer_bump],
+    ];
+
+    let wallet_to_use = if buyer.is_signer { buyer } else { seller };
+
+    let (fee_payer, fee_payer_seeds) = get_fee_payer(
+        authority,
+        auction_house,
+        wallet_to_use.to_account_info(),
+        auction_house_fee_account.to_account_info(),
+        &seeds,
+    )?;
+
+    // NOTE: the remaining units keep the flags and expiry of the original listing
+    let (flags, expiry) = get_trade_state_data(&seller_trade_state.try_borrow_data()?)
+        .map(|trade_state_data| (trade_state_data.flags, trade_state_data.expiry))
+        .unwrap_or((0, 0));
+
+    let new_ts_info = new_seller_trade_state.to_account_info();
+
+    let seller_key = seller.key();
+    let token_account_key = token_account.key();
+    let token_mint_key = token_mint.key();
+    let new_ts_seeds = [
+        PREFIX.as_bytes(),
+        seller_key.as_ref(),
+        auction_house_key.as_ref(),
+        token_account_key.as_ref(),
+        auction_house.treasury_mint.as_ref(),
+        token_mint_key.as_ref(),
+        &remaining_price.to_le_bytes(),
+        &remaining_size.to_le_bytes(),
+        &[new_seller_trade_state_bump],
+    ];
+
+    create_or_allocate_account_raw(
+        crate::id(),
+        &new_ts_info,
+        &rent.to_account_info(),
+        system_program,
+        &fee_payer,
+        TRADE_STATE_SIZE,
+        fee_payer_seeds,
+        &new_ts_seeds,
+    )?;
+
+    let clock = Clock::get()?;
+    write_trade_state(
+        &new_ts_info,
+        new_seller_trade_state_bump,
+        &TradeStateData {
+            version: TRADE_STATE_VERSION,
+            side: TradeSide::Sell,
+            flags,
+            auction_house: auction_house_key,
+            wallet: seller_key,
+            token_account: token_account_key,
+            mint: token_mint_key,
+            price: remaining_price,
+            token_size: remaining_size,
+            created_slot: clock.slot,
+            created_at: clock.unix_timestamp,
+            expiry,
+            fee_payer: fee_payer.key(),
+            referrer: Pubkey::default(),
+        },
+    )?;
+
+    // NOTE: the emptied trade states only pay their rent back once the remainder trade state was created
+    let seller_trade_state_len = seller_trade_state.data_len();
+    sol_memset(
+        *seller_trade_state.try_borrow_mut_data()?,
+        0,
+        seller_trade_state_len,
+    );
+
+    for trade_state in [seller_trade_state, buyer_trade_state, free_trade_state] {
+        collect_trade_state_rent(&trade_state.to_account_info(), &fee_payer)?;
+    }
+
+    Ok(())
+}
+
+/// Optional parts of a sale run by execute_sale_logic. The default executes the full listing at its price.
+#[derive(Default)]
+pub struct ExecuteSaleOptions<'info> {
+    /// Size delivered by a partial or auction fill, together with `fill_price`.
+    pub fill_size: Option<u64>,
+    /// Price paid for `fill_size`.
+    pub fill_price: Option<u64>,
+    /// Price encoded in the buyer trade state when the buyer bid more than it pays.
+    pub bid_price: Option<u64>,
+    /// Signer paying for a permissionless settlement directly, instead of the buyer, seller or house fee account.
+    pub settler: Option<AccountInfo<'info>>,
+    /// Most the buyer pays including its fee.
+    pub max_buyer_total: Option<u64>,
+    /// Least the seller nets after royalties and fees.
+    pub min_seller_proceeds: Option<u64>,
+    /// Settles an auction whose bid is locked; any other sale rejects locked buyer trade states.
+    pub auction_settlement: bool,
+    /// Sells an item of a bundle; any other sale rejects bundled seller trade states.
+    pub bundle: bool,
+    /// Leaves the rent of the emptied trade states for the caller to collect after its last CPI.
+    pub defer_trade_state_rent: bool,
+}
+
 /// Execute sale between provided buyer and seller trade state accounts transferring funds to seller wallet and token to buyer wallet.
 #[inline(never)]
 pub fn execute_sale_logic<'info>(
@@ -186,7 +853,20 @@ pub fn execute_sale_logic<'info>(
     program_as_signer_bump: u8,
     buyer_price: u64,
     token_size: u64,
+    options: ExecuteSaleOptions<'info>,
 ) -> Result<()> {
+    let ExecuteSaleOptions {
+        fill_size,
+        fill_price,
+        bid_price,
+        settler,
+        max_buyer_total,
+        min_seller_proceeds,
+        auction_settlement,
+        bundle,
+        defer_trade_state_rent,
+    } = options;
+
     // NOTE: Extract all data from the context and args
     let buyer = &ctx.accounts.buyer;
     let seller = &ctx.accounts.seller;
@@ -231,6 +911,12 @@ pub fn execute_sale_logic<'info>(
         );
     }
 
+    // NOTE: a partial or auction fill pays for and delivers what the buyer actually bid on
+    let (payment_price, payment_size) = match (fill_price, fill_size) {
+        (Some(price), Some(size)) => (price, size),
+        _ => (buyer_price, token_size),
+    };
+
     let token_account_mint = get_mint_from_token_account(&token_account_clone)?;
     assert_keys_equal(token_mint.key(), token_account_mint)?;
 
@@ -246,12 +932,14 @@ pub fn execute_sale_logic<'info>(
     let buyer_trade_state_data = &mut buyer_trade_state.try_borrow_mut_data()?;
     let seller_trade_state_data = &mut seller_trade_state.try_borrow_mut_data()?;
     let trade_state_bump = buyer_trade_state_data[0];
+    let seller_trade_state_bump = seller_trade_state_data.first().copied().unwrap_or(0);
 
+    // NOTE: a buyer may bid above the price it ends up paying, e.g. on a descending auction
     assert_valid_trade_state(
         &buyer.key(),
         auction_house,
-        buyer_price,
-        token_size,
+        bid_price.unwrap_or(payment_price),
+        payment_size,
         buyer_trade_state,
         &token_mint.key(),
         &token_account.key(),
@@ -265,6 +953,30 @@ pub fn execute_sale_logic<'info>(
         return Err(AuctionHouseError::BothPartiesNeedToAgreeToSale.into());
     }
 
+    // NOTE: expired orders can only be closed
+    assert_trade_state_not_expired(&buyer_trade_state_data)?;
+
+    if !auction_settlement {
+        assert_trade_state_not_locked(&buyer_trade_state_data)?;
+    }
+    assert_trade_state_not_expired(&seller_trade_state_data)?;
+
+    if !bundle {
+        assert_trade_state_not_bundled(&seller_trade_state_data)?;
+    }
+
+    // NOTE: the seller trade state always encodes the full listing
+    assert_valid_trade_state(
+        &seller.key(),
+        auction_house,
+        buyer_price,
+        token_size,
+        seller_trade_state,
+        &token_mint.key(),
+        &token_account.key(),
+        seller_trade_state_bump,
+    )?;
+
     let auction_house_key = auction_house.key();
     let seeds = [
         PREFIX.as_bytes(),
@@ -273,15 +985,21 @@ pub fn execute_sale_logic<'info>(
         &[auction_house.fee_payer_bump],
     ];
 
-    let wallet_to_use = if buyer.is_signer { buyer } else { seller };
-
-    let (fee_payer, fee_payer_seeds) = get_fee_payer(
-        authority,
-        auction_house,
-        wallet_to_use.to_account_info(),
-        auction_house_fee_account.to_account_info(),
-        &seeds,
-    )?;
+    // NOTE: a permissionless settlement is paid for by whoever cranks it, even on houses requiring sign off
+    let (fee_payer, fee_payer_seeds) = match settler {
+        Some(settler) => (settler, &[][..]),
+        None => get_fee_payer(
+            authority,
+            auction_house,
+            if buyer.is_signer {
+                buyer.to_account_info()
+            } else {
+                seller.to_account_info()
+            },
+            auction_house_fee_account.to_account_info(),
+            &seeds,
+        )?,
+    };
 
     let fee_payer_clone = fee_payer.to_account_info();
 
@@ -301,17 +1019,80 @@ pub fn execute_sale_logic<'info>(
         ],
     )?;
 
+    // NOTE: the order that rested first is the maker, migrated orders having slot 0; a collection offer is only ever
+    // filled by a new listing
+    let seller_is_maker = match (
+        get_trade_state_data(seller_trade_state_data),
+        get_trade_state_data(buyer_trade_state_data),
+    ) {
+        (Some(listing), Some(bid)) => {
+            listing.created_slot < bid.created_slot
+                || (listing.created_slot == bid.created_slot
+                    && bid.flags & TRADE_STATE_FLAG_COLLECTION_OFFER == 0)
+        }
+        _ => true,
+    };
+
+    let creator_accounts = get_creator_accounts_len(&metadata_clone, is_native)?;
+
+    // NOTE: only the referrer recorded by the buyer when bidding is paid, never one picked at execution
+    let referral = match get_trade_state_data(buyer_trade_state_data)
+        .map(|bid| bid.referrer)
+        .filter(|referrer| *referrer != Pubkey::default())
+    {
+        Some(referrer) => get_referral_payout(
+            ctx.remaining_accounts,
+            creator_accounts,
+            &auction_house.key(),
+            &referrer,
+        )?,
+        None => None,
+    };
+
+    // NOTE: volume discounts only apply to the sides whose wallet stats accounts exist
+    let now = Clock::get()?.unix_timestamp;
+    let (mut buyer_stats, mut seller_stats) = get_wallet_stats(
+        ctx.remaining_accounts,
+        creator_accounts,
+        &auction_house.key(),
+        &buyer.key(),
+        &seller.key(),
+    )?;
+    let buyer_discount = buyer_stats.as_ref().map_or(0, |stats| {
+        auction_house.fee_discount_basis_points(stats.trailing_volume(now))
+    });
+    let seller_discount = seller_stats.as_ref().map_or(0, |stats| {
+        auction_house.fee_discount_basis_points(stats.trailing_volume(now))
+    });
+
+    let sale_fees = compute_sale_fees(
+        auction_house,
+        payment_price,
+        seller_is_maker,
+        buyer_discount,
+        seller_discount,
+    )?;
+    let buyer_total = payment_price
+        .checked_add(sale_fees.buyer_fee)
+        .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+    if let Some(max_buyer_total) = max_buyer_total {
+        if buyer_total > max_buyer_total {
+            return Err(AuctionHouseError::BuyerMaxTotalExceeded.into());
+        }
+    }
+
     // For native purchases, verify that the amount in escrow is sufficient to actually purchase the token.
     // This is intended to cover the migration from pre-rent-exemption checked accounts to rent-exemption checked accounts.
     // The fee payer makes up the shortfall up to the amount of rent for an empty account.
 
     if is_native {
-        let diff = rent_checked_sub(escrow_payment_account.to_account_info(), buyer_price)?;
+        let diff = rent_checked_sub(escrow_payment_account.to_account_info(), buyer_total)?;
 
-        if diff != buyer_price {
+        if diff != buyer_total {
             // Return the shortfall amount (if greater than 0 but less than rent), but don't exceed the minimum rent the account should need.
             let short_fall = std::cmp::min(
-                buyer_price
+                buyer_total
                     .checked_sub(diff)
                     .ok_or(AuctionHouseError::NumericalOverflow)?,
                 rent.minimum_balance(escrow_payment_account.data_len()),
@@ -376,27 +1157,67 @@ pub fn execute_sale_logic<'info>(
         &rent_clone,
         &signer_seeds_for_royalties,
         fee_payer_seeds,
-        buyer_price,
+        payment_price,
         is_native,
     )?;
 
     // NOTE: pay the house
 
-    let auction_house_fee_paid = pay_auction_house_fees(
+    // NOTE: an approved referrer takes its share out of the house fee
+
+    let referral_fee = match &referral {
+        Some(referral) => {
+            let referral_fee = referral.referrer.share_of(sale_fees.house_fee)?;
+
+            pay_referral_fee(
+                referral,
+                &escrow_clone,
+                &auction_house_clone,
+                &fee_payer_clone,
+                treasury_mint,
+                &ata_clone,
+                &token_clone,
+                &sys_clone,
+                &rent_clone,
+                &signer_seeds_for_royalties,
+                fee_payer_seeds,
+                referral_fee,
+                is_native,
+            )?;
+
+            referral_fee
+        }
+        None => 0,
+    };
+
+    pay_auction_house_fee_amount(
         auction_house,
         &treasury_clone,
         &escrow_clone,
         &token_clone,
         &sys_clone,
         &signer_seeds_for_royalties,
-        buyer_price,
+        sale_fees
+            .house_fee
+            .checked_sub(referral_fee)
+            .ok_or(AuctionHouseError::NumericalOverflow)?,
         is_native,
     )?;
 
+    // NOTE: the buyer's fee comes on top of the price, so only the seller's side is deducted here
     let buyer_leftover_after_royalties_and_house_fee = buyer_leftover_after_royalties
-        .checked_sub(auction_house_fee_paid)
+        .checked_sub(sale_fees.seller_fee)
+        .ok_or(AuctionHouseError::NumericalOverflow)?
+        .checked_add(sale_fees.seller_rebate)
         .ok_or(AuctionHouseError::NumericalOverflow)?;
 
+    // NOTE: royalties and the house fee are read at execution time, so they may have changed since the listing
+    if let Some(min_seller_proceeds) = min_seller_proceeds {
+        if buyer_leftover_after_royalties_and_house_fee < min_seller_proceeds {
+            return Err(AuctionHouseError::SellerProceedsBelowMinimum.into());
+        }
+    }
+
     // NOTE: pay the seller the rest of the cut
 
     if !is_native {
@@ -493,7 +1314,7 @@ pub fn execute_sale_logic<'info>(
             &buyer_receipt_token_account.key(),
             &program_as_signer.key(),
             &[],
-            token_size,
+            payment_size,
         )?,
         &[
             token_account.to_account_info(),
@@ -504,45 +1325,45 @@ pub fn execute_sale_logic<'info>(
         &[&program_as_signer_seeds],
     )?;
 
-    // NOTE: clean up all trade states
+    // NOTE: both sides earn the sale's volume towards their fee tier, except on a self trade
+    if buyer.key() != seller.key() {
+        for stats in [&mut buyer_stats, &mut seller_stats].into_iter().flatten() {
+            stats.record_volume(payment_price, now)?;
+            stats.exit(ctx.program_id)?;
+        }
+    }
 
-    // NOTE: get the current seller lamport and set it to zero
-    let curr_seller_lamp = seller_trade_state.lamports();
-    **seller_trade_state.lamports.borrow_mut() = 0;
-    sol_memset(&mut *seller_trade_state_data, 0, TRADE_STATE_SIZE);
+    // NOTE: clean up all trade states, leaving their rent to the caller when it defers it
 
-    // NOTE: add current seller's lamport to fee payer's account
-    **fee_payer.lamports.borrow_mut() = fee_payer
-        .lamports()
-        .checked_add(curr_seller_lamp)
-        .ok_or(AuctionHouseError::NumericalOverflow)?;
+    // NOTE: a partially filled seller trade state is replaced by the caller instead
+    if payment_size == token_size {
+        let seller_trade_state_len = seller_trade_state_data.len();
+        sol_memset(&mut *seller_trade_state_data, 0, seller_trade_state_len);
+
+        if !defer_trade_state_rent {
+            collect_trade_state_rent(seller_trade_state, &fee_payer)?;
+        }
+    }
 
-    // NOTE: get the current buyer lamport and set it to zero
-    let curr_buyer_lamp = buyer_trade_state.lamports();
-    **buyer_trade_state.lamports.borrow_mut() = 0;
-    sol_memset(&mut *buyer_trade_state_data, 0, TRADE_STATE_SIZE);
+    let buyer_trade_state_len = buyer_trade_state_data.len();
+    sol_memset(&mut *buyer_trade_state_data, 0, buyer_trade_state_len);
 
-    // NOTE: add current buyer's lamport to fee payer's account
-    **fee_payer.lamports.borrow_mut() = fee_payer
-        .lamports()
-        .checked_add(curr_buyer_lamp)
-        .ok_or(AuctionHouseError::NumericalOverflow)?;
+    if !defer_trade_state_rent {
+        collect_trade_state_rent(buyer_trade_state, &fee_payer)?;
+    }
 
     // NOTE: add free trade state lamports to fee payer if it exists
     if free_trade_state.lamports() > 0 {
-        let curr_free_trade_lamp = free_trade_state.lamports();
-        **free_trade_state.lamports.borrow_mut() = 0;
-
-        **fee_payer.lamports.borrow_mut() = fee_payer
-            .lamports()
-            .checked_add(curr_free_trade_lamp)
-            .ok_or(AuctionHouseError::NumericalOverflow)?;
-
+        let free_trade_state_len = free_trade_state.data_len();
         sol_memset(
             *free_trade_state.try_borrow_mut_data()?,
             0,
-            TRADE_STATE_SIZE,
+            free_trade_state_len,
         );
+
+        if !defer_trade_state_rent {
+            collect_trade_state_rent(free_trade_state, &fee_payer)?;
+        }
     }
 
     Ok(())
@@ -0,0 +1,114 @@
+use anchor_lang::{prelude::*, AnchorDeserialize};
+
+use crate::constant::*;
+use crate::errors::AuctionHouseError;
+use crate::state::{AuctionHouse, FeeTier, WalletStats};
+
+/// Accounts for the [`set_fee_tiers` handler](auction_house/fn.set_fee_tiers.html).
+#[derive(Accounts)]
+pub struct SetFeeTiers<'info> {
+    /// Auction House instance authority account.
+    pub authority: Signer<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+}
+
+/// NOTE: Replace the Auction House fee tiers. Tiers must have strictly ascending minimum volumes and
+/// non-decreasing discounts; unused slots are cleared. An empty list disables volume discounts.
+pub fn set_fee_tiers<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetFeeTiers<'info>>,
+    tiers: Vec<FeeTier>,
+) -> Result<()> {
+    if tiers.len() > MAX_FEE_TIERS {
+        return Err(AuctionHouseError::InvalidFeeTiers.into());
+    }
+
+    for (i, tier) in tiers.iter().enumerate() {
+        if tier.discount_basis_points > 10000 {
+            return Err(AuctionHouseError::InvalidFeeTiers.into());
+        }
+
+        if i > 0
+            && (tier.min_volume <= tiers[i - 1].min_volume
+                || tier.discount_basis_points < tiers[i - 1].discount_basis_points)
+        {
+            return Err(AuctionHouseError::InvalidFeeTiers.into());
+        }
+    }
+
+    let mut fee_tiers = [FeeTier::default(); MAX_FEE_TIERS];
+    fee_tiers[..tiers.len()].copy_from_slice(&tiers);
+    ctx.accounts.auction_house.fee_tiers = fee_tiers;
+
+    Ok(())
+}
+
+/// Accounts for the [`create_wallet_stats` handler](auction_house/fn.create_wallet_stats.html).
+#[derive(Accounts)]
+pub struct CreateWalletStats<'info> {
+    /// Account paying for the wallet stats account.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Any wallet can have its volume tracked.
+    /// Wallet whose volume is tracked.
+    pub wallet: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// Wallet stats account, one per wallet and Auction House.
+    #[account(
+        init,
+        payer = payer,
+        space = WALLET_STATS_SIZE,
+        seeds = [
+            WALLET_STATS.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump
+    )]
+    pub wallet_stats: Box<Account<'info, WalletStats>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// NOTE: Start tracking a wallet's volume. Sales only count towards it once this account exists, every sale passes
+/// the wallet stats addresses of both sides.
+pub fn create_wallet_stats<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateWalletStats<'info>>,
+) -> Result<()> {
+    let wallet_stats = &mut ctx.accounts.wallet_stats;
+
+    wallet_stats.auction_house = ctx.accounts.auction_house.key();
+    wallet_stats.wallet = ctx.accounts.wallet.key();
+    wallet_stats.window_start = Clock::get()?.unix_timestamp;
+    wallet_stats.current_volume = 0;
+    wallet_stats.previous_volume = 0;
+    wallet_stats.bump = *ctx
+        .bumps
+        .get("wallet_stats")
+        .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?;
+
+    Ok(())
+}
@@ -1,21 +1,61 @@
 #![allow(warnings)]
+pub mod auctioneer;
 pub mod bid;
 pub mod constant;
+pub mod deposit;
+pub mod dutch_auction;
+pub mod english_auction;
 pub mod errors;
 pub mod execute_sale;
+pub mod fee_tiers;
+pub mod receipt;
+pub mod referral;
+pub mod revenue_split;
+pub mod sealed_auction;
+pub mod collection_offer;
+pub mod bundle;
+pub mod swap;
+pub mod sweep;
 pub mod sell;
 pub mod state;
 pub mod cancel;
+pub mod close_auction_house;
+pub mod close_expired;
+pub mod migrate_trade_state;
+pub mod update_auction_house;
+pub mod withdraw;
+pub mod withdraw_from_fee;
+pub mod withdraw_from_treasury;
 mod utils;
 
+use crate::auctioneer::*;
 use crate::bid::*;
 use crate::constant::*;
+use crate::deposit::*;
+use crate::dutch_auction::*;
+use crate::english_auction::*;
 use crate::error::*;
 use crate::execute_sale::*;
+use crate::fee_tiers::*;
+use crate::receipt::*;
+use crate::referral::*;
+use crate::revenue_split::*;
+use crate::sealed_auction::*;
+use crate::collection_offer::*;
+use crate::bundle::*;
+use crate::swap::*;
+use crate::sweep::*;
 use crate::sell::*;
 use crate::state::*;
 use crate::utils::*;
 use crate::cancel::*;
+use crate::close_auction_house::*;
+use crate::close_expired::*;
+use crate::migrate_trade_state::*;
+use crate::update_auction_house::*;
+use crate::withdraw::*;
+use crate::withdraw_from_fee::*;
+use crate::withdraw_from_treasury::*;
 
 use anchor_lang::{
     prelude::*,
@@ -137,6 +177,139 @@ pub mod auction_house {
         Ok(())
     }
 
+    /// Update the Auction House config. Optional arguments left as `None` keep their current value. Non-zero maker or taker fees replace `seller_fee_basis_points` on executed sales.
+    pub fn update_auction_house<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateAuctionHouse<'info>>,
+        seller_fee_basis_points: Option<u16>,
+        requires_sign_off: Option<bool>,
+        can_change_sale_price: Option<bool>,
+        maker_fee_basis_points: Option<i16>,
+        taker_fee_basis_points: Option<u16>,
+        new_authority: Option<Pubkey>,
+        fee_withdrawal_destination: Option<Pubkey>,
+    ) -> Result<()> {
+        update_auction_house::update_auction_house(
+            ctx,
+            seller_fee_basis_points,
+            requires_sign_off,
+            can_change_sale_price,
+            maker_fee_basis_points,
+            taker_fee_basis_points,
+            new_authority,
+            fee_withdrawal_destination,
+        )
+    }
+
+    /// Sweep the treasury and fee account to their withdrawal destinations and close the Auction House, returning its rent to the authority.
+    pub fn close_auction_house<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseAuctionHouse<'info>>,
+    ) -> Result<()> {
+        close_auction_house::close_auction_house(ctx)
+    }
+
+    /// Close a trade state, wallet stats, offer or auction account left behind by a closed Auction House, refunding its rent to the wallet that paid for it.
+    pub fn close_account_of_closed_auction_house<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseAccountOfClosedAuctionHouse<'info>>,
+    ) -> Result<()> {
+        close_auction_house::close_account_of_closed_auction_house(ctx)
+    }
+
+    /// Delegate an external auctioneer authority with the given scopes. Every trade of the house must then go through the `auctioneer_*` handlers.
+    pub fn delegate_auctioneer<'info>(
+        ctx: Context<'_, '_, '_, 'info, DelegateAuctioneer<'info>>,
+        scopes: Vec<AuthorityScope>,
+    ) -> Result<()> {
+        auctioneer::delegate_auctioneer(ctx, scopes)
+    }
+
+    /// Replace the scopes of the delegated auctioneer.
+    pub fn update_auctioneer<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateAuctioneer<'info>>,
+        scopes: Vec<AuthorityScope>,
+    ) -> Result<()> {
+        auctioneer::update_auctioneer(ctx, scopes)
+    }
+
+    /// Revoke the delegated auctioneer and close its PDA.
+    pub fn revoke_auctioneer<'info>(
+        ctx: Context<'_, '_, '_, 'info, RevokeAuctioneer<'info>>,
+    ) -> Result<()> {
+        auctioneer::revoke_auctioneer(ctx)
+    }
+
+    /// Withdraw `amount` lamports from the Auction House fee account to the `fee_withdrawal_destination`.
+    pub fn withdraw_from_fee<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawFromFee<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        withdraw_from_fee::withdraw_from_fee(ctx, amount)
+    }
+
+    /// Withdraw `amount` of the treasury mint from the Auction House treasury to the `treasury_withdrawal_destination`.
+    pub fn withdraw_from_treasury<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawFromTreasury<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        withdraw_from_treasury::withdraw_from_treasury(ctx, amount)
+    }
+
+    /// Split treasury withdrawals across up to MAX_REVENUE_RECIPIENTS wallets by percentage share.
+    pub fn create_revenue_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateRevenueSplit<'info>>,
+        recipients: Vec<RevenueRecipient>,
+    ) -> Result<()> {
+        revenue_split::create_revenue_split(ctx, recipients)
+    }
+
+    /// Replace the recipients of the Auction House revenue split.
+    pub fn update_revenue_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateRevenueSplit<'info>>,
+        recipients: Vec<RevenueRecipient>,
+    ) -> Result<()> {
+        revenue_split::update_revenue_split(ctx, recipients)
+    }
+
+    /// Remove the revenue split of an Auction House whose treasury was paid out through it.
+    pub fn remove_revenue_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, RemoveRevenueSplit<'info>>,
+    ) -> Result<()> {
+        revenue_split::remove_revenue_split(ctx)
+    }
+
+    /// Withdraw `amount` of the treasury mint from the Auction House treasury to the revenue split recipients.
+    pub fn withdraw_from_treasury_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawFromTreasurySplit<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        revenue_split::withdraw_from_treasury_split(ctx, amount)
+    }
+
+    /// Deposit `amount` into the escrow payment account for your specific wallet.
+    pub fn deposit<'info>(
+        ctx: Context<'_, '_, '_, 'info, Deposit<'info>>,
+        escrow_payment_bump: u8,
+        amount: u64,
+    ) -> Result<()> {
+        deposit::deposit(ctx, escrow_payment_bump, amount)
+    }
+
+    /// Withdraw `amount` from the escrow payment account for your specific wallet.
+    pub fn withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, Withdraw<'info>>,
+        escrow_payment_bump: u8,
+        amount: u64,
+    ) -> Result<()> {
+        withdraw::withdraw(ctx, escrow_payment_bump, amount)
+    }
+
+    /// Withdraw everything left in your escrow payment account after its Auction House was closed.
+    pub fn withdraw_from_closed_auction_house<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawFromClosedAuctionHouse<'info>>,
+        escrow_payment_bump: u8,
+    ) -> Result<()> {
+        withdraw::withdraw_from_closed_auction_house(ctx, escrow_payment_bump)
+    }
+
     pub fn sell<'info>(
         ctx: Context<'_, '_, '_, 'info, Sell<'info>>,
         trade_state_bump: u8,
@@ -144,6 +317,7 @@ pub mod auction_house {
         program_as_signer_bump: u8,
         buyer_price: u64,
         token_size: u64,
+        expiry: Option<i64>,
     ) -> Result<()> {
         sell::sell(
             ctx,
@@ -152,6 +326,7 @@ pub mod auction_house {
             program_as_signer_bump,
             buyer_price,
             token_size,
+            expiry,
         )
     }
 
@@ -161,6 +336,7 @@ pub mod auction_house {
         escrow_payment_bump: u8,
         buyer_price: u64,
         token_size: u64,
+        expiry: Option<i64>,
     ) -> Result<()> {
         private_bid(
             ctx,
@@ -168,6 +344,26 @@ pub mod auction_house {
             escrow_payment_bump,
             buyer_price,
             token_size,
+            expiry,
+        )
+    }
+
+    /// Create a public bid on a mint that is not tied to the token account currently holding it.
+    pub fn public_buy<'info>(
+        ctx: Context<'_, '_, '_, 'info, PublicBuy<'info>>,
+        trade_state_bump: u8,
+        escrow_payment_bump: u8,
+        buyer_price: u64,
+        token_size: u64,
+        expiry: Option<i64>,
+    ) -> Result<()> {
+        public_bid(
+            ctx,
+            trade_state_bump,
+            escrow_payment_bump,
+            buyer_price,
+            token_size,
+            expiry,
         )
     }
 
@@ -190,6 +386,56 @@ pub mod auction_house {
         )
     }
 
+    /// Execute a sale, failing if the buyer's total outlay exceeds `max_buyer_total` or the seller's proceeds after royalties and house fee fall below `min_seller_proceeds`. Each limit needs its party's signature.
+    pub fn execute_sale_with_limits<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteSale<'info>>,
+        escrow_payment_bump: u8,
+        free_trade_state_bump: u8,
+        program_as_signer_bump: u8,
+        _seller_trade_state_bump: u8,
+        buyer_price: u64,
+        token_size: u64,
+        max_buyer_total: Option<u64>,
+        min_seller_proceeds: Option<u64>,
+    ) -> Result<()> {
+        execute_sale::execute_sale_with_limits(
+            ctx,
+            escrow_payment_bump,
+            free_trade_state_bump,
+            program_as_signer_bump,
+            buyer_price,
+            token_size,
+            max_buyer_total,
+            min_seller_proceeds,
+        )
+    }
+
+    /// Execute a partial sale of a semi-fungible listing, re-listing the remaining units at the same unit price.
+    pub fn execute_partial_sale<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecutePartialSale<'info>>,
+        escrow_payment_bump: u8,
+        free_trade_state_bump: u8,
+        program_as_signer_bump: u8,
+        seller_trade_state_bump: u8,
+        buyer_price: u64,
+        token_size: u64,
+        partial_order_size: u64,
+        partial_order_price: u64,
+        new_seller_trade_state_bump: u8,
+    ) -> Result<()> {
+        execute_sale::execute_partial_sale(
+            ctx,
+            escrow_payment_bump,
+            free_trade_state_bump,
+            program_as_signer_bump,
+            buyer_price,
+            token_size,
+            partial_order_size,
+            partial_order_price,
+            new_seller_trade_state_bump,
+        )
+    }
+
     /// Cancel a bid or ask by revoking the token delegate, transferring all lamports from the trade state account to the fee payer, and setting the trade state account data to zero so it can be garbage collected.
     pub fn cancel<'info> (
         ctx: Context<'_, '_, '_, 'info, Cancel<'info>>,
@@ -198,6 +444,544 @@ pub mod auction_house {
     ) -> Result<()> {
         cancel::cancel(ctx, buyer_price, token_size)
     }
+
+    /// Close an expired bid or ask trade state and refund its rent to the account that paid for it. Anyone can call it.
+    pub fn close_expired<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseExpired<'info>>,
+        buyer_price: u64,
+        token_size: u64,
+    ) -> Result<()> {
+        close_expired::close_expired(ctx, buyer_price, token_size)
+    }
+
+    /// Migrate a legacy 1-byte trade state to the versioned layout recording the order details.
+    pub fn migrate_trade_state<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateTradeState<'info>>,
+        buyer_price: u64,
+        token_size: u64,
+        side: TradeSide,
+    ) -> Result<()> {
+        migrate_trade_state::migrate_trade_state(ctx, buyer_price, token_size, side)
+    }
+
+    /// List `token_size` tokens in a timed English auction with a reserve price and a minimum bid increment.
+    pub fn sell_english_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, SellEnglishAuction<'info>>,
+        trade_state_bump: u8,
+        free_trade_state_bump: u8,
+        program_as_signer_bump: u8,
+        token_size: u64,
+        start_time: i64,
+        end_time: i64,
+        reserve_price: u64,
+        min_bid_increment: u64,
+    ) -> Result<()> {
+        english_auction::sell_english_auction(
+            ctx,
+            trade_state_bump,
+            free_trade_state_bump,
+            program_as_signer_bump,
+            token_size,
+            start_time,
+            end_time,
+            reserve_price,
+            min_bid_increment,
+        )
+    }
+
+    /// Outbid the current highest bid of a running English auction.
+    pub fn bid_english_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, BidEnglishAuction<'info>>,
+        trade_state_bump: u8,
+        escrow_payment_bump: u8,
+        buyer_price: u64,
+        token_size: u64,
+    ) -> Result<()> {
+        english_auction::bid_english_auction(
+            ctx,
+            trade_state_bump,
+            escrow_payment_bump,
+            buyer_price,
+            token_size,
+        )
+    }
+
+    /// Settle an ended English auction with its highest bid. Anyone can crank it.
+    pub fn settle_english_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleEnglishAuction<'info>>,
+        escrow_payment_bump: u8,
+        free_trade_state_bump: u8,
+        program_as_signer_bump: u8,
+        token_size: u64,
+    ) -> Result<()> {
+        english_auction::settle_english_auction(
+            ctx,
+            escrow_payment_bump,
+            free_trade_state_bump,
+            program_as_signer_bump,
+            token_size,
+        )
+    }
+
+    /// Cancel an English auction that has not received any bid, or whose token can no longer be delivered to the winner.
+    pub fn cancel_english_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelEnglishAuction<'info>>,
+    ) -> Result<()> {
+        english_auction::cancel_english_auction(ctx)
+    }
+
+    /// List `token_size` tokens in a Dutch auction whose price decays from `start_price` to `floor_price`, linearly or every `step_interval` seconds.
+    pub fn sell_dutch_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, SellDutchAuction<'info>>,
+        trade_state_bump: u8,
+        free_trade_state_bump: u8,
+        program_as_signer_bump: u8,
+        token_size: u64,
+        start_time: i64,
+        end_time: i64,
+        start_price: u64,
+        floor_price: u64,
+        step_interval: i64,
+    ) -> Result<()> {
+        dutch_auction::sell_dutch_auction(
+            ctx,
+            trade_state_bump,
+            free_trade_state_bump,
+            program_as_signer_bump,
+            token_size,
+            start_time,
+            end_time,
+            start_price,
+            floor_price,
+            step_interval,
+        )
+    }
+
+    /// Buy a Dutch auction listing at its current price, as long as it does not exceed `buyer_price`.
+    pub fn buy_dutch_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyDutchAuction<'info>>,
+        trade_state_bump: u8,
+        escrow_payment_bump: u8,
+        free_trade_state_bump: u8,
+        program_as_signer_bump: u8,
+        buyer_price: u64,
+        token_size: u64,
+    ) -> Result<()> {
+        dutch_auction::buy_dutch_auction(
+            ctx,
+            trade_state_bump,
+            escrow_payment_bump,
+            free_trade_state_bump,
+            program_as_signer_bump,
+            buyer_price,
+            token_size,
+        )
+    }
+
+    /// Cancel a Dutch auction listing.
+    pub fn cancel_dutch_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelDutchAuction<'info>>,
+    ) -> Result<()> {
+        dutch_auction::cancel_dutch_auction(ctx)
+    }
+
+    /// List `token_size` tokens in a sealed-bid (commit-reveal) auction, optionally charging the winner the second price.
+    pub fn sell_sealed_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, SellSealedAuction<'info>>,
+        trade_state_bump: u8,
+        free_trade_state_bump: u8,
+        program_as_signer_bump: u8,
+        token_size: u64,
+        bid_end_time: i64,
+        reveal_end_time: i64,
+        reserve_price: u64,
+        vickrey: bool,
+    ) -> Result<()> {
+        sealed_auction::sell_sealed_auction(
+            ctx,
+            trade_state_bump,
+            free_trade_state_bump,
+            program_as_signer_bump,
+            token_size,
+            bid_end_time,
+            reveal_end_time,
+            reserve_price,
+            vickrey,
+        )
+    }
+
+    /// Commit a hash of (price, salt, wallet, sealed auction) to a sealed auction, locking `collateral` in the escrow of the sealed bid.
+    pub fn commit_sealed_bid<'info>(
+        ctx: Context<'_, '_, '_, 'info, CommitSealedBid<'info>>,
+        escrow_payment_bump: u8,
+        commitment: [u8; 32],
+        collateral: u64,
+    ) -> Result<()> {
+        sealed_auction::commit_sealed_bid(ctx, escrow_payment_bump, commitment, collateral)
+    }
+
+    /// Reveal a committed sealed bid, turning a leading bid into a locked buyer trade state at the revealed price.
+    pub fn reveal_sealed_bid<'info>(
+        ctx: Context<'_, '_, '_, 'info, RevealSealedBid<'info>>,
+        trade_state_bump: u8,
+        escrow_payment_bump: u8,
+        price: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        sealed_auction::reveal_sealed_bid(ctx, trade_state_bump, escrow_payment_bump, price, salt)
+    }
+
+    /// Settle a sealed auction with its highest revealed bid. Anyone can crank it.
+    pub fn settle_sealed_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleSealedAuction<'info>>,
+        escrow_payment_bump: u8,
+        free_trade_state_bump: u8,
+        program_as_signer_bump: u8,
+        token_size: u64,
+    ) -> Result<()> {
+        sealed_auction::settle_sealed_auction(
+            ctx,
+            escrow_payment_bump,
+            free_trade_state_bump,
+            program_as_signer_bump,
+            token_size,
+        )
+    }
+
+    /// Cancel a sealed auction that has no revealed bid, or whose token can no longer be delivered to the winner.
+    pub fn cancel_sealed_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelSealedAuction<'info>>,
+    ) -> Result<()> {
+        sealed_auction::cancel_sealed_auction(ctx)
+    }
+
+    /// Close a sealed bid that was never revealed, returning its collateral to the bidder escrow. Anyone can crank it.
+    pub fn close_sealed_bid<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseSealedBid<'info>>,
+        escrow_payment_bump: u8,
+    ) -> Result<()> {
+        sealed_auction::close_sealed_bid(ctx, escrow_payment_bump)
+    }
+
+    /// Offer `price` for each of up to `quantity` NFTs of a verified Metaplex collection, escrowing the funds.
+    /// A non-zero `merkle_root` restricts the offer to the mints of that tree.
+    pub fn make_collection_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, MakeCollectionOffer<'info>>,
+        escrow_payment_bump: u8,
+        collection: Pubkey,
+        price: u64,
+        quantity: u64,
+        merkle_root: [u8; 32],
+    ) -> Result<()> {
+        collection_offer::make_collection_offer(
+            ctx,
+            escrow_payment_bump,
+            collection,
+            price,
+            quantity,
+            merkle_root,
+        )
+    }
+
+    /// Sell an NFT of the offer's verified collection into a collection offer, proving its mint for trait offers.
+    pub fn accept_collection_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptCollectionOffer<'info>>,
+        trade_state_bump: u8,
+        free_trade_state_bump: u8,
+        buyer_trade_state_bump: u8,
+        escrow_payment_bump: u8,
+        program_as_signer_bump: u8,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        collection_offer::accept_collection_offer(
+            ctx,
+            trade_state_bump,
+            free_trade_state_bump,
+            buyer_trade_state_bump,
+            escrow_payment_bump,
+            program_as_signer_bump,
+            proof,
+        )
+    }
+
+    /// Cancel a collection offer, leaving its funds in the escrow.
+    pub fn cancel_collection_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelCollectionOffer<'info>>,
+    ) -> Result<()> {
+        collection_offer::cancel_collection_offer(ctx)
+    }
+
+    /// List several NFTs together for `price`, with `allocations` splitting the price across the items, each item
+    /// being listed at its allocation until `expiry` if given.
+    pub fn list_bundle<'info>(
+        ctx: Context<'_, '_, '_, 'info, ListBundle<'info>>,
+        program_as_signer_bump: u8,
+        bundle_id: u64,
+        price: u64,
+        allocations: Vec<u64>,
+        expiry: Option<i64>,
+    ) -> Result<()> {
+        bundle::list_bundle(
+            ctx,
+            program_as_signer_bump,
+            bundle_id,
+            price,
+            allocations,
+            expiry,
+        )
+    }
+
+    /// Buy every item of a bundle in one instruction, each item being sold at its allocation like `execute_sale`.
+    pub fn buy_bundle<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyBundle<'info>>,
+        escrow_payment_bump: u8,
+        program_as_signer_bump: u8,
+        bundle_id: u64,
+        referrer: Option<Pubkey>,
+    ) -> Result<()> {
+        bundle::buy_bundle(
+            ctx,
+            escrow_payment_bump,
+            program_as_signer_bump,
+            bundle_id,
+            referrer,
+        )
+    }
+
+    /// Cancel a bundle listing and the listings of its items.
+    pub fn cancel_bundle<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelBundle<'info>>,
+        bundle_id: u64,
+    ) -> Result<()> {
+        bundle::cancel_bundle(ctx, bundle_id)
+    }
+
+    /// Offer a token, optionally plus `cash_amount` of the treasury mint, in exchange for a token of another mint.
+    pub fn make_swap_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, MakeSwapOffer<'info>>,
+        escrow_payment_bump: u8,
+        program_as_signer_bump: u8,
+        cash_amount: u64,
+    ) -> Result<()> {
+        swap::make_swap_offer(ctx, escrow_payment_bump, program_as_signer_bump, cash_amount)
+    }
+
+    /// Accept a swap offer, exchanging both tokens and the cash leg atomically.
+    pub fn accept_swap_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptSwapOffer<'info>>,
+        escrow_payment_bump: u8,
+        program_as_signer_bump: u8,
+    ) -> Result<()> {
+        swap::accept_swap_offer(ctx, escrow_payment_bump, program_as_signer_bump)
+    }
+
+    /// Cancel a swap offer.
+    pub fn cancel_swap_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelSwapOffer<'info>>,
+    ) -> Result<()> {
+        swap::cancel_swap_offer(ctx)
+    }
+
+    /// Buy several listings in one instruction, spending at most `max_total` and skipping listings already filled.
+    pub fn sweep<'info>(
+        ctx: Context<'_, '_, '_, 'info, Sweep<'info>>,
+        escrow_payment_bump: u8,
+        program_as_signer_bump: u8,
+        max_total: u64,
+        listings: Vec<SweepListing>,
+        referrer: Option<Pubkey>,
+    ) -> Result<()> {
+        sweep::sweep(
+            ctx,
+            escrow_payment_bump,
+            program_as_signer_bump,
+            max_total,
+            listings,
+            referrer,
+        )
+    }
+
+    /// Replace the volume based fee discount tiers of the Auction House.
+    pub fn set_fee_tiers<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetFeeTiers<'info>>,
+        tiers: Vec<FeeTier>,
+    ) -> Result<()> {
+        fee_tiers::set_fee_tiers(ctx, tiers)
+    }
+
+    /// Create the account tracking a wallet's trailing volume for fee tiers.
+    pub fn create_wallet_stats<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateWalletStats<'info>>,
+    ) -> Result<()> {
+        fee_tiers::create_wallet_stats(ctx)
+    }
+
+    /// Approve a referrer, paid `share_basis_points` of the house fee on the sales it refers.
+    pub fn add_referrer<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddReferrer<'info>>,
+        share_basis_points: u16,
+    ) -> Result<()> {
+        referral::add_referrer(ctx, share_basis_points)
+    }
+
+    /// Change the house fee share of an approved referrer.
+    pub fn update_referrer<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateReferrer<'info>>,
+        share_basis_points: u16,
+    ) -> Result<()> {
+        referral::update_referrer(ctx, share_basis_points)
+    }
+
+    /// Revoke an approved referrer, returning its account rent to the authority.
+    pub fn remove_referrer<'info>(
+        ctx: Context<'_, '_, '_, 'info, RemoveReferrer<'info>>,
+    ) -> Result<()> {
+        referral::remove_referrer(ctx)
+    }
+
+    /// Deposit `amount` into the escrow payment account for your specific wallet, on behalf of the delegated auctioneer.
+    pub fn auctioneer_deposit<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerDeposit<'info>>,
+        escrow_payment_bump: u8,
+        amount: u64,
+    ) -> Result<()> {
+        deposit::auctioneer_deposit(ctx, escrow_payment_bump, amount)
+    }
+
+    /// Withdraw `amount` from the escrow payment account for your specific wallet, on behalf of the delegated auctioneer.
+    pub fn auctioneer_withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerWithdraw<'info>>,
+        escrow_payment_bump: u8,
+        amount: u64,
+    ) -> Result<()> {
+        withdraw::auctioneer_withdraw(ctx, escrow_payment_bump, amount)
+    }
+
+    /// Create a sell bid on behalf of the delegated auctioneer.
+    pub fn auctioneer_sell<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerSell<'info>>,
+        trade_state_bump: u8,
+        free_trade_state_bump: u8,
+        program_as_signer_bump: u8,
+        buyer_price: u64,
+        token_size: u64,
+        expiry: Option<i64>,
+    ) -> Result<()> {
+        sell::auctioneer_sell(
+            ctx,
+            trade_state_bump,
+            free_trade_state_bump,
+            program_as_signer_bump,
+            buyer_price,
+            token_size,
+            expiry,
+        )
+    }
+
+    /// Create a private bid on behalf of the delegated auctioneer.
+    pub fn auctioneer_buy<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerBuy<'info>>,
+        trade_state_bump: u8,
+        escrow_payment_bump: u8,
+        buyer_price: u64,
+        token_size: u64,
+        expiry: Option<i64>,
+    ) -> Result<()> {
+        auctioneer_private_bid(
+            ctx,
+            trade_state_bump,
+            escrow_payment_bump,
+            buyer_price,
+            token_size,
+            expiry,
+        )
+    }
+
+    /// Create a public bid on behalf of the delegated auctioneer.
+    pub fn auctioneer_public_buy<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerPublicBuy<'info>>,
+        trade_state_bump: u8,
+        escrow_payment_bump: u8,
+        buyer_price: u64,
+        token_size: u64,
+        expiry: Option<i64>,
+    ) -> Result<()> {
+        auctioneer_public_bid(
+            ctx,
+            trade_state_bump,
+            escrow_payment_bump,
+            buyer_price,
+            token_size,
+            expiry,
+        )
+    }
+
+    /// Execute a sale on behalf of the delegated auctioneer.
+    pub fn auctioneer_execute_sale<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerExecuteSale<'info>>,
+        escrow_payment_bump: u8,
+        _free_trade_state_bump: u8,
+        program_as_signer_bump: u8,
+        seller_trade_state_bump: u8,
+        buyer_price: u64,
+        token_size: u64,
+    ) -> Result<()> {
+        execute_sale::auctioneer_execute_sale(
+            ctx,
+            escrow_payment_bump,
+            _free_trade_state_bump,
+            program_as_signer_bump,
+            buyer_price,
+            token_size,
+        )
+    }
+
+    /// Cancel a bid or ask on behalf of the delegated auctioneer.
+    pub fn auctioneer_cancel<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuctioneerCancel<'info>>,
+        buyer_price: u64,
+        token_size: u64,
+    ) -> Result<()> {
+        cancel::auctioneer_cancel(ctx, buyer_price, token_size)
+    }
+
+    /// Create a listing receipt for the `sell` instruction preceding this one in the transaction. Listings made by partial sales, auctions, collection offers and bundles have no receipts.
+    pub fn print_listing_receipt<'info>(
+        ctx: Context<'_, '_, '_, 'info, PrintListingReceipt<'info>>,
+        receipt_bump: u8,
+    ) -> Result<()> {
+        receipt::print_listing_receipt(ctx, receipt_bump)
+    }
+
+    /// Mark a listing receipt as canceled after the `cancel` instruction preceding this one in the transaction.
+    pub fn cancel_listing_receipt<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelListingReceipt<'info>>,
+    ) -> Result<()> {
+        receipt::cancel_listing_receipt(ctx)
+    }
+
+    /// Create a bid receipt for the `buy` or `public_buy` instruction preceding this one in the transaction. Auction, collection offer and sweep bids have no receipts.
+    pub fn print_bid_receipt<'info>(
+        ctx: Context<'_, '_, '_, 'info, PrintBidReceipt<'info>>,
+        receipt_bump: u8,
+    ) -> Result<()> {
+        receipt::print_bid_receipt(ctx, receipt_bump)
+    }
+
+    /// Mark a bid receipt as canceled after the `cancel` instruction preceding this one in the transaction.
+    pub fn cancel_bid_receipt<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelBidReceipt<'info>>,
+    ) -> Result<()> {
+        receipt::cancel_bid_receipt(ctx)
+    }
+
+    /// Create a purchase receipt for the `execute_sale` instruction preceding this one in the transaction. Sales through the other execution paths have no receipts.
+    pub fn print_purchase_receipt<'info>(
+        ctx: Context<'_, '_, '_, 'info, PrintPurchaseReceipt<'info>>,
+        purchase_receipt_bump: u8,
+    ) -> Result<()> {
+        receipt::print_purchase_receipt(ctx, purchase_receipt_bump)
+    }
 }
 
 #[derive(Accounts)]
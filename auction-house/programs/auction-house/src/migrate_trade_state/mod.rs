@@ -0,0 +1,164 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke, system_instruction},
+    AnchorDeserialize,
+};
+use anchor_spl::token::TokenAccount;
+
+use crate::constant::*;
+use crate::errors::AuctionHouseError;
+use crate::state::{AuctionHouse, TradeSide, TradeStateData};
+use crate::utils::{assert_derivation, assert_valid_trade_state, write_trade_state};
+
+/// Accounts for the [`migrate_trade_state` handler](auction_house/fn.migrate_trade_state.html).
+#[derive(Accounts)]
+#[instruction(buyer_price: u64, token_size: u64, side: TradeSide)]
+pub struct MigrateTradeState<'info> {
+    /// CHECK: Validated by the trade state derivation and as a signer in migrate_trade_state.
+    /// Wallet that placed the order.
+    pub wallet: UncheckedAccount<'info>,
+
+    /// CHECK: Validated as a signer in migrate_trade_state.
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// SPL token account the order was placed on. For public bids, any token account of the mint.
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Validated in migrate_trade_state.
+    /// Legacy 1-byte trade state PDA account.
+    #[account(mut)]
+    pub trade_state: UncheckedAccount<'info>,
+
+    /// Account paying for the extra rent of the migrated trade state.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// NOTE: Grow a legacy 1-byte trade state into the versioned layout. Its PDA address is unchanged.
+/// The legacy layout does not record the side or age of the order, so the wallet or the authority states the side
+/// and the migrated order is treated as older than any order created since.
+pub fn migrate_trade_state<'info>(
+    ctx: Context<'_, '_, '_, 'info, MigrateTradeState<'info>>,
+    buyer_price: u64,
+    token_size: u64,
+    side: TradeSide,
+) -> Result<()> {
+    let wallet = &ctx.accounts.wallet;
+    let authority = &ctx.accounts.authority;
+    let token_account = &ctx.accounts.token_account;
+    let auction_house = &ctx.accounts.auction_house;
+    let trade_state = &ctx.accounts.trade_state;
+    let payer = &ctx.accounts.payer;
+    let system_program = &ctx.accounts.system_program;
+
+    if !wallet.is_signer && !authority.is_signer {
+        return Err(AuctionHouseError::NoValidSignerPresent.into());
+    }
+
+    if trade_state.data_is_empty() {
+        return Err(AuctionHouseError::UninitializedAccount.into());
+    }
+    if trade_state.data_len() != LEGACY_TRADE_STATE_SIZE {
+        return Err(AuctionHouseError::TradeStateAlreadyMigrated.into());
+    }
+    if trade_state.owner != &crate::id() {
+        return Err(AuctionHouseError::IncorrectOwner.into());
+    }
+
+    let trade_state_bump = trade_state.try_borrow_data()?[0];
+
+    assert_valid_trade_state(
+        &wallet.key(),
+        auction_house,
+        buyer_price,
+        token_size,
+        &trade_state.to_account_info(),
+        &token_account.mint,
+        &token_account.key(),
+        trade_state_bump,
+    )?;
+
+    // NOTE: only public bids are derived without the token account
+    let public = assert_derivation(
+        &crate::id(),
+        &trade_state.to_account_info(),
+        &[
+            PREFIX.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_account.mint.as_ref(),
+            &buyer_price.to_le_bytes(),
+            &token_size.to_le_bytes(),
+        ],
+    )
+    .is_err();
+
+    // NOTE: a listing is always private and on a token account held by the wallet
+    if side == TradeSide::Sell && (public || token_account.owner != wallet.key()) {
+        return Err(AuctionHouseError::TradeSideMismatch.into());
+    }
+
+    let rent_diff = Rent::get()?
+        .minimum_balance(TRADE_STATE_SIZE)
+        .saturating_sub(trade_state.lamports());
+    if rent_diff > 0 {
+        invoke(
+            &system_instruction::transfer(&payer.key(), &trade_state.key(), rent_diff),
+            &[
+                payer.to_account_info(),
+                trade_state.to_account_info(),
+                system_program.to_account_info(),
+            ],
+        )?;
+    }
+
+    trade_state.realloc(TRADE_STATE_SIZE, true)?;
+
+    write_trade_state(
+        &trade_state.to_account_info(),
+        trade_state_bump,
+        &TradeStateData {
+            version: TRADE_STATE_VERSION,
+            side,
+            flags: if public {
+                TRADE_STATE_FLAG_MIGRATED | TRADE_STATE_FLAG_PUBLIC
+            } else {
+                TRADE_STATE_FLAG_MIGRATED
+            },
+            auction_house: auction_house.key(),
+            wallet: wallet.key(),
+            token_account: if public {
+                Pubkey::default()
+            } else {
+                token_account.key()
+            },
+            mint: token_account.mint,
+            price: buyer_price,
+            token_size,
+            created_slot: 0,
+            created_at: 0,
+            expiry: 0,
+            fee_payer: payer.key(),
+            referrer: Pubkey::default(),
+        },
+    )?;
+
+    Ok(())
+}
@@ -1 +1,436 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, sysvar},
+    AnchorDeserialize,
+};
 
+use crate::constant::*;
+use crate::errors::AuctionHouseError;
+use crate::state::{BidReceipt, ListingReceipt, PurchaseReceipt};
+use crate::utils::{assert_derivation, assert_keys_equal, create_or_allocate_account_raw};
+
+/// Accounts for the [`print_listing_receipt` handler](auction_house/fn.print_listing_receipt.html).
+#[derive(Accounts)]
+#[instruction(receipt_bump: u8)]
+pub struct PrintListingReceipt<'info> {
+    /// CHECK: Validated in print_listing_receipt.
+    /// Listing receipt PDA account, seeded by the seller trade state.
+    #[account(mut)]
+    pub receipt: UncheckedAccount<'info>,
+
+    /// Account paying for the receipt.
+    #[account(mut)]
+    pub bookkeeper: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// CHECK: Address checked in constraint.
+    /// Instructions sysvar account used to introspect the `sell` instruction.
+    #[account(address = sysvar::instructions::id())]
+    pub instruction: UncheckedAccount<'info>,
+}
+
+/// Accounts for the [`cancel_listing_receipt` handler](auction_house/fn.cancel_listing_receipt.html).
+#[derive(Accounts)]
+pub struct CancelListingReceipt<'info> {
+    /// Listing receipt PDA account of the canceled listing.
+    #[account(mut)]
+    pub receipt: Box<Account<'info, ListingReceipt>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Address checked in constraint.
+    /// Instructions sysvar account used to introspect the `cancel` instruction.
+    #[account(address = sysvar::instructions::id())]
+    pub instruction: UncheckedAccount<'info>,
+}
+
+/// Accounts for the [`print_bid_receipt` handler](auction_house/fn.print_bid_receipt.html).
+#[derive(Accounts)]
+#[instruction(receipt_bump: u8)]
+pub struct PrintBidReceipt<'info> {
+    /// CHECK: Validated in print_bid_receipt.
+    /// Bid receipt PDA account, seeded by the buyer trade state.
+    #[account(mut)]
+    pub receipt: UncheckedAccount<'info>,
+
+    /// Account paying for the receipt.
+    #[account(mut)]
+    pub bookkeeper: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// CHECK: Address checked in constraint.
+    /// Instructions sysvar account used to introspect the `buy` or `public_buy` instruction.
+    #[account(address = sysvar::instructions::id())]
+    pub instruction: UncheckedAccount<'info>,
+}
+
+/// Accounts for the [`cancel_bid_receipt` handler](auction_house/fn.cancel_bid_receipt.html).
+#[derive(Accounts)]
+pub struct CancelBidReceipt<'info> {
+    /// Bid receipt PDA account of the canceled bid.
+    #[account(mut)]
+    pub receipt: Box<Account<'info, BidReceipt>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Address checked in constraint.
+    /// Instructions sysvar account used to introspect the `cancel` instruction.
+    #[account(address = sysvar::instructions::id())]
+    pub instruction: UncheckedAccount<'info>,
+}
+
+/// Accounts for the [`print_purchase_receipt` handler](auction_house/fn.print_purchase_receipt.html).
+#[derive(Accounts)]
+#[instruction(purchase_receipt_bump: u8)]
+pub struct PrintPurchaseReceipt<'info> {
+    /// CHECK: Validated in print_purchase_receipt.
+    /// Purchase receipt PDA account, seeded by the seller and buyer trade states.
+    #[account(mut)]
+    pub purchase_receipt: UncheckedAccount<'info>,
+
+    /// Listing receipt of the filled listing.
+    #[account(mut)]
+    pub listing_receipt: Box<Account<'info, ListingReceipt>>,
+
+    /// Bid receipt of the filled bid.
+    #[account(mut)]
+    pub bid_receipt: Box<Account<'info, BidReceipt>>,
+
+    /// Account paying for the receipt.
+    #[account(mut)]
+    pub bookkeeper: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// CHECK: Address checked in constraint.
+    /// Instructions sysvar account used to introspect the `execute_sale` instruction.
+    #[account(address = sysvar::instructions::id())]
+    pub instruction: UncheckedAccount<'info>,
+}
+
+/// NOTE: Load the instruction right before this one and make sure it is one of ours with an expected discriminator.
+fn get_prev_instruction(
+    instruction_account: &UncheckedAccount,
+    discriminators: &[[u8; 8]],
+) -> Result<Instruction> {
+    let prev_instruction =
+        sysvar::instructions::get_instruction_relative(-1, &instruction_account.to_account_info())?;
+
+    assert_keys_equal(prev_instruction.program_id, crate::id())?;
+
+    if prev_instruction.data.len() < 8
+        || !discriminators
+            .iter()
+            .any(|discriminator| prev_instruction.data[..8] == discriminator[..])
+    {
+        return Err(AuctionHouseError::InstructionMismatch.into());
+    }
+
+    Ok(prev_instruction)
+}
+
+/// NOTE: Grab an account key by its position in the introspected instruction's accounts struct.
+fn get_instruction_account_key(instruction: &Instruction, index: usize) -> Result<Pubkey> {
+    instruction
+        .accounts
+        .get(index)
+        .map(|account| account.pubkey)
+        .ok_or_else(|| AuctionHouseError::InstructionMismatch.into())
+}
+
+/// NOTE: Check the receipt PDA derivation and create it, paid by the bookkeeper, if it does not exist yet.
+fn create_receipt_if_not_present<'info>(
+    receipt_info: &AccountInfo<'info>,
+    bookkeeper: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    rent: &Sysvar<'info, Rent>,
+    seeds: &[&[u8]],
+    receipt_bump: u8,
+    size: usize,
+) -> Result<()> {
+    let canonical_bump = assert_derivation(&crate::id(), receipt_info, seeds)?;
+
+    if canonical_bump != receipt_bump {
+        return Err(AuctionHouseError::DerivedKeyInvalid.into());
+    }
+
+    if receipt_info.data_is_empty() {
+        let bump_bytes = [receipt_bump];
+        let mut receipt_seeds = seeds.to_vec();
+        receipt_seeds.push(&bump_bytes);
+
+        create_or_allocate_account_raw(
+            crate::id(),
+            receipt_info,
+            &rent.to_account_info(),
+            system_program,
+            bookkeeper,
+            size,
+            &[],
+            &receipt_seeds,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// NOTE: Create the listing receipt for the `sell` instruction right before this one. An existing receipt is only
+/// replaced once the order it records was canceled or filled.
+pub fn print_listing_receipt<'info>(
+    ctx: Context<'_, '_, '_, 'info, PrintListingReceipt<'info>>,
+    receipt_bump: u8,
+) -> Result<()> {
+    let receipt_account = &ctx.accounts.receipt;
+    let bookkeeper = &ctx.accounts.bookkeeper;
+    let system_program = &ctx.accounts.system_program;
+    let rent = &ctx.accounts.rent;
+    let instruction = &ctx.accounts.instruction;
+    let clock = Clock::get()?;
+
+    let prev_instruction = get_prev_instruction(
+        instruction,
+        &[SELL_DISCRIMINATOR, AUCTIONEER_SELL_DISCRIMINATOR],
+    )?;
+    let sell_data = crate::instruction::Sell::deserialize(&mut &prev_instruction.data[8..])
+        .map_err(|_| AuctionHouseError::InstructionMismatch)?;
+
+    // NOTE: indices follow the field order of the `Sell` accounts struct, which `AuctioneerSell` extends
+    let seller = get_instruction_account_key(&prev_instruction, 0)?;
+    let metadata = get_instruction_account_key(&prev_instruction, 2)?;
+    let auction_house = get_instruction_account_key(&prev_instruction, 4)?;
+    let trade_state = get_instruction_account_key(&prev_instruction, 6)?;
+
+    let receipt_info = receipt_account.to_account_info();
+
+    if !receipt_info.data_is_empty() {
+        let existing = ListingReceipt::try_deserialize(&mut &receipt_info.try_borrow_data()?[..])?;
+        if existing.canceled_at.is_none() && existing.purchase_receipt.is_none() {
+            return Err(AuctionHouseError::ReceiptAlreadyExists.into());
+        }
+    }
+
+    create_receipt_if_not_present(
+        &receipt_info,
+        &bookkeeper.to_account_info(),
+        system_program,
+        rent,
+        &[LISTING_RECEIPT_PREFIX.as_bytes(), trade_state.as_ref()],
+        receipt_bump,
+        LISTING_RECEIPT_SIZE,
+    )?;
+
+    let receipt = ListingReceipt {
+        trade_state,
+        bookkeeper: bookkeeper.key(),
+        auction_house,
+        seller,
+        metadata,
+        purchase_receipt: None,
+        price: sell_data.buyer_price,
+        token_size: sell_data.token_size,
+        bump: receipt_bump,
+        trade_state_bump: sell_data.trade_state_bump,
+        created_at: clock.unix_timestamp,
+        canceled_at: None,
+    };
+
+    let mut data = receipt_info.try_borrow_mut_data()?;
+    receipt.try_serialize(&mut *data)?;
+
+    Ok(())
+}
+
+/// NOTE: Mark the listing receipt as canceled when it follows the `cancel` of its trade state.
+pub fn cancel_listing_receipt<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelListingReceipt<'info>>,
+) -> Result<()> {
+    let receipt = &mut ctx.accounts.receipt;
+    let instruction = &ctx.accounts.instruction;
+    let clock = Clock::get()?;
+
+    let prev_instruction = get_prev_instruction(
+        instruction,
+        &[CANCEL_DISCRIMINATOR, AUCTIONEER_CANCEL_DISCRIMINATOR],
+    )?;
+
+    // NOTE: index follows the field order of the `Cancel` accounts struct, which `AuctioneerCancel` extends
+    let trade_state = get_instruction_account_key(&prev_instruction, 6)?;
+    assert_keys_equal(receipt.trade_state, trade_state)?;
+
+    receipt.canceled_at = Some(clock.unix_timestamp);
+
+    Ok(())
+}
+
+/// NOTE: Create the bid receipt for the `buy` or `public_buy` instruction right before this one.
+/// An existing receipt is only replaced once the order it records was canceled or filled.
+pub fn print_bid_receipt<'info>(
+    ctx: Context<'_, '_, '_, 'info, PrintBidReceipt<'info>>,
+    receipt_bump: u8,
+) -> Result<()> {
+    let receipt_account = &ctx.accounts.receipt;
+    let bookkeeper = &ctx.accounts.bookkeeper;
+    let system_program = &ctx.accounts.system_program;
+    let rent = &ctx.accounts.rent;
+    let instruction = &ctx.accounts.instruction;
+    let clock = Clock::get()?;
+
+    let prev_instruction = get_prev_instruction(
+        instruction,
+        &[
+            BUY_DISCRIMINATOR,
+            PUBLIC_BUY_DISCRIMINATOR,
+            AUCTIONEER_BUY_DISCRIMINATOR,
+            AUCTIONEER_PUBLIC_BUY_DISCRIMINATOR,
+        ],
+    )?;
+    let is_public = prev_instruction.data[..8] == PUBLIC_BUY_DISCRIMINATOR
+        || prev_instruction.data[..8] == AUCTIONEER_PUBLIC_BUY_DISCRIMINATOR;
+
+    // NOTE: every bid instruction shares the same argument layout
+    let buy_data = crate::instruction::Buy::deserialize(&mut &prev_instruction.data[8..])
+        .map_err(|_| AuctionHouseError::InstructionMismatch)?;
+
+    // NOTE: indices follow the field order of the `Buy` and `PublicBuy` accounts structs, which the auctioneer variants
+    // extend
+    let buyer = get_instruction_account_key(&prev_instruction, 0)?;
+    let token_account = get_instruction_account_key(&prev_instruction, 4)?;
+    let metadata = get_instruction_account_key(&prev_instruction, 5)?;
+    let auction_house = get_instruction_account_key(&prev_instruction, 8)?;
+    let trade_state = get_instruction_account_key(&prev_instruction, 10)?;
+
+    let receipt_info = receipt_account.to_account_info();
+
+    if !receipt_info.data_is_empty() {
+        let existing = BidReceipt::try_deserialize(&mut &receipt_info.try_borrow_data()?[..])?;
+        if existing.canceled_at.is_none() && existing.purchase_receipt.is_none() {
+            return Err(AuctionHouseError::ReceiptAlreadyExists.into());
+        }
+    }
+
+    create_receipt_if_not_present(
+        &receipt_info,
+        &bookkeeper.to_account_info(),
+        system_program,
+        rent,
+        &[BID_RECEIPT_PREFIX.as_bytes(), trade_state.as_ref()],
+        receipt_bump,
+        BID_RECEIPT_SIZE,
+    )?;
+
+    let receipt = BidReceipt {
+        trade_state,
+        bookkeeper: bookkeeper.key(),
+        auction_house,
+        buyer,
+        metadata,
+        token_account: if is_public { None } else { Some(token_account) },
+        purchase_receipt: None,
+        price: buy_data.buyer_price,
+        token_size: buy_data.token_size,
+        bump: receipt_bump,
+        trade_state_bump: buy_data.trade_state_bump,
+        created_at: clock.unix_timestamp,
+        canceled_at: None,
+    };
+
+    let mut data = receipt_info.try_borrow_mut_data()?;
+    receipt.try_serialize(&mut *data)?;
+
+    Ok(())
+}
+
+/// NOTE: Mark the bid receipt as canceled when it follows the `cancel` of its trade state.
+pub fn cancel_bid_receipt<'info>(ctx: Context<'_, '_, '_, 'info, CancelBidReceipt<'info>>) -> Result<()> {
+    let receipt = &mut ctx.accounts.receipt;
+    let instruction = &ctx.accounts.instruction;
+    let clock = Clock::get()?;
+
+    let prev_instruction = get_prev_instruction(
+        instruction,
+        &[CANCEL_DISCRIMINATOR, AUCTIONEER_CANCEL_DISCRIMINATOR],
+    )?;
+
+    // NOTE: index follows the field order of the `Cancel` accounts struct, which `AuctioneerCancel` extends
+    let trade_state = get_instruction_account_key(&prev_instruction, 6)?;
+    assert_keys_equal(receipt.trade_state, trade_state)?;
+
+    receipt.canceled_at = Some(clock.unix_timestamp);
+
+    Ok(())
+}
+
+/// NOTE: Create the purchase receipt for the `execute_sale` instruction right before this one and link it from the listing and bid receipts.
+pub fn print_purchase_receipt<'info>(
+    ctx: Context<'_, '_, '_, 'info, PrintPurchaseReceipt<'info>>,
+    purchase_receipt_bump: u8,
+) -> Result<()> {
+    let purchase_receipt_account = &ctx.accounts.purchase_receipt;
+    let bookkeeper = &ctx.accounts.bookkeeper;
+    let system_program = &ctx.accounts.system_program;
+    let rent = &ctx.accounts.rent;
+    let instruction = &ctx.accounts.instruction;
+    let clock = Clock::get()?;
+
+    let prev_instruction = get_prev_instruction(
+        instruction,
+        &[EXECUTE_SALE_DISCRIMINATOR, AUCTIONEER_EXECUTE_SALE_DISCRIMINATOR],
+    )?;
+    let execute_sale_data =
+        crate::instruction::ExecuteSale::deserialize(&mut &prev_instruction.data[8..])
+            .map_err(|_| AuctionHouseError::InstructionMismatch)?;
+
+    // NOTE: indices follow the field order of the `ExecuteSale` accounts struct, which `AuctioneerExecuteSale` extends
+    let buyer = get_instruction_account_key(&prev_instruction, 0)?;
+    let seller = get_instruction_account_key(&prev_instruction, 1)?;
+    let metadata = get_instruction_account_key(&prev_instruction, 4)?;
+    let auction_house = get_instruction_account_key(&prev_instruction, 10)?;
+    let buyer_trade_state = get_instruction_account_key(&prev_instruction, 13)?;
+    let seller_trade_state = get_instruction_account_key(&prev_instruction, 14)?;
+
+    assert_keys_equal(ctx.accounts.listing_receipt.trade_state, seller_trade_state)?;
+    assert_keys_equal(ctx.accounts.bid_receipt.trade_state, buyer_trade_state)?;
+
+    let purchase_receipt_info = purchase_receipt_account.to_account_info();
+
+    create_receipt_if_not_present(
+        &purchase_receipt_info,
+        &bookkeeper.to_account_info(),
+        system_program,
+        rent,
+        &[
+            PURCHASE_RECEIPT_PREFIX.as_bytes(),
+            seller_trade_state.as_ref(),
+            buyer_trade_state.as_ref(),
+        ],
+        purchase_receipt_bump,
+        PURCHASE_RECEIPT_SIZE,
+    )?;
+
+    let purchase_receipt = PurchaseReceipt {
+        bookkeeper: bookkeeper.key(),
+        buyer,
+        seller,
+        auction_house,
+        metadata,
+        token_size: execute_sale_data.token_size,
+        price: execute_sale_data.buyer_price,
+        bump: purchase_receipt_bump,
+        created_at: clock.unix_timestamp,
+    };
+
+    {
+        let mut data = purchase_receipt_info.try_borrow_mut_data()?;
+        purchase_receipt.try_serialize(&mut *data)?;
+    }
+
+    ctx.accounts.listing_receipt.purchase_receipt = Some(purchase_receipt_account.key());
+    ctx.accounts.bid_receipt.purchase_receipt = Some(purchase_receipt_account.key());
+
+    Ok(())
+}
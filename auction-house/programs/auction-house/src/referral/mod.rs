@@ -0,0 +1,169 @@
+use anchor_lang::{prelude::*, AnchorDeserialize};
+
+use crate::constant::*;
+use crate::errors::AuctionHouseError;
+use crate::state::{AuctionHouse, Referrer};
+
+/// Accounts for the [`add_referrer` handler](auction_house/fn.add_referrer.html).
+#[derive(Accounts)]
+pub struct AddReferrer<'info> {
+    /// Account paying for the referrer account.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Auction House instance authority account.
+    pub authority: Signer<'info>,
+
+    /// CHECK: The authority can approve any wallet.
+    /// Wallet receiving the referral payouts.
+    pub wallet: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// Referrer account, one per wallet and Auction House.
+    #[account(
+        init,
+        payer = payer,
+        space = REFERRER_SIZE,
+        seeds = [
+            REFERRER.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump
+    )]
+    pub referrer: Box<Account<'info, Referrer>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// NOTE: Approve a referrer, paid `share_basis_points` of the house fee on the sales it refers.
+pub fn add_referrer<'info>(
+    ctx: Context<'_, '_, '_, 'info, AddReferrer<'info>>,
+    share_basis_points: u16,
+) -> Result<()> {
+    if share_basis_points > 10000 {
+        return Err(AuctionHouseError::InvalidBasisPoints.into());
+    }
+
+    let referrer = &mut ctx.accounts.referrer;
+
+    referrer.auction_house = ctx.accounts.auction_house.key();
+    referrer.wallet = ctx.accounts.wallet.key();
+    referrer.share_basis_points = share_basis_points;
+    referrer.bump = *ctx
+        .bumps
+        .get("referrer")
+        .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?;
+
+    let auction_house = &mut ctx.accounts.auction_house;
+    auction_house.approved_referrers = auction_house
+        .approved_referrers
+        .checked_add(1)
+        .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+    Ok(())
+}
+
+/// Accounts for the [`update_referrer` handler](auction_house/fn.update_referrer.html).
+#[derive(Accounts)]
+pub struct UpdateReferrer<'info> {
+    /// Auction House instance authority account.
+    pub authority: Signer<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// Referrer account being updated.
+    #[account(
+        mut,
+        seeds = [
+            REFERRER.as_bytes(),
+            auction_house.key().as_ref(),
+            referrer.wallet.as_ref()
+        ],
+        bump = referrer.bump,
+        has_one = auction_house
+    )]
+    pub referrer: Box<Account<'info, Referrer>>,
+}
+
+/// NOTE: Change the house fee share of an approved referrer.
+pub fn update_referrer<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateReferrer<'info>>,
+    share_basis_points: u16,
+) -> Result<()> {
+    if share_basis_points > 10000 {
+        return Err(AuctionHouseError::InvalidBasisPoints.into());
+    }
+
+    ctx.accounts.referrer.share_basis_points = share_basis_points;
+
+    Ok(())
+}
+
+/// Accounts for the [`remove_referrer` handler](auction_house/fn.remove_referrer.html).
+#[derive(Accounts)]
+pub struct RemoveReferrer<'info> {
+    /// Auction House instance authority account, receiving the rent of the referrer account.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// Referrer account being removed.
+    #[account(
+        mut,
+        close = authority,
+        seeds = [
+            REFERRER.as_bytes(),
+            auction_house.key().as_ref(),
+            referrer.wallet.as_ref()
+        ],
+        bump = referrer.bump,
+        has_one = auction_house
+    )]
+    pub referrer: Box<Account<'info, Referrer>>,
+}
+
+/// NOTE: Revoke a referrer's approval. Bids recording it are still filled, without paying it a share of the house fee.
+pub fn remove_referrer<'info>(ctx: Context<'_, '_, '_, 'info, RemoveReferrer<'info>>) -> Result<()> {
+    let auction_house = &mut ctx.accounts.auction_house;
+    auction_house.approved_referrers = auction_house
+        .approved_referrers
+        .checked_sub(1)
+        .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+    Ok(())
+}
@@ -0,0 +1,386 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke_signed, system_instruction},
+    AnchorDeserialize,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token},
+};
+
+use crate::constant::*;
+use crate::errors::AuctionHouseError;
+use crate::state::{AuctionHouse, RevenueRecipient, RevenueSplit};
+use crate::utils::{assert_initialized, assert_is_ata, assert_keys_equal, make_ata};
+
+fn assert_valid_recipients(recipients: &[RevenueRecipient]) -> Result<()> {
+    if recipients.is_empty() || recipients.len() > MAX_REVENUE_RECIPIENTS {
+        return Err(AuctionHouseError::InvalidRevenueSplit.into());
+    }
+
+    let mut total_share: u16 = 0;
+    for (i, recipient) in recipients.iter().enumerate() {
+        if recipients[..i].iter().any(|r| r.wallet == recipient.wallet) {
+            return Err(AuctionHouseError::InvalidRevenueSplit.into());
+        }
+        total_share += recipient.share as u16;
+    }
+
+    if total_share != 100 {
+        return Err(AuctionHouseError::InvalidRevenueSplit.into());
+    }
+
+    Ok(())
+}
+
+/// Accounts for the [`create_revenue_split` handler](auction_house/fn.create_revenue_split.html).
+#[derive(Accounts)]
+pub struct CreateRevenueSplit<'info> {
+    /// Account paying for the revenue split account.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Auction House instance authority account.
+    pub authority: Signer<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// Revenue split account, one per Auction House.
+    #[account(
+        init,
+        payer = payer,
+        space = REVENUE_SPLIT_SIZE,
+        seeds = [
+            REVENUE_SPLIT.as_bytes(),
+            auction_house.key().as_ref()
+        ],
+        bump
+    )]
+    pub revenue_split: Box<Account<'info, RevenueSplit>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// NOTE: Split treasury withdrawals across up to MAX_REVENUE_RECIPIENTS wallets, by percentage shares summing
+/// to 100. From then on the treasury can only be withdrawn through withdraw_from_treasury_split.
+pub fn create_revenue_split<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateRevenueSplit<'info>>,
+    recipients: Vec<RevenueRecipient>,
+) -> Result<()> {
+    assert_valid_recipients(&recipients)?;
+
+    let revenue_split = &mut ctx.accounts.revenue_split;
+
+    revenue_split.auction_house = ctx.accounts.auction_house.key();
+    revenue_split.recipients = recipients;
+    revenue_split.bump = *ctx
+        .bumps
+        .get("revenue_split")
+        .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?;
+
+    ctx.accounts.auction_house.has_revenue_split = true;
+
+    Ok(())
+}
+
+/// Accounts for the [`update_revenue_split` handler](auction_house/fn.update_revenue_split.html).
+#[derive(Accounts)]
+pub struct UpdateRevenueSplit<'info> {
+    /// Auction House instance authority account.
+    pub authority: Signer<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// Revenue split account being updated.
+    #[account(
+        mut,
+        seeds = [
+            REVENUE_SPLIT.as_bytes(),
+            auction_house.key().as_ref()
+        ],
+        bump = revenue_split.bump,
+        has_one = auction_house
+    )]
+    pub revenue_split: Box<Account<'info, RevenueSplit>>,
+}
+
+/// NOTE: Replace the recipients of the revenue split.
+pub fn update_revenue_split<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateRevenueSplit<'info>>,
+    recipients: Vec<RevenueRecipient>,
+) -> Result<()> {
+    assert_valid_recipients(&recipients)?;
+
+    ctx.accounts.revenue_split.recipients = recipients;
+
+    Ok(())
+}
+
+/// Accounts for the [`remove_revenue_split` handler](auction_house/fn.remove_revenue_split.html).
+#[derive(Accounts)]
+pub struct RemoveRevenueSplit<'info> {
+    /// Auction House instance authority account, receiving the rent of the revenue split account.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Auction House instance treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance treasury PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            TREASURY.as_bytes()
+        ],
+        bump = auction_house.treasury_bump
+    )]
+    pub auction_house_treasury: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_treasury
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// Revenue split account being removed.
+    #[account(
+        mut,
+        close = authority,
+        seeds = [
+            REVENUE_SPLIT.as_bytes(),
+            auction_house.key().as_ref()
+        ],
+        bump = revenue_split.bump,
+        has_one = auction_house
+    )]
+    pub revenue_split: Box<Account<'info, RevenueSplit>>,
+}
+
+/// NOTE: Remove the revenue split, handing treasury withdrawals back to withdraw_from_treasury. The treasury must
+/// have been paid out through the split first, so what it collected under the split cannot be withdrawn around it.
+pub fn remove_revenue_split<'info>(
+    ctx: Context<'_, '_, '_, 'info, RemoveRevenueSplit<'info>>,
+) -> Result<()> {
+    let treasury_info = ctx.accounts.auction_house_treasury.to_account_info();
+
+    let treasury_amount = if ctx.accounts.treasury_mint.key() == spl_token::native_mint::id() {
+        treasury_info.lamports()
+    } else {
+        assert_initialized::<spl_token::state::Account>(&treasury_info)?.amount
+    };
+
+    if treasury_amount > 0 {
+        return Err(AuctionHouseError::MustUseRevenueSplit.into());
+    }
+
+    ctx.accounts.auction_house.has_revenue_split = false;
+
+    Ok(())
+}
+
+/// Accounts for the [`withdraw_from_treasury_split` handler](auction_house/fn.withdraw_from_treasury_split.html).
+#[derive(Accounts)]
+pub struct WithdrawFromTreasurySplit<'info> {
+    /// Key paying SOL fees for creating recipient ATAs if needed.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Auction House instance treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance treasury PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            TREASURY.as_bytes()
+        ],
+        bump = auction_house.treasury_bump
+    )]
+    pub auction_house_treasury: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = treasury_mint,
+        has_one = auction_house_treasury
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// Revenue split of the Auction House.
+    #[account(
+        seeds = [
+            REVENUE_SPLIT.as_bytes(),
+            auction_house.key().as_ref()
+        ],
+        bump = revenue_split.bump,
+        has_one = auction_house
+    )]
+    pub revenue_split: Box<Account<'info, RevenueSplit>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub ata_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: Transfer `amount` of the treasury mint from the auction house treasury to the revenue split recipients.
+/// Anyone can crank it since the recipients are fixed. The remaining accounts hold, in recipient order, each
+/// recipient wallet followed by its treasury mint ATA when the treasury mint is not native. The last recipient
+/// also receives the rounding remainder.
+pub fn withdraw_from_treasury_split<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawFromTreasurySplit<'info>>,
+    amount: u64,
+) -> Result<()> {
+    // NOTE: extract all variables from ctx (WithdrawFromTreasurySplit struct)
+    let payer = &ctx.accounts.payer;
+    let treasury_mint = &ctx.accounts.treasury_mint;
+    let auction_house_treasury = &ctx.accounts.auction_house_treasury;
+    let auction_house = &ctx.accounts.auction_house;
+    let revenue_split = &ctx.accounts.revenue_split;
+    let token_program = &ctx.accounts.token_program;
+    let system_program = &ctx.accounts.system_program;
+    let ata_program = &ctx.accounts.ata_program;
+    let rent = &ctx.accounts.rent;
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+
+    let is_native = treasury_mint.key() == spl_token::native_mint::id();
+
+    let auction_house_key = auction_house.key();
+    let auction_house_seeds = [
+        PREFIX.as_bytes(),
+        auction_house.creator.as_ref(),
+        auction_house.treasury_mint.as_ref(),
+        &[auction_house.bump],
+    ];
+
+    let auction_house_treasury_seeds = [
+        PREFIX.as_bytes(),
+        auction_house_key.as_ref(),
+        TREASURY.as_bytes(),
+        &[auction_house.treasury_bump],
+    ];
+
+    let mut remaining_amount = amount;
+    let last = revenue_split.recipients.len() - 1;
+
+    for (i, recipient) in revenue_split.recipients.iter().enumerate() {
+        let recipient_share = if i == last {
+            remaining_amount
+        } else {
+            (recipient.share as u128)
+                .checked_mul(amount as u128)
+                .ok_or(AuctionHouseError::NumericalOverflow)?
+                .checked_div(100)
+                .ok_or(AuctionHouseError::NumericalOverflow)? as u64
+        };
+
+        remaining_amount = remaining_amount
+            .checked_sub(recipient_share)
+            .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+        let recipient_info = next_account_info(remaining_accounts)?;
+        assert_keys_equal(recipient.wallet, *recipient_info.key)?;
+
+        // NOTE: the SPL treasury token account is owned by the auction house,
+        // whereas the native treasury is a system account signed for by its own seeds.
+        if !is_native {
+            let recipient_token_account_info = next_account_info(remaining_accounts)?;
+
+            if recipient_token_account_info.data_is_empty() {
+                make_ata(
+                    recipient_token_account_info.to_account_info(),
+                    recipient_info.to_account_info(),
+                    treasury_mint.to_account_info(),
+                    payer.to_account_info(),
+                    ata_program.to_account_info(),
+                    token_program.to_account_info(),
+                    system_program.to_account_info(),
+                    rent.to_account_info(),
+                    &[],
+                )?;
+            }
+
+            assert_is_ata(
+                recipient_token_account_info,
+                recipient_info.key,
+                &treasury_mint.key(),
+            )?;
+
+            if recipient_share > 0 {
+                invoke_signed(
+                    &spl_token::instruction::transfer(
+                        token_program.key,
+                        &auction_house_treasury.key(),
+                        recipient_token_account_info.key,
+                        &auction_house.key(),
+                        &[],
+                        recipient_share,
+                    )?,
+                    &[
+                        auction_house_treasury.to_account_info(),
+                        recipient_token_account_info.clone(),
+                        token_program.to_account_info(),
+                        auction_house.to_account_info(),
+                    ],
+                    &[&auction_house_seeds],
+                )?;
+            }
+        } else if recipient_share > 0 {
+            invoke_signed(
+                &system_instruction::transfer(
+                    &auction_house_treasury.key(),
+                    recipient_info.key,
+                    recipient_share,
+                ),
+                &[
+                    auction_house_treasury.to_account_info(),
+                    recipient_info.clone(),
+                    system_program.to_account_info(),
+                ],
+                &[&auction_house_treasury_seeds],
+            )?;
+        }
+    }
+
+    Ok(())
+}
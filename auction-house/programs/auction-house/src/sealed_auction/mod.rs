@@ -0,0 +1,1441 @@
+use anchor_lang::{prelude::*, AnchorDeserialize};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::bid::bid_logic;
+use crate::cancel::{cancel_logic, Cancel};
+use crate::constant::*;
+use crate::deposit::{deposit_logic, Deposit};
+use crate::errors::AuctionHouseError;
+use crate::execute_sale::{execute_sale_logic, ExecuteSale, ExecuteSaleOptions};
+use crate::sell::{sell_logic, Sell};
+use crate::state::{AuctionHouse, HighestBid, SealedAuction, SealedBid};
+use crate::utils::{
+    assert_keys_equal, bid_escrow_amount, close_auction_escrow, collect_trade_state_rent,
+    create_auction_escrow, is_listing_fillable, lock_trade_state, release_locked_bid,
+    transfer_from_escrow,
+};
+
+/// Accounts for the [`sell_sealed_auction` handler](auction_house/fn.sell_sealed_auction.html).
+#[derive(Accounts)]
+#[instruction(
+    trade_state_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    token_size: u64
+)]
+pub struct SellSealedAuction<'info> {
+    /// CHECK: Validated as a signer in sell_sealed_auction.
+    /// User wallet account, paying for the auction account.
+    #[account(mut)]
+    pub wallet: UncheckedAccount<'info>,
+
+    /// SPL token account containing token for sale
+    #[account(mut)]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated by assert_metadata_valid.
+    /// Metaplex metadata account decorating SPL mint account.
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated as a signer in sell_logic.
+    /// Auction House authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Seller trade state PDA account encoding the auction listing at the sentinel price.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_account.mint.as_ref(),
+            &AUCTION_LISTING_PRICE.to_le_bytes(),
+            &token_size.to_le_bytes()
+        ],
+        bump = trade_state_bump
+    )]
+    pub seller_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Free seller trade state PDA account encoding a free sell order.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_account.mint.as_ref(),
+            &0u64.to_le_bytes(),
+            &token_size.to_le_bytes()
+        ],
+        bump = free_trade_state_bump
+    )]
+    pub free_seller_trade_state: UncheckedAccount<'info>,
+
+    /// Sealed auction account holding the auction terms and the revealed leaders.
+    #[account(
+        init,
+        payer = wallet,
+        space = SEALED_AUCTION_SIZE,
+        seeds = [
+            SEALED_AUCTION.as_bytes(),
+            auction_house.key().as_ref(),
+            seller_trade_state.key().as_ref()
+        ],
+        bump
+    )]
+    pub sealed_auction: Box<Account<'info, SealedAuction>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction escrow PDA locking the funds of the highest revealed bid, opened at the seller's expense.
+    #[account(
+        mut,
+        seeds = [
+            AUCTION_ESCROW.as_bytes(),
+            sealed_auction.key().as_ref()
+        ],
+        bump
+    )]
+    pub auction_escrow: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    #[account(seeds = [PREFIX.as_bytes(), SIGNER.as_bytes()], bump = program_as_signer_bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: List `token_size` tokens in a sealed-bid auction. Bids are committed until `bid_end_time` and revealed until
+/// `reveal_end_time`; a Vickrey auction charges the winner the second highest revealed price. The seller pays for the
+/// auction escrow locking the highest revealed bid.
+pub fn sell_sealed_auction<'info>(
+    ctx: Context<'_, '_, '_, 'info, SellSealedAuction<'info>>,
+    trade_state_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    token_size: u64,
+    bid_end_time: i64,
+    reveal_end_time: i64,
+    reserve_price: u64,
+    vickrey: bool,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    // NOTE: If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if accounts.auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    // NOTE: the auction terms are the seller's, so the authority cannot list on its behalf
+    if !accounts.wallet.is_signer {
+        return Err(AuctionHouseError::SaleRequiresSigner.into());
+    }
+
+    let clock = Clock::get()?;
+    if bid_end_time >= reveal_end_time || bid_end_time <= clock.unix_timestamp {
+        return Err(AuctionHouseError::InvalidAuctionTimes.into());
+    }
+
+    // NOTE: every auction type lists at the same sentinel price, so a token account holds one auction at a time
+    if !accounts.seller_trade_state.data_is_empty() {
+        return Err(AuctionHouseError::TradeStateAlreadyExists.into());
+    }
+
+    let mut sell_accounts = Sell {
+        wallet: accounts.wallet.clone(),
+        token_account: accounts.token_account.clone(),
+        metadata: accounts.metadata.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        seller_trade_state: accounts.seller_trade_state.clone(),
+        free_seller_trade_state: accounts.free_seller_trade_state.clone(),
+        token_program: accounts.token_program.clone(),
+        system_program: accounts.system_program.clone(),
+        program_as_signer: accounts.program_as_signer.clone(),
+        rent: accounts.rent.clone(),
+    };
+
+    sell_logic(
+        &mut sell_accounts,
+        ctx.program_id,
+        trade_state_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        AUCTION_LISTING_PRICE,
+        token_size,
+        None,
+    )?;
+
+    let is_native = accounts.treasury_mint.key() == spl_token::native_mint::id();
+    let sealed_auction_key = accounts.sealed_auction.key();
+    let escrow_bump = *ctx
+        .bumps
+        .get("auction_escrow")
+        .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?;
+    let auction_escrow_seeds = [
+        AUCTION_ESCROW.as_bytes(),
+        sealed_auction_key.as_ref(),
+        &[escrow_bump],
+    ];
+
+    create_auction_escrow(
+        &accounts.auction_escrow,
+        &accounts.wallet.to_account_info(),
+        &accounts.treasury_mint,
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program,
+        &accounts.system_program,
+        &accounts.rent,
+        &auction_escrow_seeds,
+        is_native,
+    )?;
+    ctx.accounts.auction_house.record_open_escrow()?;
+
+    let sealed_auction = &mut ctx.accounts.sealed_auction;
+    sealed_auction.auction_house = ctx.accounts.auction_house.key();
+    sealed_auction.seller = ctx.accounts.wallet.key();
+    sealed_auction.token_account = ctx.accounts.token_account.key();
+    sealed_auction.seller_trade_state = ctx.accounts.seller_trade_state.key();
+    sealed_auction.token_size = token_size;
+    sealed_auction.bid_end_time = bid_end_time;
+    sealed_auction.reveal_end_time = reveal_end_time;
+    sealed_auction.reserve_price = reserve_price;
+    sealed_auction.vickrey = vickrey;
+    sealed_auction.highest_bid = HighestBid::default();
+    sealed_auction.second_price = 0;
+    sealed_auction.locked_amount = 0;
+    sealed_auction.bump = *ctx
+        .bumps
+        .get("sealed_auction")
+        .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?;
+    sealed_auction.escrow_bump = escrow_bump;
+
+    Ok(())
+}
+
+/// Accounts for the [`commit_sealed_bid` handler](auction_house/fn.commit_sealed_bid.html).
+#[derive(Accounts)]
+#[instruction(escrow_payment_bump: u8)]
+pub struct CommitSealedBid<'info> {
+    /// User wallet account, paying for the sealed bid account.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// CHECK: Validated in deposit_logic.
+    /// User SOL or SPL account to transfer the collateral from.
+    #[account(mut)]
+    pub payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in deposit_logic.
+    /// SPL token account transfer authority.
+    pub transfer_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer escrow payment account PDA.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// Auction House instance treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Validated in deposit_logic.
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// Sealed auction account being bid on.
+    #[account(
+        seeds = [
+            SEALED_AUCTION.as_bytes(),
+            auction_house.key().as_ref(),
+            sealed_auction.seller_trade_state.as_ref()
+        ],
+        bump = sealed_auction.bump,
+        has_one = auction_house
+    )]
+    pub sealed_auction: Box<Account<'info, SealedAuction>>,
+
+    /// Sealed bid account holding the commitment, one per bidder and auction.
+    #[account(
+        init,
+        payer = wallet,
+        space = SEALED_BID_SIZE,
+        seeds = [
+            SEALED_BID.as_bytes(),
+            sealed_auction.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump
+    )]
+    pub sealed_bid: Box<Account<'info, SealedBid>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Collateral escrow PDA of the sealed bid, opened at the bidder's expense.
+    #[account(
+        mut,
+        seeds = [
+            AUCTION_ESCROW.as_bytes(),
+            sealed_bid.key().as_ref()
+        ],
+        bump
+    )]
+    pub bid_escrow: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: Commit to `keccak(price || salt || wallet || sealed_auction)` during the bidding window, see
+/// `SealedBid::commitment_of`. `collateral` is deposited and locked in the bid's own escrow, so the revealed price and
+/// its buyer fee can be covered without disclosing it.
+pub fn commit_sealed_bid<'info>(
+    ctx: Context<'_, '_, '_, 'info, CommitSealedBid<'info>>,
+    escrow_payment_bump: u8,
+    commitment: [u8; 32],
+    collateral: u64,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    // NOTE: If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if accounts.auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    let clock = Clock::get()?;
+    if clock.unix_timestamp >= accounts.sealed_auction.bid_end_time {
+        return Err(AuctionHouseError::AuctionNotActive.into());
+    }
+
+    let mut deposit_accounts = Deposit {
+        wallet: accounts.wallet.clone(),
+        payment_account: accounts.payment_account.clone(),
+        transfer_authority: accounts.transfer_authority.clone(),
+        escrow_payment_account: accounts.escrow_payment_account.clone(),
+        treasury_mint: accounts.treasury_mint.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        token_program: accounts.token_program.clone(),
+        system_program: accounts.system_program.clone(),
+        rent: accounts.rent.clone(),
+    };
+
+    deposit_logic(&mut deposit_accounts, escrow_payment_bump, collateral)?;
+
+    let is_native = accounts.treasury_mint.key() == spl_token::native_mint::id();
+    let auction_house_key = accounts.auction_house.key();
+    let wallet_key = accounts.wallet.key();
+    let sealed_bid_key = accounts.sealed_bid.key();
+    let escrow_bump = *ctx
+        .bumps
+        .get("bid_escrow")
+        .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?;
+    let bid_escrow_seeds = [
+        AUCTION_ESCROW.as_bytes(),
+        sealed_bid_key.as_ref(),
+        &[escrow_bump],
+    ];
+    let escrow_signer_seeds = [
+        PREFIX.as_bytes(),
+        auction_house_key.as_ref(),
+        wallet_key.as_ref(),
+        &[escrow_payment_bump],
+    ];
+    let auction_house_seeds = [
+        PREFIX.as_bytes(),
+        accounts.auction_house.creator.as_ref(),
+        accounts.auction_house.treasury_mint.as_ref(),
+        &[accounts.auction_house.bump],
+    ];
+
+    create_auction_escrow(
+        &accounts.bid_escrow,
+        &accounts.wallet.to_account_info(),
+        &accounts.treasury_mint,
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program,
+        &accounts.system_program,
+        &accounts.rent,
+        &bid_escrow_seeds,
+        is_native,
+    )?;
+
+    transfer_from_escrow(
+        &accounts.escrow_payment_account.to_account_info(),
+        &accounts.bid_escrow.to_account_info(),
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        &escrow_signer_seeds,
+        &auction_house_seeds,
+        collateral,
+        is_native,
+    )?;
+    ctx.accounts.auction_house.record_open_escrow()?;
+
+    let sealed_auction_key = ctx.accounts.sealed_auction.key();
+    let sealed_bid = &mut ctx.accounts.sealed_bid;
+    sealed_bid.auction_house = auction_house_key;
+    sealed_bid.sealed_auction = sealed_auction_key;
+    sealed_bid.bidder = wallet_key;
+    sealed_bid.commitment = commitment;
+    sealed_bid.collateral = collateral;
+    sealed_bid.bump = *ctx
+        .bumps
+        .get("sealed_bid")
+        .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?;
+    sealed_bid.escrow_bump = escrow_bump;
+
+    Ok(())
+}
+
+/// Accounts for the [`reveal_sealed_bid` handler](auction_house/fn.reveal_sealed_bid.html).
+#[derive(Accounts)]
+#[instruction(trade_state_bump: u8, escrow_payment_bump: u8)]
+pub struct RevealSealedBid<'info> {
+    /// User wallet account, receiving the rent of the sealed bid account.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// User SOL or SPL account to transfer funds from.
+    #[account(mut)]
+    pub payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// SPL token account transfer authority.
+    pub transfer_authority: UncheckedAccount<'info>,
+
+    /// Auction House instance treasury mint account.
+    pub treasury_mint: Account<'info, Mint>,
+
+    /// SPL token account being auctioned.
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated in bid_logic.
+    /// SPL token account metadata.
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer escrow payment account PDA.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// Buyer trade state PDA encoding the revealed price.
+    #[account(mut)]
+    pub buyer_trade_state: UncheckedAccount<'info>,
+
+    /// Sealed auction account being bid on.
+    #[account(
+        mut,
+        seeds = [
+            SEALED_AUCTION.as_bytes(),
+            auction_house.key().as_ref(),
+            sealed_auction.seller_trade_state.as_ref()
+        ],
+        bump = sealed_auction.bump,
+        has_one = auction_house,
+        has_one = token_account
+    )]
+    pub sealed_auction: Box<Account<'info, SealedAuction>>,
+
+    /// Sealed bid account being revealed, closed back to the bidder.
+    #[account(
+        mut,
+        close = wallet,
+        seeds = [
+            SEALED_BID.as_bytes(),
+            sealed_auction.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump = sealed_bid.bump,
+        has_one = sealed_auction
+    )]
+    pub sealed_bid: Box<Account<'info, SealedBid>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Collateral escrow PDA of the sealed bid, emptied into the bidder escrow and closed back to the bidder.
+    #[account(
+        mut,
+        seeds = [
+            AUCTION_ESCROW.as_bytes(),
+            sealed_bid.key().as_ref()
+        ],
+        bump = sealed_bid.escrow_bump
+    )]
+    pub bid_escrow: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction escrow PDA locking the funds of the highest revealed bid.
+    #[account(
+        mut,
+        seeds = [
+            AUCTION_ESCROW.as_bytes(),
+            sealed_auction.key().as_ref()
+        ],
+        bump = sealed_auction.escrow_bump
+    )]
+    pub auction_escrow: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by derivation from the highest bid in reveal_sealed_bid.
+    /// Escrow payment account of the bidder being outbid, refunded its locked funds. Ignored unless outbidding.
+    #[account(mut)]
+    pub previous_bidder_escrow_payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against the highest bid in reveal_sealed_bid.
+    /// Trade state of the bid being outbid. Ignored unless outbidding.
+    #[account(mut)]
+    pub previous_bidder_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against the outbid trade state in reveal_sealed_bid.
+    /// Account that paid for the outbid trade state, receiving its rent. Ignored unless outbidding.
+    #[account(mut)]
+    pub previous_trade_state_fee_payer: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: Reveal a committed bid during the reveal window. The price and salt only open the commitment together with the
+/// bidder and this auction, so a copied commitment cannot be revealed by anyone else. Its collateral returns to the
+/// bidder escrow; a bid that takes the lead becomes a regular buyer trade state through bid_logic, locked with its
+/// funds in the auction escrow, while the bid it outbids is refunded. Bids below the reserve price are rejected. Bids
+/// committed before an auctioneer was delegated can still be revealed.
+pub fn reveal_sealed_bid<'info>(
+    ctx: Context<'_, '_, '_, 'info, RevealSealedBid<'info>>,
+    trade_state_bump: u8,
+    escrow_payment_bump: u8,
+    price: u64,
+    salt: [u8; 32],
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let sealed_auction = &accounts.sealed_auction;
+    let sealed_bid = &accounts.sealed_bid;
+
+    let clock = Clock::get()?;
+    if clock.unix_timestamp < sealed_auction.bid_end_time
+        || clock.unix_timestamp >= sealed_auction.reveal_end_time
+    {
+        return Err(AuctionHouseError::AuctionNotInRevealWindow.into());
+    }
+
+    let commitment =
+        SealedBid::commitment_of(price, &salt, &sealed_bid.bidder, &sealed_auction.key());
+    if commitment != sealed_bid.commitment {
+        return Err(AuctionHouseError::InvalidCommitment.into());
+    }
+
+    if price == 0 || price < sealed_auction.reserve_price {
+        return Err(AuctionHouseError::BidTooLow.into());
+    }
+
+    let locked_amount = bid_escrow_amount(&accounts.auction_house, price)?;
+    if locked_amount > sealed_bid.collateral {
+        return Err(AuctionHouseError::RevealExceedsCollateral.into());
+    }
+
+    let is_native = accounts.treasury_mint.key() == spl_token::native_mint::id();
+    let auction_house_key = accounts.auction_house.key();
+    let wallet_key = accounts.wallet.key();
+    let sealed_auction_key = sealed_auction.key();
+    let sealed_bid_key = sealed_bid.key();
+    let bid_escrow_seeds = [
+        AUCTION_ESCROW.as_bytes(),
+        sealed_bid_key.as_ref(),
+        &[sealed_bid.escrow_bump],
+    ];
+    let auction_escrow_seeds = [
+        AUCTION_ESCROW.as_bytes(),
+        sealed_auction_key.as_ref(),
+        &[sealed_auction.escrow_bump],
+    ];
+    let escrow_signer_seeds = [
+        PREFIX.as_bytes(),
+        auction_house_key.as_ref(),
+        wallet_key.as_ref(),
+        &[escrow_payment_bump],
+    ];
+    let auction_house_seeds = [
+        PREFIX.as_bytes(),
+        accounts.auction_house.creator.as_ref(),
+        accounts.auction_house.treasury_mint.as_ref(),
+        &[accounts.auction_house.bump],
+    ];
+
+    transfer_from_escrow(
+        &accounts.bid_escrow.to_account_info(),
+        &accounts.escrow_payment_account.to_account_info(),
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        &bid_escrow_seeds,
+        &auction_house_seeds,
+        sealed_bid.collateral,
+        is_native,
+    )?;
+
+    close_auction_escrow(
+        &accounts.bid_escrow.to_account_info(),
+        &accounts.wallet.to_account_info(),
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        &bid_escrow_seeds,
+        &auction_house_seeds,
+        is_native,
+    )?;
+
+    // NOTE: ties go to the first reveal, and a bid that does not lead can never win, so it is done with its collateral
+    let highest_bid = sealed_auction.highest_bid.clone();
+    if price <= highest_bid.amount {
+        ctx.accounts.auction_house.record_closed_escrow()?;
+        let sealed_auction = &mut ctx.accounts.sealed_auction;
+        sealed_auction.second_price = std::cmp::max(sealed_auction.second_price, price);
+        return Ok(());
+    }
+
+    if highest_bid.amount > 0 {
+        release_locked_bid(
+            &highest_bid,
+            sealed_auction.locked_amount,
+            &auction_house_key,
+            &accounts.auction_house.to_account_info(),
+            &accounts.auction_escrow.to_account_info(),
+            &accounts.previous_bidder_escrow_payment_account.to_account_info(),
+            &accounts.previous_bidder_trade_state.to_account_info(),
+            &accounts.previous_trade_state_fee_payer.to_account_info(),
+            &accounts.token_program.to_account_info(),
+            &accounts.system_program.to_account_info(),
+            &auction_escrow_seeds,
+            &auction_house_seeds,
+            is_native,
+        )?;
+    }
+
+    // NOTE: the locked trade state belongs to the auction, so it cannot be an order placed beforehand
+    if !accounts.buyer_trade_state.data_is_empty() {
+        return Err(AuctionHouseError::TradeStateAlreadyExists.into());
+    }
+
+    bid_logic(
+        accounts.wallet.to_owned(),
+        accounts.payment_account.to_owned(),
+        accounts.transfer_authority.to_owned(),
+        accounts.treasury_mint.to_owned(),
+        *accounts.token_account.to_owned(),
+        accounts.metadata.to_owned(),
+        accounts.escrow_payment_account.to_owned(),
+        accounts.authority.to_owned(),
+        *accounts.auction_house.to_owned(),
+        accounts.auction_house_fee_account.to_owned(),
+        accounts.buyer_trade_state.to_owned(),
+        accounts.token_program.to_owned(),
+        accounts.system_program.to_owned(),
+        accounts.rent.to_owned(),
+        trade_state_bump,
+        escrow_payment_bump,
+        price,
+        sealed_auction.token_size,
+        false,
+        None,
+        None,
+    )?;
+
+    transfer_from_escrow(
+        &accounts.escrow_payment_account.to_account_info(),
+        &accounts.auction_escrow.to_account_info(),
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        &escrow_signer_seeds,
+        &auction_house_seeds,
+        locked_amount,
+        is_native,
+    )?;
+
+    lock_trade_state(&accounts.buyer_trade_state.to_account_info())?;
+
+    // NOTE: the outbid trade state pays its rent back after the CPIs above, which its fee payer may be part of
+    if highest_bid.amount > 0 {
+        collect_trade_state_rent(
+            &accounts.previous_bidder_trade_state.to_account_info(),
+            &accounts.previous_trade_state_fee_payer.to_account_info(),
+        )?;
+    }
+
+    let buyer_trade_state_key = ctx.accounts.buyer_trade_state.key();
+    let sealed_auction = &mut ctx.accounts.sealed_auction;
+    sealed_auction.second_price = highest_bid.amount;
+    sealed_auction.highest_bid = HighestBid {
+        amount: price,
+        buyer: wallet_key,
+        buyer_trade_state: buyer_trade_state_key,
+    };
+    sealed_auction.locked_amount = locked_amount;
+
+    ctx.accounts.auction_house.record_closed_escrow()
+}
+
+/// Accounts for the [`settle_sealed_auction` handler](auction_house/fn.settle_sealed_auction.html).
+#[derive(Accounts)]
+#[instruction(
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    token_size: u64
+)]
+pub struct SettleSealedAuction<'info> {
+    /// CHECK: Validated against the highest bid in settle_sealed_auction.
+    /// Buyer's wallet account
+    #[account(mut)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the sealed auction.
+    /// Seller's wallet account, receiving the rent of the sealed auction account.
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the sealed auction.
+    /// Token account where the SPL token is being held
+    #[account(mut)]
+    pub token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Token mint account for the SPL token.
+    pub token_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Metaplex metaplex account holding extra infos.
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Auction House treasury mint account.
+    pub treasury_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. validate via seeds check.
+    /// Buyer escrow payment account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            buyer.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Seller SOL or SPL account to receive payment at,
+    #[account(mut)]
+    pub seller_payment_receipt_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Buyer SOL or SPL account to receive the purchased item at.
+    #[account(mut)]
+    pub buyer_receipt_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in execute_sale_logic.
+    /// Auction House instance authority
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_treasury,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Auction House instance fee account
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Auction House instance treasury account
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            TREASURY.as_bytes()
+        ],
+        bump = auction_house.treasury_bump
+    )]
+    pub auction_house_treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against the highest bid in settle_sealed_auction.
+    /// Buyer trade state PDA account encoding the winning bid.
+    #[account(mut)]
+    pub buyer_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the sealed auction seeds.
+    /// Seller trade state PDA account encoding the auction listing.
+    #[account(mut)]
+    pub seller_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    /// Free seller trade state PDA account encoding a free sell order.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            seller.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_mint.key().as_ref(),
+            &0u64.to_le_bytes(),
+            &token_size.to_le_bytes(),
+        ],
+        bump = free_trade_state_bump
+    )]
+    pub free_trade_state: UncheckedAccount<'info>,
+
+    /// Sealed auction account being settled, closed back to the seller.
+    #[account(
+        mut,
+        close = seller,
+        seeds = [
+            SEALED_AUCTION.as_bytes(),
+            auction_house.key().as_ref(),
+            seller_trade_state.key().as_ref()
+        ],
+        bump = sealed_auction.bump,
+        has_one = auction_house,
+        has_one = seller,
+        has_one = token_account
+    )]
+    pub sealed_auction: Box<Account<'info, SealedAuction>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction escrow PDA locking the funds of the highest revealed bid, closed back to the seller.
+    #[account(
+        mut,
+        seeds = [
+            AUCTION_ESCROW.as_bytes(),
+            sealed_auction.key().as_ref()
+        ],
+        bump = sealed_auction.escrow_bump
+    )]
+    pub auction_escrow: UncheckedAccount<'info>,
+
+    /// Anyone cranking the settlement, paying for any account it creates.
+    #[account(mut)]
+    pub settler: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub ata_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Not dangerous. Validate via seeds check.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            SIGNER.as_bytes()
+        ],
+        bump = program_as_signer_bump
+    )]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: Permissionlessly settle a sealed auction once its reveal window is over. The winner pays its own price, or
+/// in a Vickrey auction the second highest revealed price, never less than the reserve. Its locked funds return to the
+/// buyer escrow to pay through execute_sale_logic, then the auction escrow is closed. Auctions started before an
+/// auctioneer was delegated still settle here.
+pub fn settle_sealed_auction<'info>(
+    ctx: Context<'_, '_, '_, 'info, SettleSealedAuction<'info>>,
+    escrow_payment_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    token_size: u64,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let sealed_auction = &accounts.sealed_auction;
+
+    let clock = Clock::get()?;
+    if clock.unix_timestamp < sealed_auction.reveal_end_time {
+        return Err(AuctionHouseError::AuctionNotEnded.into());
+    }
+
+    let highest_bid = sealed_auction.highest_bid.clone();
+    if highest_bid.amount == 0 {
+        return Err(AuctionHouseError::NoWinningBid.into());
+    }
+
+    assert_keys_equal(accounts.buyer.key(), highest_bid.buyer)?;
+    assert_keys_equal(
+        accounts.buyer_trade_state.key(),
+        highest_bid.buyer_trade_state,
+    )?;
+
+    if token_size != sealed_auction.token_size {
+        return Err(AuctionHouseError::InvalidTokenAmount.into());
+    }
+
+    let price = if sealed_auction.vickrey {
+        std::cmp::max(
+            std::cmp::max(sealed_auction.second_price, sealed_auction.reserve_price),
+            1,
+        )
+    } else {
+        highest_bid.amount
+    };
+
+    let is_native = accounts.treasury_mint.key() == spl_token::native_mint::id();
+    let sealed_auction_key = sealed_auction.key();
+    let auction_escrow_seeds = [
+        AUCTION_ESCROW.as_bytes(),
+        sealed_auction_key.as_ref(),
+        &[sealed_auction.escrow_bump],
+    ];
+    let auction_house_seeds = [
+        PREFIX.as_bytes(),
+        accounts.auction_house.creator.as_ref(),
+        accounts.auction_house.treasury_mint.as_ref(),
+        &[accounts.auction_house.bump],
+    ];
+
+    transfer_from_escrow(
+        &accounts.auction_escrow.to_account_info(),
+        &accounts.escrow_payment_account.to_account_info(),
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        &auction_escrow_seeds,
+        &auction_house_seeds,
+        sealed_auction.locked_amount,
+        is_native,
+    )?;
+
+    let mut execute_sale_accounts = ExecuteSale {
+        buyer: accounts.buyer.clone(),
+        seller: accounts.seller.clone(),
+        token_account: accounts.token_account.clone(),
+        token_mint: accounts.token_mint.clone(),
+        metadata: accounts.metadata.clone(),
+        treasury_mint: accounts.treasury_mint.clone(),
+        escrow_payment_account: accounts.escrow_payment_account.clone(),
+        seller_payment_receipt_account: accounts.seller_payment_receipt_account.clone(),
+        buyer_receipt_token_account: accounts.buyer_receipt_token_account.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        auction_house_treasury: accounts.auction_house_treasury.clone(),
+        buyer_trade_state: accounts.buyer_trade_state.clone(),
+        seller_trade_state: accounts.seller_trade_state.clone(),
+        free_trade_state: accounts.free_trade_state.clone(),
+        token_program: accounts.token_program.clone(),
+        system_program: accounts.system_program.clone(),
+        ata_program: accounts.ata_program.clone(),
+        program_as_signer: accounts.program_as_signer.clone(),
+        rent: accounts.rent.clone(),
+    };
+
+    execute_sale_logic(
+        Context::new(
+            ctx.program_id,
+            &mut execute_sale_accounts,
+            ctx.remaining_accounts,
+            ctx.bumps.clone(),
+        ),
+        escrow_payment_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        AUCTION_LISTING_PRICE,
+        token_size,
+        ExecuteSaleOptions {
+            fill_size: Some(token_size),
+            fill_price: Some(price),
+            bid_price: Some(highest_bid.amount),
+            settler: Some(accounts.settler.to_account_info()),
+            auction_settlement: true,
+            defer_trade_state_rent: true,
+            ..Default::default()
+        },
+    )?;
+
+    close_auction_escrow(
+        &accounts.auction_escrow.to_account_info(),
+        &accounts.seller.to_account_info(),
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        &auction_escrow_seeds,
+        &auction_house_seeds,
+        is_native,
+    )?;
+
+    // NOTE: the settler may be the seller, so the trade states pay their rent back once the escrow paid it
+    let settler = accounts.settler.to_account_info();
+    for trade_state in [
+        &accounts.buyer_trade_state,
+        &accounts.seller_trade_state,
+        &accounts.free_trade_state,
+    ] {
+        collect_trade_state_rent(&trade_state.to_account_info(), &settler)?;
+    }
+
+    ctx.accounts.auction_house.record_closed_escrow()
+}
+
+/// Accounts for the [`cancel_sealed_auction` handler](auction_house/fn.cancel_sealed_auction.html).
+#[derive(Accounts)]
+pub struct CancelSealedAuction<'info> {
+    /// CHECK: Validated by the `constraint` on the sealed auction.
+    /// Seller wallet account, receiving the rent of the sealed auction account.
+    #[account(mut)]
+    pub wallet: UncheckedAccount<'info>,
+
+    /// SPL token account containing the auctioned token.
+    #[account(mut)]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Token mint account of SPL token
+    #[account(mut)]
+    pub token_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Validated as a signer in cancel_logic.
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in cancel_logic.
+    /// Seller trade state PDA account encoding the auction listing.
+    #[account(mut)]
+    pub trade_state: UncheckedAccount<'info>,
+
+    /// Sealed auction account being canceled, closed back to the seller.
+    #[account(
+        mut,
+        close = wallet,
+        seeds = [
+            SEALED_AUCTION.as_bytes(),
+            auction_house.key().as_ref(),
+            trade_state.key().as_ref()
+        ],
+        bump = sealed_auction.bump,
+        has_one = auction_house,
+        has_one = token_account,
+        constraint = sealed_auction.seller == wallet.key()
+    )]
+    pub sealed_auction: Box<Account<'info, SealedAuction>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction escrow PDA locking the funds of the highest revealed bid, closed back to the seller.
+    #[account(
+        mut,
+        seeds = [
+            AUCTION_ESCROW.as_bytes(),
+            sealed_auction.key().as_ref()
+        ],
+        bump = sealed_auction.escrow_bump
+    )]
+    pub auction_escrow: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by derivation from the highest bid in cancel_sealed_auction.
+    /// Escrow payment account of the highest bidder, refunded its locked funds. Ignored without revealed bids.
+    #[account(mut)]
+    pub highest_bidder_escrow_payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against the highest bid in cancel_sealed_auction.
+    /// Trade state of the highest bid. Ignored without revealed bids.
+    #[account(mut)]
+    pub highest_bidder_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Validated against the highest bid trade state in cancel_sealed_auction.
+    /// Account that paid for the highest bid trade state, receiving its rent. Ignored without revealed bids.
+    #[account(mut)]
+    pub highest_bid_trade_state_fee_payer: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// NOTE: Cancel a sealed auction before bidding closes, or after the reveal window when no bid was revealed. A revealed
+/// winner is owed a settlement, unless the seller's token can no longer be delivered: its bid is then refunded.
+/// Committed bids are reclaimed with close_sealed_bid. Works whether or not an auctioneer was delegated since the
+/// auction started.
+pub fn cancel_sealed_auction<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelSealedAuction<'info>>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let sealed_auction = &accounts.sealed_auction;
+
+    // NOTE: committed bidders are owed a chance to reveal, and a revealed winner a settlement
+    let clock = Clock::get()?;
+    if clock.unix_timestamp >= sealed_auction.bid_end_time
+        && clock.unix_timestamp < sealed_auction.reveal_end_time
+    {
+        return Err(AuctionHouseError::AuctionNotEnded.into());
+    }
+
+    let is_native = accounts.auction_house.treasury_mint == spl_token::native_mint::id();
+    let sealed_auction_key = sealed_auction.key();
+    let auction_escrow_seeds = [
+        AUCTION_ESCROW.as_bytes(),
+        sealed_auction_key.as_ref(),
+        &[sealed_auction.escrow_bump],
+    ];
+    let auction_house_seeds = [
+        PREFIX.as_bytes(),
+        accounts.auction_house.creator.as_ref(),
+        accounts.auction_house.treasury_mint.as_ref(),
+        &[accounts.auction_house.bump],
+    ];
+
+    let highest_bid = sealed_auction.highest_bid.clone();
+    if highest_bid.amount > 0 {
+        if is_listing_fillable(&accounts.token_account, sealed_auction.token_size, ctx.program_id)? {
+            return Err(AuctionHouseError::AuctionHasBids.into());
+        }
+
+        release_locked_bid(
+            &highest_bid,
+            sealed_auction.locked_amount,
+            &accounts.auction_house.key(),
+            &accounts.auction_house.to_account_info(),
+            &accounts.auction_escrow.to_account_info(),
+            &accounts.highest_bidder_escrow_payment_account.to_account_info(),
+            &accounts.highest_bidder_trade_state.to_account_info(),
+            &accounts.highest_bid_trade_state_fee_payer.to_account_info(),
+            &accounts.token_program.to_account_info(),
+            &accounts.system_program.to_account_info(),
+            &auction_escrow_seeds,
+            &auction_house_seeds,
+            is_native,
+        )?;
+    }
+
+    // NOTE: close the escrow before cancel_logic credits the trade state rent to the wallet outside of any CPI,
+    // which would leave the wallet unbalanced in the escrow transfer
+    close_auction_escrow(
+        &accounts.auction_escrow.to_account_info(),
+        &accounts.wallet.to_account_info(),
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        &auction_escrow_seeds,
+        &auction_house_seeds,
+        is_native,
+    )?;
+
+    let mut cancel_accounts = Cancel {
+        wallet: accounts.wallet.clone(),
+        token_account: accounts.token_account.clone(),
+        token_mint: accounts.token_mint.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        trade_state: accounts.trade_state.clone(),
+        token_program: accounts.token_program.clone(),
+    };
+
+    cancel_logic(
+        &mut cancel_accounts,
+        AUCTION_LISTING_PRICE,
+        sealed_auction.token_size,
+    )?;
+
+    if highest_bid.amount > 0 {
+        collect_trade_state_rent(
+            &ctx.accounts.highest_bidder_trade_state.to_account_info(),
+            &ctx.accounts.highest_bid_trade_state_fee_payer.to_account_info(),
+        )?;
+    }
+
+    ctx.accounts.auction_house.record_closed_escrow()
+}
+
+/// Accounts for the [`close_sealed_bid` handler](auction_house/fn.close_sealed_bid.html).
+#[derive(Accounts)]
+#[instruction(escrow_payment_bump: u8)]
+pub struct CloseSealedBid<'info> {
+    /// CHECK: Validated by the `has_one` constraint on the sealed bid.
+    /// Bidder wallet account, receiving the rent of the sealed bid and of its collateral escrow.
+    #[account(mut)]
+    pub bidder: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Bidder escrow payment account PDA, refunded the collateral.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            bidder.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Deserialized in close_sealed_bid when the auction is still open.
+    /// Sealed auction of the bid, possibly already settled or canceled.
+    #[account(address = sealed_bid.sealed_auction)]
+    pub sealed_auction: UncheckedAccount<'info>,
+
+    /// Unrevealed sealed bid, closed back to the bidder.
+    #[account(
+        mut,
+        close = bidder,
+        seeds = [
+            SEALED_BID.as_bytes(),
+            sealed_bid.sealed_auction.as_ref(),
+            bidder.key().as_ref()
+        ],
+        bump = sealed_bid.bump,
+        has_one = auction_house,
+        has_one = bidder
+    )]
+    pub sealed_bid: Box<Account<'info, SealedBid>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Collateral escrow PDA of the sealed bid, closed back to the bidder.
+    #[account(
+        mut,
+        seeds = [
+            AUCTION_ESCROW.as_bytes(),
+            sealed_bid.key().as_ref()
+        ],
+        bump = sealed_bid.escrow_bump
+    )]
+    pub bid_escrow: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// NOTE: Permissionlessly close a sealed bid that was never revealed, once its reveal window is over or its auction is
+/// gone. The collateral returns to the bidder escrow and the rent to the bidder.
+pub fn close_sealed_bid<'info>(
+    ctx: Context<'_, '_, '_, 'info, CloseSealedBid<'info>>,
+    _escrow_payment_bump: u8,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let sealed_bid = &accounts.sealed_bid;
+
+    if !accounts.sealed_auction.data_is_empty() {
+        let sealed_auction =
+            SealedAuction::try_deserialize(&mut &accounts.sealed_auction.try_borrow_data()?[..])?;
+        if Clock::get()?.unix_timestamp < sealed_auction.reveal_end_time {
+            return Err(AuctionHouseError::AuctionNotEnded.into());
+        }
+    }
+
+    let is_native = accounts.auction_house.treasury_mint == spl_token::native_mint::id();
+    let sealed_bid_key = sealed_bid.key();
+    let bid_escrow_seeds = [
+        AUCTION_ESCROW.as_bytes(),
+        sealed_bid_key.as_ref(),
+        &[sealed_bid.escrow_bump],
+    ];
+    let auction_house_seeds = [
+        PREFIX.as_bytes(),
+        accounts.auction_house.creator.as_ref(),
+        accounts.auction_house.treasury_mint.as_ref(),
+        &[accounts.auction_house.bump],
+    ];
+
+    transfer_from_escrow(
+        &accounts.bid_escrow.to_account_info(),
+        &accounts.escrow_payment_account.to_account_info(),
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        &bid_escrow_seeds,
+        &auction_house_seeds,
+        sealed_bid.collateral,
+        is_native,
+    )?;
+
+    close_auction_escrow(
+        &accounts.bid_escrow.to_account_info(),
+        &accounts.bidder.to_account_info(),
+        &accounts.auction_house.to_account_info(),
+        &accounts.token_program.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        &bid_escrow_seeds,
+        &auction_house_seeds,
+        is_native,
+    )?;
+
+    ctx.accounts.auction_house.record_closed_escrow()
+}
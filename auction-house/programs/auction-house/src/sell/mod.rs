@@ -1,13 +1,13 @@
 use anchor_lang::{prelude::*, solana_program::program::invoke, AnchorDeserialize};
 use anchor_spl::token::{Token, TokenAccount};
-use solana_program::program_memory::sol_memset;
 use spl_token::instruction::approve;
 
 use crate::constant::*;
 use crate::errors::AuctionHouseError;
-use crate::state::AuctionHouse;
+use crate::state::{AuctionHouse, Auctioneer, AuthorityScope, TradeSide, TradeStateData};
 use crate::utils::{
-    assert_is_ata, assert_metadata_valid, create_or_allocate_account_raw, get_fee_payer,
+    assert_is_ata, assert_metadata_valid, assert_valid_auctioneer_and_scope, assert_valid_expiry,
+    create_or_allocate_account_raw, get_fee_payer, write_trade_state,
 };
 
 /// Accounts for the [`sell` handler](auction_house/fn.sell.html).
@@ -101,6 +101,162 @@ pub struct Sell<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+/// Accounts for the [`auctioneer_sell` handler](auction_house/fn.auctioneer_sell.html).
+#[derive(Accounts)]
+#[instruction(
+    trade_state_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    buyer_price: u64,
+    token_size: u64
+)]
+pub struct AuctioneerSell<'info> {
+    /// CHECK: Validated in sell_logic.
+    /// User wallet account
+    #[account(mut)]
+    pub wallet: UncheckedAccount<'info>,
+
+    /// SPL token account containing token for sale
+    #[account(mut)]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated by assert_metadata_valid.
+    /// Metaplex metadata account decorating SPL mint account.
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated as a signer in sell_logic.
+    /// Auction House authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    /// PDA was seeded from PREFIX + Auction house's creator + Auction house's treasury mint
+    #[account(seeds = [PREFIX.as_bytes(), auction_house.creator.as_ref(), auction_house.treasury_mint.as_ref()], bump = auction_house.bump,has_one=authority, has_one = auction_house_fee_account)]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump=auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Seller trade state PDA account encoding the sell order.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_account.mint.as_ref(),
+            &buyer_price.to_le_bytes(),
+            &token_size.to_le_bytes()
+        ],
+        bump=trade_state_bump
+    )]
+    pub seller_trade_state: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Free seller trade state PDA account encoding a free sell order.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            wallet.key().as_ref(),
+            auction_house.key().as_ref(),
+            token_account.key().as_ref(),
+            auction_house.treasury_mint.as_ref(),
+            token_account.mint.as_ref(),
+            &0u64.to_le_bytes(),
+            &token_size.to_le_bytes()
+        ],
+        bump=free_trade_state_bump
+    )]
+    pub free_seller_trade_state: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    #[account(seeds=[PREFIX.as_bytes(), SIGNER.as_bytes()], bump=program_as_signer_bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+
+    /// The auctioneer authority - typically a PDA of the Auctioneer program running this action.
+    pub auctioneer_authority: Signer<'info>,
+
+    /// The auctioneer PDA owned by Auction House storing scopes.
+    #[account(
+        seeds = [
+            AUCTIONEER.as_bytes(),
+            auction_house.key().as_ref(),
+            auctioneer_authority.key().as_ref()
+        ],
+        bump = auction_house.auctioneer_pda_bump
+    )]
+    pub ah_auctioneer_pda: Box<Account<'info, Auctioneer>>,
+}
+
+
+pub fn auctioneer_sell<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuctioneerSell<'info>>,
+    trade_state_bump: u8,
+    free_trade_state_bump: u8,
+    program_as_signer_bump: u8,
+    buyer_price: u64,
+    token_size: u64,
+    expiry: Option<i64>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    assert_valid_auctioneer_and_scope(
+        &accounts.auction_house,
+        &accounts.auctioneer_authority.key(),
+        &accounts.ah_auctioneer_pda,
+        AuthorityScope::Sell,
+    )?;
+
+    // NOTE: the sentinel price is reserved for auction listings
+    if buyer_price == AUCTION_LISTING_PRICE {
+        return Err(AuctionHouseError::MustUseAuctionHandler.into());
+    }
+
+    let mut sell_accounts = Sell {
+        wallet: accounts.wallet.clone(),
+        token_account: accounts.token_account.clone(),
+        metadata: accounts.metadata.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        seller_trade_state: accounts.seller_trade_state.clone(),
+        free_seller_trade_state: accounts.free_seller_trade_state.clone(),
+        token_program: accounts.token_program.clone(),
+        system_program: accounts.system_program.clone(),
+        program_as_signer: accounts.program_as_signer.clone(),
+        rent: accounts.rent.clone(),
+    };
+
+    sell_logic(
+        &mut sell_accounts,
+        ctx.program_id,
+        trade_state_bump,
+        free_trade_state_bump,
+        program_as_signer_bump,
+        buyer_price,
+        token_size,
+        expiry,
+    )
+}
+
 pub fn sell<'info>(
     ctx: Context<'_, '_, '_, 'info, Sell<'info>>,
     trade_state_bump: u8,
@@ -108,6 +264,7 @@ pub fn sell<'info>(
     program_as_signer_bump: u8,
     buyer_price: u64,
     token_size: u64,
+    expiry: Option<i64>,
 ) -> Result<()> {
     // NOTE: get auction house account from context
     let auction_house = &ctx.accounts.auction_house;
@@ -117,6 +274,11 @@ pub fn sell<'info>(
         return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
     }
 
+    // NOTE: the sentinel price is reserved for auction listings
+    if buyer_price == AUCTION_LISTING_PRICE {
+        return Err(AuctionHouseError::MustUseAuctionHandler.into());
+    }
+
     sell_logic(
         ctx.accounts,
         ctx.program_id,
@@ -125,6 +287,7 @@ pub fn sell<'info>(
         program_as_signer_bump,
         buyer_price,
         token_size,
+        expiry,
     )
 }
 
@@ -137,6 +300,7 @@ pub fn sell_logic<'info>(
     _program_as_signer_bump: u8,
     buyer_price: u64,
     token_size: u64,
+    expiry: Option<i64>,
 ) -> Result<()> {
     // NOTE: extract all variables from ctx (Sell struct)
     let wallet = &accounts.wallet;
@@ -152,6 +316,13 @@ pub fn sell_logic<'info>(
     let program_as_signer = &accounts.program_as_signer;
     let rent = &accounts.rent;
 
+    assert_valid_expiry(expiry)?;
+
+    // NOTE: an existing trade state keeps the expiry it was placed with, so a new one would be silently dropped
+    if expiry.is_some() && !seller_trade_state.data_is_empty() {
+        return Err(AuctionHouseError::TradeStateAlreadyExists.into());
+    }
+
     // NOTE: check for valid signer
     if !wallet.to_account_info().is_signer
         && (buyer_price == 0
@@ -240,11 +411,27 @@ pub fn sell_logic<'info>(
             &ts_seeds,
         )?;
 
-        sol_memset(
-            *ts_info.try_borrow_mut_data()?,
+        let clock = Clock::get()?;
+        write_trade_state(
+            &ts_info,
             trade_state_bump,
-            TRADE_STATE_SIZE,
-        );
+            &TradeStateData {
+                version: TRADE_STATE_VERSION,
+                side: TradeSide::Sell,
+                flags: 0,
+                auction_house: auction_house_key,
+                wallet: wallet_key,
+                token_account: token_account_key,
+                mint: token_account.mint,
+                price: buyer_price,
+                token_size,
+                created_slot: clock.slot,
+                created_at: clock.unix_timestamp,
+                expiry: expiry.unwrap_or(0),
+                fee_payer: fee_payer.key(),
+                referrer: Pubkey::default(),
+            },
+        )?;
     }
 
     // let data = &mut ts_info.data.borrow_mut();
@@ -1,7 +1,8 @@
 /// states for Auction House
-use anchor_lang::{prelude::*, AnchorDeserialize, AnchorSerialize};
+use anchor_lang::{prelude::*, solana_program::keccak, AnchorDeserialize, AnchorSerialize};
 
 use crate::constant::*;
+use crate::errors::AuctionHouseError;
 
 #[account]
 pub struct AuctionHouse {
@@ -21,6 +22,49 @@ pub struct AuctionHouse {
     pub escrow_payment_bump: u8,
     pub has_auctioneer: bool,
     pub auctioneer_pda_bump: u8,
+    pub maker_fee_basis_points: i16,
+    pub taker_fee_basis_points: u16,
+    pub fee_tiers: [FeeTier; MAX_FEE_TIERS],
+    pub has_revenue_split: bool,
+    pub open_auction_escrows: u32,
+    pub approved_referrers: u32,
+}
+
+impl AuctionHouse {
+    /// Counts an auction or sealed bid escrow holding funds under the house, which cannot close until it is released.
+    pub fn record_open_escrow(&mut self) -> Result<()> {
+        self.open_auction_escrows = self
+            .open_auction_escrows
+            .checked_add(1)
+            .ok_or(AuctionHouseError::NumericalOverflow)?;
+        Ok(())
+    }
+
+    /// Releases an escrow counted by `record_open_escrow`.
+    pub fn record_closed_escrow(&mut self) -> Result<()> {
+        self.open_auction_escrows = self
+            .open_auction_escrows
+            .checked_sub(1)
+            .ok_or(AuctionHouseError::NumericalOverflow)?;
+        Ok(())
+    }
+
+    /// Fee discount of a wallet with the given trailing volume, from the highest tier it reaches.
+    pub fn fee_discount_basis_points(&self, trailing_volume: u64) -> u16 {
+        self.fee_tiers
+            .iter()
+            .filter(|tier| trailing_volume >= tier.min_volume)
+            .map(|tier| tier.discount_basis_points)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Fee discount, in basis points of the fee, for wallets whose trailing volume reaches `min_volume`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Debug)]
+pub struct FeeTier {
+    pub min_volume: u64,
+    pub discount_basis_points: u16,
 }
 
 #[account]
@@ -30,6 +74,320 @@ pub struct Auctioneer {
     pub scopes: [bool; MAX_NUM_SCOPES],
 }
 
+/// Receipt for a listing created by `sell`, seeded by the seller trade state.
+#[account]
+pub struct ListingReceipt {
+    pub trade_state: Pubkey,
+    pub bookkeeper: Pubkey,
+    pub auction_house: Pubkey,
+    pub seller: Pubkey,
+    pub metadata: Pubkey,
+    pub purchase_receipt: Option<Pubkey>,
+    pub price: u64,
+    pub token_size: u64,
+    pub bump: u8,
+    pub trade_state_bump: u8,
+    pub created_at: i64,
+    pub canceled_at: Option<i64>,
+}
+
+/// Receipt for a bid created by `buy` or `public_buy`, seeded by the buyer trade state.
+#[account]
+pub struct BidReceipt {
+    pub trade_state: Pubkey,
+    pub bookkeeper: Pubkey,
+    pub auction_house: Pubkey,
+    pub buyer: Pubkey,
+    pub metadata: Pubkey,
+    pub token_account: Option<Pubkey>,
+    pub purchase_receipt: Option<Pubkey>,
+    pub price: u64,
+    pub token_size: u64,
+    pub bump: u8,
+    pub trade_state_bump: u8,
+    pub created_at: i64,
+    pub canceled_at: Option<i64>,
+}
+
+/// Receipt for a sale completed by `execute_sale`, seeded by the seller and buyer trade states.
+#[account]
+pub struct PurchaseReceipt {
+    pub bookkeeper: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub auction_house: Pubkey,
+    pub metadata: Pubkey,
+    pub token_size: u64,
+    pub price: u64,
+    pub bump: u8,
+    pub created_at: i64,
+}
+
+#[account]
+pub struct EnglishAuction {
+    pub auction_house: Pubkey,
+    pub seller: Pubkey,
+    pub token_account: Pubkey,
+    pub seller_trade_state: Pubkey,
+    pub token_size: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub reserve_price: u64,
+    pub min_bid_increment: u64,
+    pub highest_bid: HighestBid,
+    pub locked_amount: u64,
+    pub bump: u8,
+    pub escrow_bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, PartialEq, Debug)]
+pub struct HighestBid {
+    pub amount: u64,
+    pub buyer: Pubkey,
+    pub buyer_trade_state: Pubkey,
+}
+
+#[account]
+pub struct DutchAuction {
+    pub auction_house: Pubkey,
+    pub seller: Pubkey,
+    pub token_account: Pubkey,
+    pub seller_trade_state: Pubkey,
+    pub token_size: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub start_price: u64,
+    pub floor_price: u64,
+    pub step_interval: i64,
+    pub bump: u8,
+}
+
+impl DutchAuction {
+    /// Price of the listing at `now`: linear decay from `start_price` to `floor_price` over the auction window,
+    /// applied every `step_interval` seconds when it is set.
+    pub fn price_at(&self, now: i64) -> Result<u64> {
+        if now <= self.start_time {
+            return Ok(self.start_price);
+        }
+        if now >= self.end_time {
+            return Ok(self.floor_price);
+        }
+
+        let mut elapsed = now - self.start_time;
+        if self.step_interval > 0 {
+            elapsed -= elapsed % self.step_interval;
+        }
+
+        let price_drop = ((self.start_price - self.floor_price) as u128)
+            .checked_mul(elapsed as u128)
+            .ok_or(AuctionHouseError::NumericalOverflow)?
+            .checked_div((self.end_time - self.start_time) as u128)
+            .ok_or(AuctionHouseError::NumericalOverflow)? as u64;
+
+        self.start_price
+            .checked_sub(price_drop)
+            .ok_or_else(|| AuctionHouseError::NumericalOverflow.into())
+    }
+}
+
+#[account]
+pub struct SealedAuction {
+    pub auction_house: Pubkey,
+    pub seller: Pubkey,
+    pub token_account: Pubkey,
+    pub seller_trade_state: Pubkey,
+    pub token_size: u64,
+    pub bid_end_time: i64,
+    pub reveal_end_time: i64,
+    pub reserve_price: u64,
+    pub vickrey: bool,
+    pub highest_bid: HighestBid,
+    pub second_price: u64,
+    pub locked_amount: u64,
+    pub bump: u8,
+    pub escrow_bump: u8,
+}
+
+#[account]
+pub struct SealedBid {
+    pub auction_house: Pubkey,
+    pub sealed_auction: Pubkey,
+    pub bidder: Pubkey,
+    pub commitment: [u8; 32],
+    pub collateral: u64,
+    pub bump: u8,
+    pub escrow_bump: u8,
+}
+
+impl SealedBid {
+    /// Commitment to `price` for `bidder` on `sealed_auction`. Binding the bidder and the auction keeps a commitment
+    /// seen on chain from being copied into another bid and revealed once its owner reveals.
+    pub fn commitment_of(
+        price: u64,
+        salt: &[u8; 32],
+        bidder: &Pubkey,
+        sealed_auction: &Pubkey,
+    ) -> [u8; 32] {
+        keccak::hashv(&[
+            &price.to_le_bytes(),
+            salt,
+            bidder.as_ref(),
+            sealed_auction.as_ref(),
+        ])
+        .to_bytes()
+    }
+}
+
+#[account]
+pub struct CollectionOffer {
+    pub auction_house: Pubkey,
+    pub buyer: Pubkey,
+    pub collection: Pubkey,
+    pub price: u64,
+    pub quantity: u64,
+    pub merkle_root: [u8; 32],
+    pub bump: u8,
+}
+
+#[account]
+pub struct Bundle {
+    pub auction_house: Pubkey,
+    pub seller: Pubkey,
+    pub price: u64,
+    pub items: Vec<BundleItem>,
+    pub bump: u8,
+}
+
+/// Listing bought by a sweep, at the price and size its trade state address commits to.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, PartialEq, Debug)]
+pub struct SweepListing {
+    pub price: u64,
+    pub token_size: u64,
+}
+
+/// One NFT of a bundle and the part of the bundle price its royalties are computed on.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, PartialEq, Debug)]
+pub struct BundleItem {
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub allocation: u64,
+}
+
+#[account]
+pub struct SwapOffer {
+    pub auction_house: Pubkey,
+    pub maker: Pubkey,
+    pub offered_token_account: Pubkey,
+    pub offered_mint: Pubkey,
+    pub requested_mint: Pubkey,
+    pub cash_amount: u64,
+    pub bump: u8,
+}
+
+/// Per-wallet trading volume, in treasury mint units, used to pick the wallet's fee tier.
+#[account]
+pub struct WalletStats {
+    pub auction_house: Pubkey,
+    pub wallet: Pubkey,
+    pub window_start: i64,
+    pub current_volume: u64,
+    pub previous_volume: u64,
+    pub bump: u8,
+}
+
+impl WalletStats {
+    /// Volume of the current and previous windows as of `now`.
+    pub fn trailing_volume(&self, now: i64) -> u64 {
+        if now >= self.window_start + 2 * FEE_TIER_WINDOW {
+            0
+        } else if now >= self.window_start + FEE_TIER_WINDOW {
+            self.current_volume
+        } else {
+            self.current_volume.saturating_add(self.previous_volume)
+        }
+    }
+
+    /// Adds `amount` to the current window, rolling the windows forward first if `now` is past it.
+    pub fn record_volume(&mut self, amount: u64, now: i64) -> Result<()> {
+        if now >= self.window_start + FEE_TIER_WINDOW {
+            self.previous_volume = if now >= self.window_start + 2 * FEE_TIER_WINDOW {
+                0
+            } else {
+                self.current_volume
+            };
+            self.current_volume = 0;
+            self.window_start = now;
+        }
+
+        self.current_volume = self
+            .current_volume
+            .checked_add(amount)
+            .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+        Ok(())
+    }
+}
+
+/// Referrer approved by the Auction House authority, paid `share_basis_points` of the house fee on sales it refers.
+#[account]
+pub struct Referrer {
+    pub auction_house: Pubkey,
+    pub wallet: Pubkey,
+    pub share_basis_points: u16,
+    pub bump: u8,
+}
+
+impl Referrer {
+    /// Part of a sale's `house_fee` paid to the referrer.
+    pub fn share_of(&self, house_fee: u64) -> Result<u64> {
+        Ok((self.share_basis_points as u128)
+            .checked_mul(house_fee as u128)
+            .ok_or(AuctionHouseError::NumericalOverflow)?
+            .checked_div(10000)
+            .ok_or(AuctionHouseError::NumericalOverflow)? as u64)
+    }
+}
+
+/// Split of treasury withdrawals across the co-operators of an Auction House.
+#[account]
+pub struct RevenueSplit {
+    pub auction_house: Pubkey,
+    pub recipients: Vec<RevenueRecipient>,
+    pub bump: u8,
+}
+
+/// Recipient of `share` percent of each split treasury withdrawal.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, PartialEq, Debug)]
+pub struct RevenueRecipient {
+    pub wallet: Pubkey,
+    pub share: u8,
+}
+
+/// Order details stored after the bump byte of a versioned trade state.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub struct TradeStateData {
+    pub version: u8,
+    pub side: TradeSide,
+    pub flags: u8,
+    pub auction_house: Pubkey,
+    pub wallet: Pubkey,
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub price: u64,
+    pub token_size: u64,
+    pub created_slot: u64,
+    pub created_at: i64,
+    pub expiry: i64,
+    pub fee_payer: Pubkey,
+    pub referrer: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub enum TradeSide {
+    Sell,
+    Buy,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
 #[repr(u32)]
 pub enum AuthorityScope {
@@ -41,3 +399,140 @@ pub enum AuthorityScope {
     Cancel = 5,
     Withdraw = 6,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dutch_auction(step_interval: i64) -> DutchAuction {
+        DutchAuction {
+            auction_house: Pubkey::default(),
+            seller: Pubkey::default(),
+            token_account: Pubkey::default(),
+            seller_trade_state: Pubkey::default(),
+            token_size: 1,
+            start_time: 1_000,
+            end_time: 2_000,
+            start_price: 10_000,
+            floor_price: 2_000,
+            step_interval,
+            bump: 0,
+        }
+    }
+
+    fn wallet_stats() -> WalletStats {
+        WalletStats {
+            auction_house: Pubkey::default(),
+            wallet: Pubkey::default(),
+            window_start: 0,
+            current_volume: 0,
+            previous_volume: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn dutch_auction_price_holds_outside_the_window() {
+        let auction = dutch_auction(0);
+
+        assert_eq!(auction.price_at(0).unwrap(), 10_000);
+        assert_eq!(auction.price_at(1_000).unwrap(), 10_000);
+        assert_eq!(auction.price_at(2_000).unwrap(), 2_000);
+        assert_eq!(auction.price_at(5_000).unwrap(), 2_000);
+    }
+
+    #[test]
+    fn dutch_auction_price_decays_linearly() {
+        let auction = dutch_auction(0);
+
+        assert_eq!(auction.price_at(1_250).unwrap(), 8_000);
+        assert_eq!(auction.price_at(1_500).unwrap(), 6_000);
+        assert_eq!(auction.price_at(1_999).unwrap(), 2_008);
+    }
+
+    #[test]
+    fn dutch_auction_price_drops_in_steps() {
+        let auction = dutch_auction(100);
+
+        assert_eq!(auction.price_at(1_099).unwrap(), 10_000);
+        assert_eq!(auction.price_at(1_100).unwrap(), 9_200);
+        assert_eq!(auction.price_at(1_550).unwrap(), 6_000);
+    }
+
+    #[test]
+    fn record_volume_accumulates_within_the_window() {
+        let mut stats = wallet_stats();
+
+        stats.record_volume(100, 10).unwrap();
+        stats.record_volume(50, FEE_TIER_WINDOW - 1).unwrap();
+
+        assert_eq!(stats.window_start, 0);
+        assert_eq!(stats.current_volume, 150);
+        assert_eq!(stats.trailing_volume(FEE_TIER_WINDOW - 1), 150);
+    }
+
+    #[test]
+    fn record_volume_rolls_into_the_previous_window() {
+        let mut stats = wallet_stats();
+
+        stats.record_volume(100, 10).unwrap();
+        stats.record_volume(50, FEE_TIER_WINDOW).unwrap();
+
+        assert_eq!(stats.window_start, FEE_TIER_WINDOW);
+        assert_eq!(stats.previous_volume, 100);
+        assert_eq!(stats.current_volume, 50);
+        assert_eq!(stats.trailing_volume(FEE_TIER_WINDOW), 150);
+        assert_eq!(stats.trailing_volume(2 * FEE_TIER_WINDOW), 50);
+        assert_eq!(stats.trailing_volume(3 * FEE_TIER_WINDOW), 0);
+    }
+
+    #[test]
+    fn record_volume_drops_windows_older_than_the_previous_one() {
+        let mut stats = wallet_stats();
+
+        stats.record_volume(100, 10).unwrap();
+        stats.record_volume(50, 2 * FEE_TIER_WINDOW).unwrap();
+
+        assert_eq!(stats.previous_volume, 0);
+        assert_eq!(stats.current_volume, 50);
+    }
+
+    #[test]
+    fn record_volume_rejects_overflow() {
+        let mut stats = wallet_stats();
+
+        stats.record_volume(u64::MAX, 0).unwrap();
+
+        assert!(stats.record_volume(1, 0).is_err());
+    }
+
+    #[test]
+    fn referrer_share_is_taken_from_the_house_fee() {
+        let referrer = Referrer {
+            auction_house: Pubkey::default(),
+            wallet: Pubkey::default(),
+            share_basis_points: 2_500,
+            bump: 0,
+        };
+
+        assert_eq!(referrer.share_of(1_000).unwrap(), 250);
+        assert_eq!(referrer.share_of(3).unwrap(), 0);
+        assert_eq!(referrer.share_of(0).unwrap(), 0);
+    }
+
+    #[test]
+    fn sealed_bid_commitment_is_bound_to_bidder_and_auction() {
+        let salt = [7; 32];
+        let bidder = Pubkey::new_unique();
+        let sealed_auction = Pubkey::new_unique();
+        let commitment = SealedBid::commitment_of(100, &salt, &bidder, &sealed_auction);
+
+        assert_eq!(commitment, SealedBid::commitment_of(100, &salt, &bidder, &sealed_auction));
+        assert_ne!(commitment, SealedBid::commitment_of(101, &salt, &bidder, &sealed_auction));
+        assert_ne!(
+            commitment,
+            SealedBid::commitment_of(100, &salt, &Pubkey::new_unique(), &sealed_auction)
+        );
+        assert_ne!(commitment, SealedBid::commitment_of(100, &salt, &bidder, &Pubkey::new_unique()));
+    }
+}
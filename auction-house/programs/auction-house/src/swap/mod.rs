@@ -0,0 +1,683 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke, program::invoke_signed, system_instruction},
+    AnchorDeserialize,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+use spl_token::instruction::{approve, revoke};
+
+use crate::constant::*;
+use crate::deposit::{deposit_logic, Deposit};
+use crate::errors::AuctionHouseError;
+use crate::state::{AuctionHouse, SwapOffer};
+use crate::utils::{
+    assert_is_ata, assert_keys_equal, assert_metadata_valid, compute_sale_fees,
+    get_delegate_from_token_account, get_fee_payer, make_ata, pay_auction_house_fee_amount,
+};
+
+/// Accounts for the [`make_swap_offer` handler](auction_house/fn.make_swap_offer.html).
+#[derive(Accounts)]
+#[instruction(escrow_payment_bump: u8, program_as_signer_bump: u8)]
+pub struct MakeSwapOffer<'info> {
+    /// Maker wallet account, paying for the swap offer account.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// CHECK: Validated in deposit_logic.
+    /// User SOL or SPL account to transfer the cash leg from.
+    #[account(mut)]
+    pub payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in deposit_logic.
+    /// SPL token account transfer authority.
+    pub transfer_authority: UncheckedAccount<'info>,
+
+    /// SPL token account containing the offered token.
+    #[account(mut)]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated by assert_metadata_valid.
+    /// Metaplex metadata account of the offered token.
+    pub metadata: UncheckedAccount<'info>,
+
+    /// Mint of the token requested in exchange.
+    pub requested_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Maker escrow payment account PDA, holding the cash leg.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// Auction House instance treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Validated in deposit_logic.
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// Swap offer account, one per offered token account and requested mint.
+    #[account(
+        init,
+        payer = wallet,
+        space = SWAP_OFFER_SIZE,
+        seeds = [
+            SWAP_OFFER.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref(),
+            token_account.key().as_ref(),
+            requested_mint.key().as_ref()
+        ],
+        bump
+    )]
+    pub swap_offer: Box<Account<'info, SwapOffer>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    #[account(seeds = [PREFIX.as_bytes(), SIGNER.as_bytes()], bump = program_as_signer_bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: Offer a token, plus `cash_amount` of the treasury mint deposited into the escrow along with the maker's fee on
+/// it, in exchange for a token of `requested_mint`. The offered token account is approved to `program_as_signer` as
+/// sell_logic does.
+pub fn make_swap_offer<'info>(
+    ctx: Context<'_, '_, '_, 'info, MakeSwapOffer<'info>>,
+    escrow_payment_bump: u8,
+    _program_as_signer_bump: u8,
+    cash_amount: u64,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let wallet = &accounts.wallet;
+    let token_account = &accounts.token_account;
+    let auction_house = &accounts.auction_house;
+    let token_program = &accounts.token_program;
+    let program_as_signer = &accounts.program_as_signer;
+
+    // NOTE: If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    if auction_house.requires_sign_off && !accounts.authority.is_signer {
+        return Err(AuctionHouseError::CannotTakeThisActionWithoutAuctionHouseSignOff.into());
+    }
+
+    assert_is_ata(
+        &token_account.to_account_info(),
+        &wallet.key(),
+        &token_account.mint,
+    )?;
+    assert_metadata_valid(&accounts.metadata, token_account)?;
+
+    if token_account.amount < 1 {
+        return Err(AuctionHouseError::InvalidTokenAmount.into());
+    }
+
+    invoke(
+        &approve(
+            &token_program.key(),
+            &token_account.key(),
+            &program_as_signer.key(),
+            &wallet.key(),
+            &[],
+            1,
+        )?,
+        &[
+            token_program.to_account_info(),
+            token_account.to_account_info(),
+            program_as_signer.to_account_info(),
+            wallet.to_account_info(),
+        ],
+    )?;
+
+    if cash_amount > 0 {
+        let mut deposit_accounts = Deposit {
+            wallet: wallet.clone(),
+            payment_account: accounts.payment_account.clone(),
+            transfer_authority: accounts.transfer_authority.clone(),
+            escrow_payment_account: accounts.escrow_payment_account.clone(),
+            treasury_mint: accounts.treasury_mint.clone(),
+            authority: accounts.authority.clone(),
+            auction_house: auction_house.clone(),
+            auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+            token_program: token_program.clone(),
+            system_program: accounts.system_program.clone(),
+            rent: accounts.rent.clone(),
+        };
+
+        // NOTE: the offer pays the cash leg as the maker of the trade
+        let maker_total = cash_amount
+            .checked_add(compute_sale_fees(auction_house, cash_amount, false, 0, 0)?.buyer_fee)
+            .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+        deposit_logic(&mut deposit_accounts, escrow_payment_bump, maker_total)?;
+    }
+
+    let swap_offer = &mut ctx.accounts.swap_offer;
+    swap_offer.auction_house = ctx.accounts.auction_house.key();
+    swap_offer.maker = ctx.accounts.wallet.key();
+    swap_offer.offered_token_account = ctx.accounts.token_account.key();
+    swap_offer.offered_mint = ctx.accounts.token_account.mint;
+    swap_offer.requested_mint = ctx.accounts.requested_mint.key();
+    swap_offer.cash_amount = cash_amount;
+    swap_offer.bump = *ctx
+        .bumps
+        .get("swap_offer")
+        .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?;
+
+    Ok(())
+}
+
+/// Accounts for the [`accept_swap_offer` handler](auction_house/fn.accept_swap_offer.html).
+#[derive(Accounts)]
+#[instruction(escrow_payment_bump: u8, program_as_signer_bump: u8)]
+pub struct AcceptSwapOffer<'info> {
+    /// Taker wallet account.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// SPL token account containing the requested token.
+    #[account(
+        mut,
+        constraint = token_account.mint == swap_offer.requested_mint
+    )]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated by the `has_one` constraint on the swap offer.
+    /// Maker wallet account, receiving the rent of the swap offer account.
+    #[account(mut)]
+    pub maker: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the `constraint` on the swap offer and in accept_swap_offer.
+    /// SPL token account containing the offered token.
+    #[account(
+        mut,
+        constraint = maker_token_account.key() == swap_offer.offered_token_account
+    )]
+    pub maker_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the `constraint` on the swap offer.
+    /// Mint of the offered token.
+    #[account(constraint = offered_mint.key() == swap_offer.offered_mint)]
+    pub offered_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the `constraint` on the swap offer.
+    /// Mint of the requested token.
+    #[account(constraint = requested_mint.key() == swap_offer.requested_mint)]
+    pub requested_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in accept_swap_offer.
+    /// Maker token account to receive the requested token at.
+    #[account(mut)]
+    pub maker_receipt_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in accept_swap_offer.
+    /// Taker token account to receive the offered token at.
+    #[account(mut)]
+    pub taker_receipt_token_account: UncheckedAccount<'info>,
+
+    /// Auction House treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Maker escrow payment account PDA, holding the cash leg.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            maker.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in accept_swap_offer.
+    /// Taker SOL or SPL account to receive the cash leg at.
+    #[account(mut)]
+    pub taker_payment_receipt_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the auction house.
+    /// Auction House instance authority.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_treasury,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance treasury account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            TREASURY.as_bytes()
+        ],
+        bump = auction_house.treasury_bump
+    )]
+    pub auction_house_treasury: UncheckedAccount<'info>,
+
+    /// Swap offer account being accepted, closed back to the maker.
+    #[account(
+        mut,
+        close = maker,
+        seeds = [
+            SWAP_OFFER.as_bytes(),
+            auction_house.key().as_ref(),
+            maker.key().as_ref(),
+            swap_offer.offered_token_account.as_ref(),
+            swap_offer.requested_mint.as_ref()
+        ],
+        bump = swap_offer.bump,
+        has_one = auction_house,
+        has_one = maker
+    )]
+    pub swap_offer: Box<Account<'info, SwapOffer>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub ata_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    #[account(seeds = [PREFIX.as_bytes(), SIGNER.as_bytes()], bump = program_as_signer_bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: Accept a swap offer. The taker's token account is approved to `program_as_signer` like the maker's, then
+/// both tokens are moved by it in the same instruction. House fees apply to the cash leg only.
+pub fn accept_swap_offer<'info>(
+    ctx: Context<'_, '_, '_, 'info, AcceptSwapOffer<'info>>,
+    escrow_payment_bump: u8,
+    program_as_signer_bump: u8,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let wallet = &accounts.wallet;
+    let token_account = &accounts.token_account;
+    let maker = &accounts.maker;
+    let maker_token_account = &accounts.maker_token_account;
+    let offered_mint = &accounts.offered_mint;
+    let requested_mint = &accounts.requested_mint;
+    let maker_receipt_token_account = &accounts.maker_receipt_token_account;
+    let taker_receipt_token_account = &accounts.taker_receipt_token_account;
+    let treasury_mint = &accounts.treasury_mint;
+    let escrow_payment_account = &accounts.escrow_payment_account;
+    let taker_payment_receipt_account = &accounts.taker_payment_receipt_account;
+    let authority = &accounts.authority;
+    let auction_house = &accounts.auction_house;
+    let auction_house_fee_account = &accounts.auction_house_fee_account;
+    let auction_house_treasury = &accounts.auction_house_treasury;
+    let swap_offer = &accounts.swap_offer;
+    let token_program = &accounts.token_program;
+    let system_program = &accounts.system_program;
+    let ata_program = &accounts.ata_program;
+    let program_as_signer = &accounts.program_as_signer;
+    let rent = &accounts.rent;
+
+    // NOTE: If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    let is_native = treasury_mint.key() == spl_token::native_mint::id();
+
+    assert_is_ata(
+        &token_account.to_account_info(),
+        &wallet.key(),
+        &token_account.mint,
+    )?;
+
+    if token_account.amount < 1 {
+        return Err(AuctionHouseError::InvalidTokenAmount.into());
+    }
+
+    assert_is_ata(
+        &maker_token_account.to_account_info(),
+        &maker.key(),
+        &swap_offer.offered_mint,
+    )?;
+
+    match get_delegate_from_token_account(&maker_token_account.to_account_info())? {
+        Some(delegate) => assert_keys_equal(program_as_signer.key(), delegate)?,
+        None => return Err(AuctionHouseError::BothPartiesNeedToAgreeToSale.into()),
+    }
+
+    invoke(
+        &approve(
+            &token_program.key(),
+            &token_account.key(),
+            &program_as_signer.key(),
+            &wallet.key(),
+            &[],
+            1,
+        )?,
+        &[
+            token_program.to_account_info(),
+            token_account.to_account_info(),
+            program_as_signer.to_account_info(),
+            wallet.to_account_info(),
+        ],
+    )?;
+
+    let auction_house_key = auction_house.key();
+    let seeds = [
+        PREFIX.as_bytes(),
+        auction_house_key.as_ref(),
+        FEE_PAYER.as_bytes(),
+        &[auction_house.fee_payer_bump],
+    ];
+
+    let (fee_payer, fee_payer_seeds) = get_fee_payer(
+        authority,
+        auction_house,
+        wallet.to_account_info(),
+        auction_house_fee_account.to_account_info(),
+        &seeds,
+    )?;
+
+    let program_as_signer_seeds = [
+        PREFIX.as_bytes(),
+        SIGNER.as_bytes(),
+        &[program_as_signer_bump],
+    ];
+
+    // NOTE: send the offered token to the taker and the requested token to the maker
+    for (source, receipt, receipt_owner, mint) in [
+        (
+            maker_token_account.to_account_info(),
+            taker_receipt_token_account,
+            wallet.to_account_info(),
+            offered_mint,
+        ),
+        (
+            token_account.to_account_info(),
+            maker_receipt_token_account,
+            maker.to_account_info(),
+            requested_mint,
+        ),
+    ] {
+        if receipt.data_is_empty() {
+            make_ata(
+                receipt.to_account_info(),
+                receipt_owner.clone(),
+                mint.to_account_info(),
+                fee_payer.to_account_info(),
+                ata_program.to_account_info(),
+                token_program.to_account_info(),
+                system_program.to_account_info(),
+                rent.to_account_info(),
+                fee_payer_seeds,
+            )?;
+        }
+
+        let receipt_record_account =
+            assert_is_ata(&receipt.to_account_info(), receipt_owner.key, &mint.key())?;
+
+        if receipt_record_account.delegate.is_some() {
+            return Err(AuctionHouseError::BuyerATACannotHaveDelegate.into());
+        }
+
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                source.key,
+                &receipt.key(),
+                &program_as_signer.key(),
+                &[],
+                1,
+            )?,
+            &[
+                source,
+                receipt.to_account_info(),
+                program_as_signer.to_account_info(),
+                token_program.to_account_info(),
+            ],
+            &[&program_as_signer_seeds],
+        )?;
+    }
+
+    if swap_offer.cash_amount == 0 {
+        return Ok(());
+    }
+
+    // NOTE: the maker pays its fee on top of the cash leg, the taker gets the rest after its own fee
+
+    let maker_key = maker.key();
+    let escrow_signer_seeds = [
+        PREFIX.as_bytes(),
+        auction_house_key.as_ref(),
+        maker_key.as_ref(),
+        &[escrow_payment_bump],
+    ];
+
+    let auction_house_seeds = [
+        PREFIX.as_bytes(),
+        auction_house.creator.as_ref(),
+        auction_house.treasury_mint.as_ref(),
+        &[auction_house.bump],
+    ];
+
+    let signer_seeds_for_fees = if is_native {
+        escrow_signer_seeds
+    } else {
+        auction_house_seeds
+    };
+
+    let sale_fees = compute_sale_fees(auction_house, swap_offer.cash_amount, false, 0, 0)?;
+
+    pay_auction_house_fee_amount(
+        auction_house,
+        &auction_house_treasury.to_account_info(),
+        &escrow_payment_account.to_account_info(),
+        &token_program.to_account_info(),
+        &system_program.to_account_info(),
+        &signer_seeds_for_fees,
+        sale_fees.house_fee,
+        is_native,
+    )?;
+
+    let taker_proceeds = swap_offer
+        .cash_amount
+        .checked_sub(sale_fees.seller_fee)
+        .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+    if !is_native {
+        if taker_payment_receipt_account.data_is_empty() {
+            make_ata(
+                taker_payment_receipt_account.to_account_info(),
+                wallet.to_account_info(),
+                treasury_mint.to_account_info(),
+                fee_payer.to_account_info(),
+                ata_program.to_account_info(),
+                token_program.to_account_info(),
+                system_program.to_account_info(),
+                rent.to_account_info(),
+                fee_payer_seeds,
+            )?;
+        }
+
+        let taker_record_account = assert_is_ata(
+            &taker_payment_receipt_account.to_account_info(),
+            &wallet.key(),
+            &treasury_mint.key(),
+        )?;
+
+        if taker_record_account.delegate.is_some() {
+            return Err(AuctionHouseError::SellerATACannotHaveDelegate.into());
+        }
+
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                &escrow_payment_account.key(),
+                &taker_payment_receipt_account.key(),
+                &auction_house.key(),
+                &[],
+                taker_proceeds,
+            )?,
+            &[
+                escrow_payment_account.to_account_info(),
+                taker_payment_receipt_account.to_account_info(),
+                token_program.to_account_info(),
+                auction_house.to_account_info(),
+            ],
+            &[&auction_house_seeds],
+        )?;
+    } else {
+        assert_keys_equal(taker_payment_receipt_account.key(), wallet.key())?;
+        invoke_signed(
+            &system_instruction::transfer(
+                escrow_payment_account.key,
+                taker_payment_receipt_account.key,
+                taker_proceeds,
+            ),
+            &[
+                escrow_payment_account.to_account_info(),
+                taker_payment_receipt_account.to_account_info(),
+                system_program.to_account_info(),
+            ],
+            &[&escrow_signer_seeds],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Accounts for the [`cancel_swap_offer` handler](auction_house/fn.cancel_swap_offer.html).
+#[derive(Accounts)]
+pub struct CancelSwapOffer<'info> {
+    /// Maker wallet account, receiving the rent of the swap offer account.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// SPL token account containing the offered token.
+    #[account(
+        mut,
+        constraint = token_account.key() == swap_offer.offered_token_account
+    )]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// Swap offer account being canceled.
+    #[account(
+        mut,
+        close = wallet,
+        seeds = [
+            SWAP_OFFER.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref(),
+            swap_offer.offered_token_account.as_ref(),
+            swap_offer.requested_mint.as_ref()
+        ],
+        bump = swap_offer.bump,
+        has_one = auction_house,
+        constraint = swap_offer.maker == wallet.key()
+    )]
+    pub swap_offer: Box<Account<'info, SwapOffer>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// NOTE: Cancel a swap offer, revoking the offered token account. The cash leg stays in the escrow and can be
+/// withdrawn.
+pub fn cancel_swap_offer<'info>(ctx: Context<'_, '_, '_, 'info, CancelSwapOffer<'info>>) -> Result<()> {
+    let wallet = &ctx.accounts.wallet;
+    let token_account = &ctx.accounts.token_account;
+    let token_program = &ctx.accounts.token_program;
+
+    if token_account.owner == wallet.key() {
+        invoke(
+            &revoke(
+                &token_program.key(),
+                &token_account.key(),
+                &wallet.key(),
+                &[],
+            )?,
+            &[
+                token_program.to_account_info(),
+                token_account.to_account_info(),
+                wallet.to_account_info(),
+            ],
+        )?;
+    }
+
+    Ok(())
+}
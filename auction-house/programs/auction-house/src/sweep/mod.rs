@@ -0,0 +1,361 @@
+use anchor_lang::{prelude::*, solana_program::program_option::COption, AnchorDeserialize};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::bid::bid_logic;
+use crate::constant::*;
+use crate::errors::AuctionHouseError;
+use crate::execute_sale::{execute_sale_logic, ExecuteSale, ExecuteSaleOptions};
+use crate::state::{AuctionHouse, SweepListing, TradeSide};
+use crate::utils::{
+    assert_derivation, assert_keys_equal, collect_trade_state_rent, compute_sale_fees, get_approved_referrer,
+    get_creator_accounts_len, get_fee_payer, get_trade_state_data,
+};
+
+/// Accounts for the [`sweep` handler](auction_house/fn.sweep.html).
+#[derive(Accounts)]
+#[instruction(escrow_payment_bump: u8, program_as_signer_bump: u8)]
+pub struct Sweep<'info> {
+    /// Buyer's wallet account.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// User SOL or SPL account to transfer funds from.
+    #[account(mut)]
+    pub payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic.
+    /// SPL token account transfer authority.
+    pub transfer_authority: UncheckedAccount<'info>,
+
+    /// Auction House treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer escrow payment account PDA.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in bid_logic and execute_sale_logic.
+    /// Auction House instance authority.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_treasury,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance treasury account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            TREASURY.as_bytes()
+        ],
+        bump = auction_house.treasury_bump
+    )]
+    pub auction_house_treasury: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub ata_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    #[account(seeds = [PREFIX.as_bytes(), SIGNER.as_bytes()], bump = program_as_signer_bump)]
+    pub program_as_signer: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: Buy several listings at once, spending at most `max_total` including the taker fees. The remaining accounts
+/// hold one group per entry of `listings`: `[seller, token_account, token_mint, metadata, seller_trade_state,
+/// free_trade_state, buyer_trade_state, seller_payment_receipt_account, buyer_receipt_token_account]` followed by the
+/// creator accounts pay_creator_fees expects, the buyer and seller wallet stats addresses and, with a `referrer`, its
+/// referral accounts as for execute_sale. Each entry's price and size are checked against the listing's trade state
+/// address, and each sale goes through bid_logic and execute_sale_logic. Listings that were filled, canceled, expired
+/// or no longer fit the budget are skipped, as are free, auction and bundle listings.
+pub fn sweep<'info>(
+    ctx: Context<'_, '_, '_, 'info, Sweep<'info>>,
+    escrow_payment_bump: u8,
+    program_as_signer_bump: u8,
+    max_total: u64,
+    listings: Vec<SweepListing>,
+    referrer: Option<Pubkey>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let wallet = &accounts.wallet;
+    let auction_house = &accounts.auction_house;
+    let remaining_accounts = ctx.remaining_accounts;
+
+    // NOTE: If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    let is_native = accounts.treasury_mint.key() == spl_token::native_mint::id();
+    let now = Clock::get()?.unix_timestamp;
+    let wallet_key = wallet.key();
+    let auction_house_key = auction_house.key();
+
+    let referral_accounts = if referrer.is_some() { REFERRAL_GROUP_SIZE } else { 0 };
+
+    let mut spent: u64 = 0;
+    let mut cursor = 0;
+    let mut emptied_trade_states = Vec::new();
+
+    for sweep_listing in listings.iter() {
+        let group = remaining_accounts
+            .get(cursor..cursor + SWEEP_GROUP_SIZE)
+            .ok_or(AuctionHouseError::InvalidSweepAccounts)?;
+        let seller = &group[0];
+        let token_account = &group[1];
+        let token_mint = &group[2];
+        let metadata = &group[3];
+        let seller_trade_state = &group[4];
+        let free_trade_state = &group[5];
+        let buyer_trade_state = &group[6];
+        let seller_payment_receipt_account = &group[7];
+        let buyer_receipt_token_account = &group[8];
+
+        // NOTE: the creator and wallet stats accounts are needed to find the next group, even for a skipped listing
+        let creator_accounts = get_creator_accounts_len(metadata, is_native)?;
+        let sale_accounts_start = cursor + SWEEP_GROUP_SIZE;
+        let sale_accounts_end =
+            sale_accounts_start + creator_accounts + WALLET_STATS_GROUP_SIZE + referral_accounts;
+        let sale_accounts = remaining_accounts
+            .get(sale_accounts_start..sale_accounts_end)
+            .ok_or(AuctionHouseError::InvalidSweepAccounts)?;
+        cursor = sale_accounts_end;
+
+        let price = sweep_listing.price;
+        let size = sweep_listing.token_size;
+
+        // NOTE: the trade state address commits to the price and size, so legacy listings can be swept too
+        assert_derivation(
+            &crate::id(),
+            seller_trade_state,
+            &[
+                PREFIX.as_bytes(),
+                seller.key.as_ref(),
+                auction_house_key.as_ref(),
+                token_account.key.as_ref(),
+                auction_house.treasury_mint.as_ref(),
+                token_mint.key.as_ref(),
+                &price.to_le_bytes(),
+                &size.to_le_bytes(),
+            ],
+        )?;
+
+        if seller_trade_state.data_is_empty() || price == 0 || price == AUCTION_LISTING_PRICE {
+            continue;
+        }
+
+        if let Some(listing) = get_trade_state_data(&seller_trade_state.try_borrow_data()?) {
+            if listing.side != TradeSide::Sell
+                || (listing.expiry != 0 && listing.expiry <= now)
+                || listing.flags & TRADE_STATE_FLAG_BUNDLE != 0
+            {
+                continue;
+            }
+        }
+
+        // NOTE: the resting listing is the maker, so the sweep pays the taker fee on top of each price
+        let total = spent
+            .checked_add(price)
+            .ok_or(AuctionHouseError::NumericalOverflow)?
+            .checked_add(compute_sale_fees(auction_house, price, true, 0, 0)?.buyer_fee)
+            .ok_or(AuctionHouseError::NumericalOverflow)?;
+        if total > max_total {
+            continue;
+        }
+
+        // NOTE: a listing whose token moved or whose delegation was revoked can no longer be filled
+        let token: Account<TokenAccount> = Account::try_from(token_account)?;
+        if token.owner != seller.key()
+            || token.amount < size
+            || token.delegate != COption::Some(accounts.program_as_signer.key())
+            || token.delegated_amount < size
+        {
+            continue;
+        }
+
+        let buyer_trade_state_bump = assert_derivation(
+            &crate::id(),
+            buyer_trade_state,
+            &[
+                PREFIX.as_bytes(),
+                wallet_key.as_ref(),
+                auction_house_key.as_ref(),
+                token_account.key.as_ref(),
+                auction_house.treasury_mint.as_ref(),
+                token.mint.as_ref(),
+                &price.to_le_bytes(),
+                &size.to_le_bytes(),
+            ],
+        )?;
+
+        let free_trade_state_bump = assert_derivation(
+            &crate::id(),
+            free_trade_state,
+            &[
+                PREFIX.as_bytes(),
+                seller.key.as_ref(),
+                auction_house_key.as_ref(),
+                token_account.key.as_ref(),
+                auction_house.treasury_mint.as_ref(),
+                token.mint.as_ref(),
+                &0u64.to_le_bytes(),
+                &size.to_le_bytes(),
+            ],
+        )?;
+
+        if let Some(referrer) = referrer {
+            let approved = get_approved_referrer(
+                &sale_accounts[creator_accounts + WALLET_STATS_GROUP_SIZE],
+                &auction_house_key,
+            )?;
+            assert_keys_equal(approved.wallet, referrer)?;
+        }
+
+        bid_logic(
+            wallet.to_owned(),
+            accounts.payment_account.to_owned(),
+            accounts.transfer_authority.to_owned(),
+            *accounts.treasury_mint.to_owned(),
+            token,
+            UncheckedAccount::try_from(metadata.clone()),
+            accounts.escrow_payment_account.to_owned(),
+            accounts.authority.to_owned(),
+            *auction_house.to_owned(),
+            accounts.auction_house_fee_account.to_owned(),
+            UncheckedAccount::try_from(buyer_trade_state.clone()),
+            accounts.token_program.to_owned(),
+            accounts.system_program.to_owned(),
+            accounts.rent.to_owned(),
+            buyer_trade_state_bump,
+            escrow_payment_bump,
+            price,
+            size,
+            false,
+            None,
+            referrer,
+        )?;
+
+        let mut execute_sale_accounts = ExecuteSale {
+            buyer: UncheckedAccount::try_from(wallet.to_account_info()),
+            seller: UncheckedAccount::try_from(seller.clone()),
+            token_account: UncheckedAccount::try_from(token_account.clone()),
+            token_mint: UncheckedAccount::try_from(token_mint.clone()),
+            metadata: UncheckedAccount::try_from(metadata.clone()),
+            treasury_mint: UncheckedAccount::try_from(accounts.treasury_mint.to_account_info()),
+            escrow_payment_account: accounts.escrow_payment_account.clone(),
+            seller_payment_receipt_account: UncheckedAccount::try_from(
+                seller_payment_receipt_account.clone(),
+            ),
+            buyer_receipt_token_account: UncheckedAccount::try_from(
+                buyer_receipt_token_account.clone(),
+            ),
+            authority: accounts.authority.clone(),
+            auction_house: auction_house.clone(),
+            auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+            auction_house_treasury: accounts.auction_house_treasury.clone(),
+            buyer_trade_state: UncheckedAccount::try_from(buyer_trade_state.clone()),
+            seller_trade_state: UncheckedAccount::try_from(seller_trade_state.clone()),
+            free_trade_state: UncheckedAccount::try_from(free_trade_state.clone()),
+            token_program: accounts.token_program.clone(),
+            system_program: accounts.system_program.clone(),
+            ata_program: accounts.ata_program.clone(),
+            program_as_signer: accounts.program_as_signer.clone(),
+            rent: accounts.rent.clone(),
+        };
+
+        execute_sale_logic(
+            Context::new(
+                ctx.program_id,
+                &mut execute_sale_accounts,
+                sale_accounts,
+                ctx.bumps.clone(),
+            ),
+            escrow_payment_bump,
+            free_trade_state_bump,
+            program_as_signer_bump,
+            price,
+            size,
+            ExecuteSaleOptions {
+                defer_trade_state_rent: true,
+                ..Default::default()
+            },
+        )?;
+
+        emptied_trade_states.extend([
+            seller_trade_state.clone(),
+            free_trade_state.clone(),
+            buyer_trade_state.clone(),
+        ]);
+
+        spent = total;
+    }
+
+    if cursor != remaining_accounts.len() {
+        return Err(AuctionHouseError::InvalidSweepAccounts.into());
+    }
+
+    // NOTE: the rent of the emptied trade states is only collected once every sale ran its CPIs
+    if !emptied_trade_states.is_empty() {
+        let seeds = [
+            PREFIX.as_bytes(),
+            auction_house_key.as_ref(),
+            FEE_PAYER.as_bytes(),
+            &[auction_house.fee_payer_bump],
+        ];
+        let (fee_payer, _) = get_fee_payer(
+            &accounts.authority,
+            auction_house,
+            wallet.to_account_info(),
+            accounts.auction_house_fee_account.to_account_info(),
+            &seeds,
+        )?;
+
+        for trade_state in emptied_trade_states.iter() {
+            collect_trade_state_rent(trade_state, &fee_payer)?;
+        }
+    }
+
+    Ok(())
+}
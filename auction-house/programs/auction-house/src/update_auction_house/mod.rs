@@ -0,0 +1,149 @@
+use anchor_lang::{prelude::*, AnchorDeserialize};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token},
+};
+
+use crate::constant::*;
+use crate::errors::AuctionHouseError;
+use crate::state::AuctionHouse;
+use crate::utils::{assert_is_ata, assert_keys_equal, make_ata};
+
+/// Accounts for the [`update_auction_house` handler](auction_house/fn.update_auction_house.html).
+#[derive(Accounts)]
+pub struct UpdateAuctionHouse<'info> {
+    /// Treasury mint account, must match the Auction House's treasury mint.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// Key paying SOL fees for creating a new treasury withdrawal ATA if needed.
+    #[account(mut @ AuctionHouseError::NotMutableAccount)]
+    pub payer: Signer<'info>,
+
+    /// Current authority key for the Auction House.
+    pub authority: Signer<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub ata_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// NOTE: Update the Auction House config. `None` arguments leave the stored value untouched. The treasury withdrawal
+/// destination is only replaced when `[treasury_withdrawal_destination, treasury_withdrawal_destination_owner]` are
+/// passed as remaining accounts, the owner being the destination itself if the `treasury_mint` is native.
+pub fn update_auction_house<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateAuctionHouse<'info>>,
+    seller_fee_basis_points: Option<u16>,
+    requires_sign_off: Option<bool>,
+    can_change_sale_price: Option<bool>,
+    maker_fee_basis_points: Option<i16>,
+    taker_fee_basis_points: Option<u16>,
+    new_authority: Option<Pubkey>,
+    fee_withdrawal_destination: Option<Pubkey>,
+) -> Result<()> {
+    // NOTE: extract all variables from ctx (UpdateAuctionHouse struct)
+    let treasury_mint = &ctx.accounts.treasury_mint;
+    let payer = &ctx.accounts.payer;
+    let auction_house = &mut ctx.accounts.auction_house;
+    let token_program = &ctx.accounts.token_program;
+    let system_program = &ctx.accounts.system_program;
+    let ata_program = &ctx.accounts.ata_program;
+    let rent = &ctx.accounts.rent;
+
+    let is_native = treasury_mint.key() == spl_token::native_mint::id();
+
+    // NOTE: validate the input seller fee basis point
+    if let Some(seller_fee_basis_points) = seller_fee_basis_points {
+        if seller_fee_basis_points > 10000 {
+            return Err(AuctionHouseError::InvalidBasisPoints.into());
+        }
+
+        auction_house.seller_fee_basis_points = seller_fee_basis_points;
+    }
+
+    if let Some(requires_sign_off) = requires_sign_off {
+        auction_house.requires_sign_off = requires_sign_off;
+    }
+
+    if let Some(can_change_sale_price) = can_change_sale_price {
+        auction_house.can_change_sale_price = can_change_sale_price;
+    }
+
+    if let Some(maker_fee_basis_points) = maker_fee_basis_points {
+        auction_house.maker_fee_basis_points = maker_fee_basis_points;
+    }
+
+    if let Some(taker_fee_basis_points) = taker_fee_basis_points {
+        auction_house.taker_fee_basis_points = taker_fee_basis_points;
+    }
+
+    // NOTE: a maker rebate is paid out of the taker fee, so it cannot exceed it
+    if auction_house.taker_fee_basis_points > 10000
+        || auction_house.maker_fee_basis_points > 10000
+        || -(auction_house.maker_fee_basis_points as i32)
+            > auction_house.taker_fee_basis_points as i32
+    {
+        return Err(AuctionHouseError::InvalidBasisPoints.into());
+    }
+
+    if let Some(new_authority) = new_authority {
+        auction_house.authority = new_authority;
+    }
+
+    if let Some(fee_withdrawal_destination) = fee_withdrawal_destination {
+        auction_house.fee_withdrawal_destination = fee_withdrawal_destination;
+    }
+
+    let (treasury_withdrawal_destination, treasury_withdrawal_destination_owner) =
+        match ctx.remaining_accounts {
+            [] => return Ok(()),
+            [destination, owner] => (destination, owner),
+            _ => return Err(AuctionHouseError::InvalidTreasuryWithdrawalDestinationAccounts.into()),
+        };
+
+    // NOTE: validate the new treasury withdrawal destination the same way create_auction_house does
+    if is_native {
+        assert_keys_equal(
+            treasury_withdrawal_destination.key(),
+            treasury_withdrawal_destination_owner.key(),
+        )?;
+    } else {
+        if treasury_withdrawal_destination.data_is_empty() {
+            make_ata(
+                treasury_withdrawal_destination.to_account_info(),
+                treasury_withdrawal_destination_owner.to_account_info(),
+                treasury_mint.to_account_info(),
+                payer.to_account_info(),
+                ata_program.to_account_info(),
+                token_program.to_account_info(),
+                system_program.to_account_info(),
+                rent.to_account_info(),
+                &[],
+            )?;
+        }
+
+        assert_is_ata(
+            treasury_withdrawal_destination,
+            &treasury_withdrawal_destination_owner.key(),
+            &treasury_mint.key(),
+        )?;
+    }
+
+    auction_house.treasury_withdrawal_destination = treasury_withdrawal_destination.key();
+
+    Ok(())
+}
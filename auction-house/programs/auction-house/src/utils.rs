@@ -4,7 +4,9 @@ use crate::constant;
 use crate::constant::PREFIX;
 use crate::errors;
 use crate::errors::AuctionHouseError;
-use crate::state::AuctionHouse;
+use crate::state::{
+    AuctionHouse, Auctioneer, AuthorityScope, HighestBid, Referrer, TradeStateData, WalletStats,
+};
 use anchor_lang::accounts::signer;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
@@ -12,8 +14,8 @@ use arrayref::array_ref;
 use mpl_token_metadata::state::Metadata;
 use solana_program::program_pack::IsInitialized;
 use solana_program::{
-    program::invoke_signed, program_memory::sol_memcmp, program_pack::Pack, pubkey::PUBKEY_BYTES,
-    system_instruction,
+    keccak, program::invoke, program::invoke_signed, program_memory::{sol_memcmp, sol_memset},
+    program_pack::Pack, pubkey::PUBKEY_BYTES, system_instruction,
 };
 use spl_associated_token_account::*;
 use spl_token::instruction::initialize_account2;
@@ -38,6 +40,27 @@ pub fn assert_is_ata(ata: &AccountInfo, wallet: &Pubkey, mint: &Pubkey) -> Resul
     Ok(ata_account)
 }
 
+/// Check that the house is delegated to `auctioneer_authority` and that the delegation grants `scope`.
+pub fn assert_valid_auctioneer_and_scope(
+    auction_house: &Account<AuctionHouse>,
+    auctioneer_authority: &Pubkey,
+    auctioneer_pda: &Account<Auctioneer>,
+    scope: AuthorityScope,
+) -> Result<()> {
+    if !auction_house.has_auctioneer {
+        return err!(errors::AuctionHouseError::AuctionHouseNotDelegated);
+    }
+
+    assert_keys_equal(auctioneer_pda.auctioneer_authority, *auctioneer_authority)?;
+    assert_keys_equal(auctioneer_pda.auction_house, auction_house.key())?;
+
+    if !auctioneer_pda.scopes[scope as usize] {
+        return err!(errors::AuctionHouseError::MissingAuctioneerScope);
+    }
+
+    Ok(())
+}
+
 pub fn assert_owned_by(account: &AccountInfo, owner: &Pubkey) -> Result<()> {
     if account.owner != owner {
         return err!(errors::AuctionHouseError::IncorrectOwner);
@@ -255,6 +278,33 @@ pub fn assert_metadata_valid<'a>(
     Ok(())
 }
 
+pub fn assert_verified_collection(metadata: &UncheckedAccount, collection: &Pubkey) -> Result<()> {
+    let metadata = Metadata::from_account_info(&metadata.to_account_info())?;
+
+    match metadata.collection {
+        Some(metadata_collection)
+            if metadata_collection.verified && metadata_collection.key == *collection =>
+        {
+            Ok(())
+        }
+        _ => Err(AuctionHouseError::InvalidCollection.into()),
+    }
+}
+
+/// Verify a merkle proof over a tree with `keccak(0x00 || leaf)` leaves and `keccak(0x01 || min || max)` nodes,
+/// the prefixes keeping a node from being passed off as a leaf.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: &[u8]) -> bool {
+    let mut computed = keccak::hashv(&[&[0x00], leaf]).to_bytes();
+    for node in proof {
+        computed = if computed <= *node {
+            keccak::hashv(&[&[0x01], &computed, node]).to_bytes()
+        } else {
+            keccak::hashv(&[&[0x01], node, &computed]).to_bytes()
+        };
+    }
+    computed == *root
+}
+
 pub fn assert_derivation(
     program_id: &Pubkey,
     account: &AccountInfo,
@@ -345,6 +395,102 @@ pub fn get_delegate_from_token_account(token_account_info: &AccountInfo) -> Resu
     }
 }
 
+/// Write the bump of a freshly created trade state followed by its versioned order details.
+pub fn write_trade_state(
+    trade_state: &AccountInfo,
+    trade_state_bump: u8,
+    trade_state_data: &TradeStateData,
+) -> Result<()> {
+    let mut data = trade_state.try_borrow_mut_data()?;
+    data[0] = trade_state_bump;
+    let mut details: &mut [u8] = &mut data[1..];
+    trade_state_data.serialize(&mut details)?;
+    Ok(())
+}
+
+/// Order details of a versioned trade state, `None` for legacy 1-byte trade states.
+pub fn get_trade_state_data(data: &[u8]) -> Option<TradeStateData> {
+    if data.len() < constant::TRADE_STATE_SIZE || data[1] != constant::TRADE_STATE_VERSION {
+        return None;
+    }
+    TradeStateData::deserialize(&mut &data[1..]).ok()
+}
+
+/// Flag a versioned trade state as locked by an auction, so only the auction can fill or release it.
+pub fn lock_trade_state(trade_state: &AccountInfo) -> Result<()> {
+    let mut trade_state_data = get_trade_state_data(&trade_state.try_borrow_data()?)
+        .ok_or(AuctionHouseError::TradeStateLocked)?;
+    trade_state_data.flags |= constant::TRADE_STATE_FLAG_LOCKED;
+    let trade_state_bump = trade_state.try_borrow_data()?[0];
+    write_trade_state(trade_state, trade_state_bump, &trade_state_data)
+}
+
+/// Flag a versioned seller trade state as an item of a bundle, so it is only sold together with the other items.
+pub fn bundle_trade_state(trade_state: &AccountInfo) -> Result<()> {
+    let mut trade_state_data = get_trade_state_data(&trade_state.try_borrow_data()?)
+        .ok_or(AuctionHouseError::InvalidBundle)?;
+    trade_state_data.flags |= constant::TRADE_STATE_FLAG_BUNDLE;
+    let trade_state_bump = trade_state.try_borrow_data()?[0];
+    write_trade_state(trade_state, trade_state_bump, &trade_state_data)
+}
+
+pub fn assert_trade_state_not_bundled(data: &[u8]) -> Result<()> {
+    match get_trade_state_data(data) {
+        Some(trade_state_data) if trade_state_data.flags & constant::TRADE_STATE_FLAG_BUNDLE != 0 => {
+            Err(AuctionHouseError::BundledListing.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Credit the rent of an emptied trade state to `fee_payer`. Handlers running several orders collect it once their
+/// last CPI ran, as the runtime only balances the accounts of each CPI and rent credited before would unbalance it.
+pub fn collect_trade_state_rent(trade_state: &AccountInfo, fee_payer: &AccountInfo) -> Result<()> {
+    let curr_lamp = trade_state.lamports();
+    **trade_state.lamports.borrow_mut() = 0;
+
+    **fee_payer.lamports.borrow_mut() = fee_payer
+        .lamports()
+        .checked_add(curr_lamp)
+        .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+    Ok(())
+}
+
+pub fn assert_trade_state_not_locked(data: &[u8]) -> Result<()> {
+    match get_trade_state_data(data) {
+        Some(trade_state_data) if trade_state_data.flags & constant::TRADE_STATE_FLAG_LOCKED != 0 => {
+            Err(AuctionHouseError::TradeStateLocked.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Expiry and rent payer of an expiring trade state, `None` for orders without expiry.
+pub fn get_trade_state_expiry(data: &[u8]) -> Option<(i64, Pubkey)> {
+    get_trade_state_data(data)
+        .filter(|trade_state_data| trade_state_data.expiry != 0)
+        .map(|trade_state_data| (trade_state_data.expiry, trade_state_data.fee_payer))
+}
+
+pub fn assert_valid_expiry(expiry: Option<i64>) -> Result<()> {
+    if let Some(expiry) = expiry {
+        if expiry <= Clock::get()?.unix_timestamp {
+            return Err(AuctionHouseError::InvalidExpiry.into());
+        }
+    }
+    Ok(())
+}
+
+pub fn assert_trade_state_not_expired(data: &[u8]) -> Result<()> {
+    if let Some((expiry, _)) = get_trade_state_expiry(data) {
+        if expiry <= Clock::get()?.unix_timestamp {
+            return Err(AuctionHouseError::OrderExpired.into());
+        }
+    }
+    Ok(())
+}
+
 pub fn rent_checked_sub(escrow_account: AccountInfo, diff: u64) -> Result<u64> {
     let rent_minimum = (Rent::get()?).minimum_balance((escrow_account.data_len()));
     let account_lamports = escrow_account
@@ -358,6 +504,225 @@ pub fn rent_checked_sub(escrow_account: AccountInfo, diff: u64) -> Result<u64> {
     }
 }
 
+/// Amount a bid holds in escrow: its price plus the larger of the maker and taker buyer fees, since the bid may end up
+/// on either side of the trade.
+pub fn bid_escrow_amount(auction_house: &AuctionHouse, buyer_price: u64) -> Result<u64> {
+    buyer_price
+        .checked_add(std::cmp::max(
+            compute_sale_fees(auction_house, buyer_price, true, 0, 0)?.buyer_fee,
+            compute_sale_fees(auction_house, buyer_price, false, 0, 0)?.buyer_fee,
+        ))
+        .ok_or_else(|| AuctionHouseError::NumericalOverflow.into())
+}
+
+/// Move `amount` of the treasury mint out of a program escrow. A native escrow signs for itself with
+/// `escrow_signer_seeds`, whereas the auction house owns a token escrow.
+pub fn transfer_from_escrow<'a>(
+    escrow: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    auction_house: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    escrow_signer_seeds: &[&[u8]],
+    auction_house_seeds: &[&[u8]],
+    amount: u64,
+    is_native: bool,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    if is_native {
+        invoke_signed(
+            &system_instruction::transfer(escrow.key, destination.key, amount),
+            &[escrow.clone(), destination.clone(), system_program.clone()],
+            &[escrow_signer_seeds],
+        )?;
+    } else {
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                escrow.key,
+                destination.key,
+                auction_house.key,
+                &[],
+                amount,
+            )?,
+            &[
+                escrow.clone(),
+                destination.clone(),
+                token_program.clone(),
+                auction_house.clone(),
+            ],
+            &[auction_house_seeds],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Open an auction escrow paid for by `payer`: a rent-exempt system account for native treasury mints, or a token
+/// account owned by the auction house.
+pub fn create_auction_escrow<'a>(
+    auction_escrow: &UncheckedAccount<'a>,
+    payer: &AccountInfo<'a>,
+    treasury_mint: &anchor_lang::prelude::Account<'a, Mint>,
+    auction_house: &AccountInfo<'a>,
+    token_program: &Program<'a, Token>,
+    system_program: &Program<'a, System>,
+    rent: &Sysvar<'a, Rent>,
+    auction_escrow_seeds: &[&[u8]],
+    is_native: bool,
+) -> Result<()> {
+    if is_native {
+        let required_lamports = rent
+            .minimum_balance(0)
+            .saturating_sub(auction_escrow.lamports());
+
+        if required_lamports > 0 {
+            invoke(
+                &system_instruction::transfer(payer.key, auction_escrow.key, required_lamports),
+                &[
+                    payer.clone(),
+                    auction_escrow.to_account_info(),
+                    system_program.to_account_info(),
+                ],
+            )?;
+        }
+
+        Ok(())
+    } else {
+        create_program_token_account_if_not_present(
+            auction_escrow,
+            system_program,
+            payer,
+            token_program,
+            treasury_mint,
+            auction_house,
+            rent,
+            auction_escrow_seeds,
+            &[],
+            is_native,
+        )
+    }
+}
+
+/// Close an emptied auction escrow, returning its rent to `destination`.
+pub fn close_auction_escrow<'a>(
+    auction_escrow: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    auction_house: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    auction_escrow_seeds: &[&[u8]],
+    auction_house_seeds: &[&[u8]],
+    is_native: bool,
+) -> Result<()> {
+    if is_native {
+        transfer_from_escrow(
+            auction_escrow,
+            destination,
+            auction_house,
+            token_program,
+            system_program,
+            auction_escrow_seeds,
+            auction_house_seeds,
+            auction_escrow.lamports(),
+            is_native,
+        )
+    } else {
+        invoke_signed(
+            &spl_token::instruction::close_account(
+                token_program.key,
+                auction_escrow.key,
+                destination.key,
+                auction_house.key,
+                &[],
+            )?,
+            &[
+                auction_escrow.clone(),
+                destination.clone(),
+                token_program.clone(),
+                auction_house.clone(),
+            ],
+            &[auction_house_seeds],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Refund the locked funds of a bid to its escrow payment account and empty its trade state, checking
+/// `trade_state_fee_payer` paid for it. The caller collects the trade state rent into it after its last CPI.
+pub fn release_locked_bid<'info>(
+    bid: &HighestBid,
+    locked_amount: u64,
+    auction_house_key: &Pubkey,
+    auction_house: &AccountInfo<'info>,
+    auction_escrow: &AccountInfo<'info>,
+    bidder_escrow_payment_account: &AccountInfo<'info>,
+    bidder_trade_state: &AccountInfo<'info>,
+    trade_state_fee_payer: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    auction_escrow_seeds: &[&[u8]],
+    auction_house_seeds: &[&[u8]],
+    is_native: bool,
+) -> Result<()> {
+    assert_derivation(
+        &crate::id(),
+        bidder_escrow_payment_account,
+        &[
+            PREFIX.as_bytes(),
+            auction_house_key.as_ref(),
+            bid.buyer.as_ref(),
+        ],
+    )?;
+    assert_keys_equal(bidder_trade_state.key(), bid.buyer_trade_state)?;
+
+    let trade_state_data = get_trade_state_data(&bidder_trade_state.try_borrow_data()?)
+        .ok_or(AuctionHouseError::BothPartiesNeedToAgreeToSale)?;
+    assert_keys_equal(trade_state_fee_payer.key(), trade_state_data.fee_payer)?;
+
+    transfer_from_escrow(
+        auction_escrow,
+        bidder_escrow_payment_account,
+        auction_house,
+        token_program,
+        system_program,
+        auction_escrow_seeds,
+        auction_house_seeds,
+        locked_amount,
+        is_native,
+    )?;
+
+    let bidder_trade_state_len = bidder_trade_state.data_len();
+    sol_memset(
+        *bidder_trade_state.try_borrow_mut_data()?,
+        0,
+        bidder_trade_state_len,
+    );
+
+    Ok(())
+}
+
+/// Whether a seller's token account can still deliver an auction listing of `token_size` through the program signer.
+pub fn is_listing_fillable(
+    token_account: &anchor_lang::prelude::Account<TokenAccount>,
+    token_size: u64,
+    program_id: &Pubkey,
+) -> Result<bool> {
+    let (program_as_signer, _) = Pubkey::find_program_address(
+        &[PREFIX.as_bytes(), constant::SIGNER.as_bytes()],
+        program_id,
+    );
+
+    Ok(token_account.amount >= token_size
+        && token_account.delegated_amount >= token_size
+        && get_delegate_from_token_account(&token_account.to_account_info())?
+            == Some(program_as_signer))
+}
+
 pub fn pay_creator_fees<'a>(
     remaining_accounts: &mut Iter<AccountInfo<'a>>,
     metadata_info: &AccountInfo<'a>,
@@ -473,6 +838,90 @@ pub fn pay_creator_fees<'a>(
         .ok_or(AuctionHouseError::NumericalOverflow)?)
 }
 
+/// Referrer accounts passed to execute_sale_logic to share the house fee of a sale.
+pub struct ReferralPayout<'info> {
+    pub referrer: Account<'info, Referrer>,
+    pub wallet: AccountInfo<'info>,
+    pub payment_account: AccountInfo<'info>,
+}
+
+/// Pays `amount` from the escrow to the referrer, in the treasury mint. SPL payouts go to the referrer's ATA,
+/// created with the fee payer like creator payouts.
+pub fn pay_referral_fee<'a>(
+    referral: &ReferralPayout<'a>,
+    escrow_payment_account: &AccountInfo<'a>,
+    payment_account_owner: &AccountInfo<'a>,
+    fee_payer: &AccountInfo<'a>,
+    treasury_mint: &AccountInfo<'a>,
+    ata_program: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &AccountInfo<'a>,
+    signer_seeds: &[&[u8]],
+    fee_payer_seeds: &[&[u8]],
+    amount: u64,
+    is_native: bool,
+) -> Result<()> {
+    let wallet = &referral.wallet;
+    let payment_account = &referral.payment_account;
+
+    assert_keys_equal(referral.referrer.wallet, *wallet.key)?;
+
+    if !is_native {
+        if payment_account.data_is_empty() {
+            make_ata(
+                payment_account.to_account_info(),
+                wallet.to_account_info(),
+                treasury_mint.to_account_info(),
+                fee_payer.to_account_info(),
+                ata_program.to_account_info(),
+                token_program.to_account_info(),
+                system_program.to_account_info(),
+                rent.to_account_info(),
+                fee_payer_seeds,
+            )?;
+        }
+
+        assert_is_ata(payment_account, wallet.key, &treasury_mint.key())?;
+
+        if amount > 0 {
+            invoke_signed(
+                &spl_token::instruction::transfer(
+                    token_program.key,
+                    escrow_payment_account.key,
+                    payment_account.key,
+                    payment_account_owner.key,
+                    &[],
+                    amount,
+                )?,
+                &[
+                    escrow_payment_account.clone(),
+                    payment_account.clone(),
+                    token_program.clone(),
+                    payment_account_owner.clone(),
+                ],
+                &[signer_seeds],
+            )?;
+        }
+    } else {
+        assert_keys_equal(*payment_account.key, *wallet.key)?;
+
+        if amount > 0 {
+            invoke_signed(
+                &system_instruction::transfer(escrow_payment_account.key, wallet.key, amount),
+                &[
+                    escrow_payment_account.clone(),
+                    wallet.clone(),
+                    system_program.clone(),
+                ],
+                &[signer_seeds],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
 pub fn pay_auction_house_fees<'a>(
     auction_house: &anchor_lang::prelude::Account<'a, AuctionHouse>,
     auction_house_treasury: &AccountInfo<'a>,
@@ -483,12 +932,240 @@ pub fn pay_auction_house_fees<'a>(
     size: u64,
     is_native: bool,
 ) -> Result<u64> {
-    let fees = auction_house.seller_fee_basis_points;
-    let total_fee = (fees as u128)
-        .checked_mul(size as u128)
+    let total_fee = basis_points_of(auction_house.seller_fee_basis_points, size)?;
+
+    pay_auction_house_fee_amount(
+        auction_house,
+        auction_house_treasury,
+        escrow_payment_account,
+        token_program,
+        system_program,
+        signer_seeds,
+        total_fee,
+        is_native,
+    )?;
+
+    Ok(total_fee)
+}
+
+/// Fee split of a single sale when the Auction House charges separate maker and taker rates.
+pub struct SaleFees {
+    /// Charged to the buyer on top of the sale price, taken from escrow.
+    pub buyer_fee: u64,
+    /// Deducted from the seller's proceeds.
+    pub seller_fee: u64,
+    /// Transferred from escrow to the Auction House treasury.
+    pub house_fee: u64,
+    /// Maker rebate added to the seller's proceeds. A buyer's rebate simply stays in escrow.
+    pub seller_rebate: u64,
+}
+
+fn basis_points_of(basis_points: u16, amount: u64) -> Result<u64> {
+    Ok((basis_points as u128)
+        .checked_mul(amount as u128)
         .ok_or(AuctionHouseError::NumericalOverflow)?
         .checked_div(10000)
-        .ok_or(AuctionHouseError::NumericalOverflow)? as u64;
+        .ok_or(AuctionHouseError::NumericalOverflow)? as u64)
+}
+
+/// Computes the fees of a sale at `price`. When neither maker nor taker rates are set the legacy
+/// `seller_fee_basis_points` is deducted from the seller, otherwise the taker pays the taker fee and the
+/// maker pays the maker fee, or receives it as a rebate out of the taker fee when negative. Each side's fee is
+/// then reduced by its fee tier discount, the discounts together never exceeding what the house keeps.
+pub fn compute_sale_fees(
+    auction_house: &AuctionHouse,
+    price: u64,
+    seller_is_maker: bool,
+    buyer_discount_basis_points: u16,
+    seller_discount_basis_points: u16,
+) -> Result<SaleFees> {
+    let fees = compute_undiscounted_sale_fees(auction_house, price, seller_is_maker)?;
+
+    let buyer_discount =
+        basis_points_of(buyer_discount_basis_points, fees.buyer_fee)?.min(fees.house_fee);
+    let seller_discount = basis_points_of(seller_discount_basis_points, fees.seller_fee)?
+        .min(fees.house_fee - buyer_discount);
+
+    Ok(SaleFees {
+        buyer_fee: fees.buyer_fee - buyer_discount,
+        seller_fee: fees.seller_fee - seller_discount,
+        house_fee: fees.house_fee - buyer_discount - seller_discount,
+        seller_rebate: fees.seller_rebate,
+    })
+}
+
+fn compute_undiscounted_sale_fees(
+    auction_house: &AuctionHouse,
+    price: u64,
+    seller_is_maker: bool,
+) -> Result<SaleFees> {
+    let maker_bps = auction_house.maker_fee_basis_points;
+    let taker_bps = auction_house.taker_fee_basis_points;
+
+    if maker_bps == 0 && taker_bps == 0 {
+        let fee = basis_points_of(auction_house.seller_fee_basis_points, price)?;
+        return Ok(SaleFees {
+            buyer_fee: 0,
+            seller_fee: fee,
+            house_fee: fee,
+            seller_rebate: 0,
+        });
+    }
+
+    let taker_fee = basis_points_of(taker_bps, price)?;
+    let maker_amount = basis_points_of(maker_bps.unsigned_abs(), price)?;
+    let (maker_fee, rebate) = if maker_bps >= 0 {
+        (maker_amount, 0)
+    } else {
+        (0, maker_amount)
+    };
+
+    let house_fee = taker_fee
+        .checked_add(maker_fee)
+        .ok_or(AuctionHouseError::NumericalOverflow)?
+        .checked_sub(rebate)
+        .ok_or(AuctionHouseError::NumericalOverflow)?;
+
+    let (buyer_fee, seller_fee, seller_rebate) = if seller_is_maker {
+        (taker_fee, maker_fee, rebate)
+    } else {
+        (maker_fee, taker_fee, 0)
+    };
+
+    Ok(SaleFees {
+        buyer_fee,
+        seller_fee,
+        house_fee,
+        seller_rebate,
+    })
+}
+
+/// Number of creator accounts pay_creator_fees expects for `metadata_info`, two per creator for SPL payouts.
+pub fn get_creator_accounts_len(metadata_info: &AccountInfo, is_native: bool) -> Result<usize> {
+    Ok(match Metadata::from_account_info(metadata_info)?.data.creators {
+        Some(creators) if is_native => creators.len(),
+        Some(creators) => creators.len() * 2,
+        None => 0,
+    })
+}
+
+/// Loads the buyer and seller wallet stats accounts, passed in that order at their derived addresses after the
+/// `creator_accounts` of a sale. A wallet that does not track its volume yet has nothing at its address, and sales
+/// passing no accounts after the creators, like those of clients predating wallet stats, track neither side.
+pub fn get_wallet_stats<'a>(
+    remaining_accounts: &[AccountInfo<'a>],
+    creator_accounts: usize,
+    auction_house: &Pubkey,
+    buyer: &Pubkey,
+    seller: &Pubkey,
+) -> Result<(Option<Account<'a, WalletStats>>, Option<Account<'a, WalletStats>>)> {
+    if remaining_accounts.len() <= creator_accounts {
+        return Ok((None, None));
+    }
+
+    let stats_accounts = remaining_accounts
+        .get(creator_accounts..creator_accounts + constant::WALLET_STATS_GROUP_SIZE)
+        .ok_or(AuctionHouseError::InvalidWalletStats)?;
+
+    let load = |account: &AccountInfo<'a>, wallet: &Pubkey| -> Result<Option<Account<'a, WalletStats>>> {
+        assert_derivation(
+            &crate::id(),
+            account,
+            &[
+                constant::WALLET_STATS.as_bytes(),
+                auction_house.as_ref(),
+                wallet.as_ref(),
+            ],
+        )
+        .map_err(|_| AuctionHouseError::InvalidWalletStats)?;
+
+        if account.data_is_empty() {
+            return Ok(None);
+        }
+
+        Account::try_from(account)
+            .map(Some)
+            .map_err(|_| AuctionHouseError::InvalidWalletStats.into())
+    };
+
+    Ok((load(&stats_accounts[0], buyer)?, load(&stats_accounts[1], seller)?))
+}
+
+/// Loads a referrer approved by `auction_house`, passed at its derived address.
+pub fn get_approved_referrer<'a>(
+    account: &AccountInfo<'a>,
+    auction_house: &Pubkey,
+) -> Result<Account<'a, Referrer>> {
+    let referrer: Account<'a, Referrer> =
+        Account::try_from(account).map_err(|_| AuctionHouseError::InvalidReferrer)?;
+
+    if referrer.auction_house != *auction_house {
+        return Err(AuctionHouseError::InvalidReferrer.into());
+    }
+
+    assert_derivation(
+        &crate::id(),
+        account,
+        &[
+            constant::REFERRER.as_bytes(),
+            auction_house.as_ref(),
+            referrer.wallet.as_ref(),
+        ],
+    )
+    .map_err(|_| AuctionHouseError::InvalidReferrer)?;
+
+    Ok(referrer)
+}
+
+/// Loads the referral accounts of a sale whose bid records `referrer`, passed after its wallet stats addresses, which
+/// such a sale cannot leave out. A referrer removed since the bid was placed has nothing at its address and is not paid.
+pub fn get_referral_payout<'a>(
+    remaining_accounts: &[AccountInfo<'a>],
+    creator_accounts: usize,
+    auction_house: &Pubkey,
+    referrer: &Pubkey,
+) -> Result<Option<ReferralPayout<'a>>> {
+    let start = creator_accounts + constant::WALLET_STATS_GROUP_SIZE;
+    let referral_accounts = remaining_accounts
+        .get(start..start + constant::REFERRAL_GROUP_SIZE)
+        .ok_or(AuctionHouseError::ReferrerMismatch)?;
+
+    assert_derivation(
+        &crate::id(),
+        &referral_accounts[0],
+        &[
+            constant::REFERRER.as_bytes(),
+            auction_house.as_ref(),
+            referrer.as_ref(),
+        ],
+    )
+    .map_err(|_| AuctionHouseError::ReferrerMismatch)?;
+
+    if referral_accounts[0].data_is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(ReferralPayout {
+        referrer: Account::try_from(&referral_accounts[0])?,
+        wallet: referral_accounts[1].clone(),
+        payment_account: referral_accounts[2].clone(),
+    }))
+}
+
+/// Transfers `amount` from the escrow payment account to the Auction House treasury.
+pub fn pay_auction_house_fee_amount<'a>(
+    auction_house: &anchor_lang::prelude::Account<'a, AuctionHouse>,
+    auction_house_treasury: &AccountInfo<'a>,
+    escrow_payment_account: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    signer_seeds: &[&[u8]],
+    amount: u64,
+    is_native: bool,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
 
     if !is_native {
         invoke_signed(
@@ -498,7 +1175,7 @@ pub fn pay_auction_house_fees<'a>(
                 auction_house_treasury.key,
                 &auction_house.key(),
                 &[],
-                total_fee,
+                amount,
             )?,
             &[
                 escrow_payment_account.clone(),
@@ -513,7 +1190,7 @@ pub fn pay_auction_house_fees<'a>(
             &system_instruction::transfer(
                 escrow_payment_account.key,
                 auction_house_treasury.key,
-                total_fee,
+                amount,
             ),
             &[
                 escrow_payment_account.clone(),
@@ -524,5 +1201,158 @@ pub fn pay_auction_house_fees<'a>(
         )?;
     }
 
-    Ok(total_fee)
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constant::MAX_FEE_TIERS, state::FeeTier};
+
+    fn leaf(data: &[u8]) -> [u8; 32] {
+        keccak::hashv(&[&[0x00], data]).to_bytes()
+    }
+
+    fn node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        let (min, max) = if a <= b { (a, b) } else { (b, a) };
+        keccak::hashv(&[&[0x01], min, max]).to_bytes()
+    }
+
+    fn auction_house(maker_fee_basis_points: i16, taker_fee_basis_points: u16) -> AuctionHouse {
+        AuctionHouse {
+            auction_house_fee_account: Pubkey::default(),
+            auction_house_treasury: Pubkey::default(),
+            treasury_withdrawal_destination: Pubkey::default(),
+            fee_withdrawal_destination: Pubkey::default(),
+            treasury_mint: Pubkey::default(),
+            authority: Pubkey::default(),
+            creator: Pubkey::default(),
+            bump: 0,
+            treasury_bump: 0,
+            fee_payer_bump: 0,
+            seller_fee_basis_points: 0,
+            requires_sign_off: false,
+            can_change_sale_price: false,
+            escrow_payment_bump: 0,
+            has_auctioneer: false,
+            auctioneer_pda_bump: 0,
+            maker_fee_basis_points,
+            taker_fee_basis_points,
+            fee_tiers: [FeeTier::default(); MAX_FEE_TIERS],
+            has_revenue_split: false,
+            open_auction_escrows: 0,
+            approved_referrers: 0,
+        }
+    }
+
+    #[test]
+    fn maker_rebate_equal_to_taker_fee_leaves_no_house_fee() {
+        let auction_house = auction_house(-100, 100);
+
+        let fees = compute_sale_fees(&auction_house, 10_000, true, 5_000, 5_000).unwrap();
+
+        assert_eq!(fees.buyer_fee, 100);
+        assert_eq!(fees.seller_fee, 0);
+        assert_eq!(fees.house_fee, 0);
+        assert_eq!(fees.seller_rebate, 100);
+    }
+
+    #[test]
+    fn legacy_seller_fee_applies_without_maker_and_taker_rates() {
+        let mut auction_house = auction_house(0, 0);
+        auction_house.seller_fee_basis_points = 250;
+
+        let fees = compute_sale_fees(&auction_house, 10_000, true, 0, 0).unwrap();
+
+        assert_eq!(fees.buyer_fee, 0);
+        assert_eq!(fees.seller_fee, 250);
+        assert_eq!(fees.house_fee, 250);
+        assert_eq!(fees.seller_rebate, 0);
+    }
+
+    #[test]
+    fn taker_and_maker_fees_follow_the_resting_order() {
+        let auction_house = auction_house(50, 200);
+
+        let seller_maker = compute_sale_fees(&auction_house, 10_000, true, 0, 0).unwrap();
+        assert_eq!(seller_maker.buyer_fee, 200);
+        assert_eq!(seller_maker.seller_fee, 50);
+        assert_eq!(seller_maker.house_fee, 250);
+
+        let buyer_maker = compute_sale_fees(&auction_house, 10_000, false, 0, 0).unwrap();
+        assert_eq!(buyer_maker.buyer_fee, 50);
+        assert_eq!(buyer_maker.seller_fee, 200);
+        assert_eq!(buyer_maker.house_fee, 250);
+    }
+
+    #[test]
+    fn maker_rebate_comes_out_of_the_taker_fee() {
+        let auction_house = auction_house(-50, 200);
+
+        let seller_maker = compute_sale_fees(&auction_house, 10_000, true, 0, 0).unwrap();
+        assert_eq!(seller_maker.buyer_fee, 200);
+        assert_eq!(seller_maker.seller_fee, 0);
+        assert_eq!(seller_maker.house_fee, 150);
+        assert_eq!(seller_maker.seller_rebate, 50);
+
+        // NOTE: a buyer's rebate stays in its escrow, so only the house fee shrinks
+        let buyer_maker = compute_sale_fees(&auction_house, 10_000, false, 0, 0).unwrap();
+        assert_eq!(buyer_maker.buyer_fee, 0);
+        assert_eq!(buyer_maker.seller_fee, 200);
+        assert_eq!(buyer_maker.house_fee, 150);
+        assert_eq!(buyer_maker.seller_rebate, 0);
+    }
+
+    #[test]
+    fn discounts_reduce_each_side_and_the_house_fee() {
+        let auction_house = auction_house(100, 200);
+
+        let fees = compute_sale_fees(&auction_house, 10_000, true, 5_000, 2_000).unwrap();
+
+        assert_eq!(fees.buyer_fee, 100);
+        assert_eq!(fees.seller_fee, 80);
+        assert_eq!(fees.house_fee, 180);
+    }
+
+    #[test]
+    fn discounts_never_exceed_the_house_fee() {
+        let auction_house = auction_house(-150, 200);
+
+        let fees = compute_sale_fees(&auction_house, 10_000, true, 10_000, 10_000).unwrap();
+
+        assert_eq!(fees.buyer_fee, 150);
+        assert_eq!(fees.seller_fee, 0);
+        assert_eq!(fees.house_fee, 0);
+        assert_eq!(fees.seller_rebate, 150);
+    }
+
+    #[test]
+    fn bid_escrow_covers_the_larger_buyer_fee() {
+        let auction_house = auction_house(50, 200);
+
+        assert_eq!(bid_escrow_amount(&auction_house, 10_000).unwrap(), 10_200);
+    }
+
+    #[test]
+    fn merkle_proof_of_a_listed_leaf_verifies() {
+        let leaves = [leaf(b"a"), leaf(b"b"), leaf(b"c"), leaf(b"d")];
+        let left = node(&leaves[0], &leaves[1]);
+        let right = node(&leaves[2], &leaves[3]);
+        let root = node(&left, &right);
+
+        assert!(verify_merkle_proof(&[leaves[1], right], &root, b"a"));
+        assert!(verify_merkle_proof(&[leaves[2], left], &root, b"d"));
+    }
+
+    #[test]
+    fn merkle_proof_rejects_other_leaves_and_inner_nodes() {
+        let leaves = [leaf(b"a"), leaf(b"b"), leaf(b"c"), leaf(b"d")];
+        let left = node(&leaves[0], &leaves[1]);
+        let right = node(&leaves[2], &leaves[3]);
+        let root = node(&left, &right);
+
+        assert!(!verify_merkle_proof(&[leaves[1], right], &root, b"e"));
+        assert!(!verify_merkle_proof(&[leaves[0], right], &root, b"a"));
+        assert!(!verify_merkle_proof(&[right], &root, &left));
+    }
 }
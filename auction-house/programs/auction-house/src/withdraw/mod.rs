@@ -1 +1,499 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke_signed, system_instruction},
+    AnchorDeserialize,
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token},
+};
 
+use crate::constant::*;
+use crate::errors::AuctionHouseError;
+use crate::state::{AuctionHouse, Auctioneer, AuthorityScope};
+use crate::utils::{
+    assert_initialized, assert_is_ata, assert_keys_equal, assert_valid_auctioneer_and_scope,
+    get_fee_payer, make_ata, rent_checked_sub,
+};
+
+/// Accounts for the [`withdraw` handler](auction_house/fn.withdraw.html).
+#[derive(Accounts)]
+#[instruction(escrow_payment_bump: u8)]
+pub struct Withdraw<'info> {
+    /// CHECK: Validated in withdraw_logic.
+    /// User wallet account.
+    pub wallet: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in withdraw_logic.
+    /// SPL token account or native SOL account to transfer funds to. If the account is a native SOL account, this is the same as the wallet address.
+    #[account(mut)]
+    pub receipt_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer escrow payment account PDA.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// Auction House instance treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Validated in withdraw_logic.
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub ata_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn withdraw<'info>(
+    ctx: Context<'_, '_, '_, 'info, Withdraw<'info>>,
+    escrow_payment_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let auction_house = &ctx.accounts.auction_house;
+
+    // NOTE: If it has an auctioneer authority delegated must use auctioneer_* handler.
+    if auction_house.has_auctioneer {
+        return Err(AuctionHouseError::MustUseAuctioneerHandler.into());
+    }
+
+    withdraw_logic(ctx.accounts, escrow_payment_bump, amount)
+}
+
+/// Accounts for the [`auctioneer_withdraw` handler](auction_house/fn.auctioneer_withdraw.html).
+#[derive(Accounts)]
+#[instruction(escrow_payment_bump: u8)]
+pub struct AuctioneerWithdraw<'info> {
+    /// CHECK: Validated in withdraw_logic.
+    /// User wallet account.
+    pub wallet: UncheckedAccount<'info>,
+
+    /// CHECK: Validated in withdraw_logic.
+    /// SPL token account or native SOL account to transfer funds to. If the account is a native SOL account, this is the same as the wallet address.
+    #[account(mut)]
+    pub receipt_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer escrow payment account PDA.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// Auction House instance treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Validated in withdraw_logic.
+    /// Auction House instance authority account.
+    pub authority: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub ata_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// The auctioneer authority - typically a PDA of the Auctioneer program running this action.
+    pub auctioneer_authority: Signer<'info>,
+
+    /// The auctioneer PDA owned by Auction House storing scopes.
+    #[account(
+        seeds = [
+            AUCTIONEER.as_bytes(),
+            auction_house.key().as_ref(),
+            auctioneer_authority.key().as_ref()
+        ],
+        bump = auction_house.auctioneer_pda_bump
+    )]
+    pub ah_auctioneer_pda: Box<Account<'info, Auctioneer>>,
+}
+
+pub fn auctioneer_withdraw<'info>(
+    ctx: Context<'_, '_, '_, 'info, AuctioneerWithdraw<'info>>,
+    escrow_payment_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    assert_valid_auctioneer_and_scope(
+        &accounts.auction_house,
+        &accounts.auctioneer_authority.key(),
+        &accounts.ah_auctioneer_pda,
+        AuthorityScope::Withdraw,
+    )?;
+
+    let mut withdraw_accounts = Withdraw {
+        wallet: accounts.wallet.clone(),
+        receipt_account: accounts.receipt_account.clone(),
+        escrow_payment_account: accounts.escrow_payment_account.clone(),
+        treasury_mint: accounts.treasury_mint.clone(),
+        authority: accounts.authority.clone(),
+        auction_house: accounts.auction_house.clone(),
+        auction_house_fee_account: accounts.auction_house_fee_account.clone(),
+        token_program: accounts.token_program.clone(),
+        system_program: accounts.system_program.clone(),
+        ata_program: accounts.ata_program.clone(),
+        rent: accounts.rent.clone(),
+    };
+
+    withdraw_logic(&mut withdraw_accounts, escrow_payment_bump, amount)
+}
+
+/// NOTE: Move `amount` of the treasury mint out of the wallet's escrow payment account back to the wallet (or its ATA), never dipping below rent exemption.
+pub fn withdraw_logic<'info>(
+    accounts: &mut Withdraw<'info>,
+    escrow_payment_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    // NOTE: extract all variables from ctx (Withdraw struct)
+    let wallet = &accounts.wallet;
+    let receipt_account = &accounts.receipt_account;
+    let escrow_payment_account = &accounts.escrow_payment_account;
+    let treasury_mint = &accounts.treasury_mint;
+    let authority = &accounts.authority;
+    let auction_house = &accounts.auction_house;
+    let auction_house_fee_account = &accounts.auction_house_fee_account;
+    let token_program = &accounts.token_program;
+    let system_program = &accounts.system_program;
+    let ata_program = &accounts.ata_program;
+    let rent = &accounts.rent;
+
+    // NOTE: either the owner of the escrow or the auction house must sign
+    if !wallet.to_account_info().is_signer && !authority.to_account_info().is_signer {
+        return Err(AuctionHouseError::NoValidSignerPresent.into());
+    }
+
+    let auction_house_key = auction_house.key();
+    let seeds = [
+        PREFIX.as_bytes(),
+        auction_house_key.as_ref(),
+        FEE_PAYER.as_bytes(),
+        &[auction_house.fee_payer_bump],
+    ];
+
+    let auction_house_seeds = [
+        PREFIX.as_bytes(),
+        auction_house.creator.as_ref(),
+        auction_house.treasury_mint.as_ref(),
+        &[auction_house.bump],
+    ];
+
+    let wallet_key = wallet.key();
+    let escrow_signer_seeds = [
+        PREFIX.as_bytes(),
+        auction_house_key.as_ref(),
+        wallet_key.as_ref(),
+        &[escrow_payment_bump],
+    ];
+
+    let (fee_payer, fee_seeds) = get_fee_payer(
+        authority,
+        auction_house,
+        wallet.to_account_info(),
+        auction_house_fee_account.to_account_info(),
+        &seeds,
+    )?;
+
+    let is_native = treasury_mint.key() == spl_token::native_mint::id();
+
+    if !is_native {
+        if receipt_account.data_is_empty() {
+            make_ata(
+                receipt_account.to_account_info(),
+                wallet.to_account_info(),
+                treasury_mint.to_account_info(),
+                fee_payer.to_account_info(),
+                ata_program.to_account_info(),
+                token_program.to_account_info(),
+                system_program.to_account_info(),
+                rent.to_account_info(),
+                fee_seeds,
+            )?;
+        }
+
+        let receipt_record_account = assert_is_ata(
+            &receipt_account.to_account_info(),
+            &wallet.key(),
+            &treasury_mint.key(),
+        )?;
+
+        // NOTE: a delegate on the receiving ATA could drain the withdrawn funds
+        if receipt_record_account.delegate.is_some() {
+            return Err(AuctionHouseError::BuyerATACannotHaveDelegate.into());
+        }
+
+        // NOTE: SPL escrows are owned by the auction house, so it signs the transfer
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                &escrow_payment_account.key(),
+                &receipt_account.key(),
+                &auction_house.key(),
+                &[],
+                amount,
+            )?,
+            &[
+                escrow_payment_account.to_account_info(),
+                receipt_account.to_account_info(),
+                token_program.to_account_info(),
+                auction_house.to_account_info(),
+            ],
+            &[&auction_house_seeds],
+        )?;
+    } else {
+        assert_keys_equal(receipt_account.key(), wallet.key())?;
+
+        // NOTE: cap the amount so the escrow account stays rent exempt
+        let checked_amount = rent_checked_sub(escrow_payment_account.to_account_info(), amount)?;
+
+        invoke_signed(
+            &system_instruction::transfer(
+                &escrow_payment_account.key(),
+                &receipt_account.key(),
+                checked_amount,
+            ),
+            &[
+                escrow_payment_account.to_account_info(),
+                receipt_account.to_account_info(),
+                system_program.to_account_info(),
+            ],
+            &[&escrow_signer_seeds],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Accounts for the [`withdraw_from_closed_auction_house` handler](auction_house/fn.withdraw_from_closed_auction_house.html).
+#[derive(Accounts)]
+#[instruction(escrow_payment_bump: u8)]
+pub struct WithdrawFromClosedAuctionHouse<'info> {
+    /// User wallet account, receiving the rent of an SPL escrow.
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    /// CHECK: Validated in withdraw_from_closed_auction_house.
+    /// SPL token account or native SOL account to transfer funds to. If the account is a native SOL account, this is the same as the wallet address.
+    #[account(mut)]
+    pub receipt_account: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Buyer escrow payment account PDA.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            wallet.key().as_ref()
+        ],
+        bump = escrow_payment_bump
+    )]
+    pub escrow_payment_account: UncheckedAccount<'info>,
+
+    /// Treasury mint account of the closed Auction House.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Only used to derive the closed Auction House.
+    /// Creator of the closed Auction House.
+    pub creator: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint, and checked to be closed in withdraw_from_closed_auction_house.
+    /// Closed Auction House instance PDA account.
+    #[account(
+        seeds = [
+            PREFIX.as_bytes(),
+            creator.key().as_ref(),
+            treasury_mint.key().as_ref()
+        ],
+        bump
+    )]
+    pub auction_house: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// NOTE: Empty the wallet's escrow payment account of a closed Auction House. The house PDA still signs for SPL
+/// escrows, which are then closed to the wallet.
+pub fn withdraw_from_closed_auction_house<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawFromClosedAuctionHouse<'info>>,
+    escrow_payment_bump: u8,
+) -> Result<()> {
+    let wallet = &ctx.accounts.wallet;
+    let receipt_account = &ctx.accounts.receipt_account;
+    let escrow_payment_account = &ctx.accounts.escrow_payment_account;
+    let treasury_mint = &ctx.accounts.treasury_mint;
+    let creator = &ctx.accounts.creator;
+    let auction_house = &ctx.accounts.auction_house;
+    let token_program = &ctx.accounts.token_program;
+    let system_program = &ctx.accounts.system_program;
+
+    if !auction_house.data_is_empty() {
+        return Err(AuctionHouseError::AuctionHouseNotClosed.into());
+    }
+
+    let is_native = treasury_mint.key() == spl_token::native_mint::id();
+
+    if !is_native {
+        let auction_house_bump = *ctx
+            .bumps
+            .get("auction_house")
+            .ok_or(AuctionHouseError::BumpSeedNotInHashMap)?;
+        let creator_key = creator.key();
+        let treasury_mint_key = treasury_mint.key();
+        let auction_house_seeds = [
+            PREFIX.as_bytes(),
+            creator_key.as_ref(),
+            treasury_mint_key.as_ref(),
+            &[auction_house_bump],
+        ];
+
+        let receipt_record_account = assert_is_ata(
+            &receipt_account.to_account_info(),
+            &wallet.key(),
+            &treasury_mint_key,
+        )?;
+
+        // NOTE: a delegate on the receiving ATA could drain the withdrawn funds
+        if receipt_record_account.delegate.is_some() {
+            return Err(AuctionHouseError::BuyerATACannotHaveDelegate.into());
+        }
+
+        let escrow: spl_token::state::Account =
+            assert_initialized(&escrow_payment_account.to_account_info())?;
+
+        if escrow.amount > 0 {
+            invoke_signed(
+                &spl_token::instruction::transfer(
+                    token_program.key,
+                    &escrow_payment_account.key(),
+                    &receipt_account.key(),
+                    &auction_house.key(),
+                    &[],
+                    escrow.amount,
+                )?,
+                &[
+                    escrow_payment_account.to_account_info(),
+                    receipt_account.to_account_info(),
+                    token_program.to_account_info(),
+                    auction_house.to_account_info(),
+                ],
+                &[&auction_house_seeds],
+            )?;
+        }
+
+        invoke_signed(
+            &spl_token::instruction::close_account(
+                token_program.key,
+                &escrow_payment_account.key(),
+                &wallet.key(),
+                &auction_house.key(),
+                &[],
+            )?,
+            &[
+                escrow_payment_account.to_account_info(),
+                wallet.to_account_info(),
+                token_program.to_account_info(),
+                auction_house.to_account_info(),
+            ],
+            &[&auction_house_seeds],
+        )?;
+    } else {
+        assert_keys_equal(receipt_account.key(), wallet.key())?;
+
+        let auction_house_key = auction_house.key();
+        let wallet_key = wallet.key();
+        let escrow_signer_seeds = [
+            PREFIX.as_bytes(),
+            auction_house_key.as_ref(),
+            wallet_key.as_ref(),
+            &[escrow_payment_bump],
+        ];
+
+        // NOTE: nothing can fund the escrow again, so it is emptied entirely, rent included
+        invoke_signed(
+            &system_instruction::transfer(
+                &escrow_payment_account.key(),
+                &receipt_account.key(),
+                escrow_payment_account.lamports(),
+            ),
+            &[
+                escrow_payment_account.to_account_info(),
+                receipt_account.to_account_info(),
+                system_program.to_account_info(),
+            ],
+            &[&escrow_signer_seeds],
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke_signed, system_instruction},
+    AnchorDeserialize,
+};
+
+use crate::constant::*;
+use crate::state::AuctionHouse;
+
+/// Accounts for the [`withdraw_from_fee` handler](auction_house/fn.withdraw_from_fee.html).
+#[derive(Accounts)]
+pub struct WithdrawFromFee<'info> {
+    /// Authority key for the Auction House.
+    pub authority: Signer<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the auction house.
+    /// Account that pays for fees if the marketplace executes sales.
+    #[account(mut)]
+    pub fee_withdrawal_destination: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance fee account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            FEE_PAYER.as_bytes()
+        ],
+        bump = auction_house.fee_payer_bump
+    )]
+    pub auction_house_fee_account: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = fee_withdrawal_destination,
+        has_one = auction_house_fee_account
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// NOTE: Transfer `amount` lamports from the auction house fee account to the configured fee withdrawal destination.
+pub fn withdraw_from_fee<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawFromFee<'info>>,
+    amount: u64,
+) -> Result<()> {
+    // NOTE: extract all variables from ctx (WithdrawFromFee struct)
+    let auction_house_fee_account = &ctx.accounts.auction_house_fee_account;
+    let fee_withdrawal_destination = &ctx.accounts.fee_withdrawal_destination;
+    let auction_house = &ctx.accounts.auction_house;
+    let system_program = &ctx.accounts.system_program;
+
+    let auction_house_key = auction_house.key();
+    let seeds = [
+        PREFIX.as_bytes(),
+        auction_house_key.as_ref(),
+        FEE_PAYER.as_bytes(),
+        &[auction_house.fee_payer_bump],
+    ];
+
+    invoke_signed(
+        &system_instruction::transfer(
+            &auction_house_fee_account.key(),
+            &fee_withdrawal_destination.key(),
+            amount,
+        ),
+        &[
+            auction_house_fee_account.to_account_info(),
+            fee_withdrawal_destination.to_account_info(),
+            system_program.to_account_info(),
+        ],
+        &[&seeds],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke_signed, system_instruction},
+    AnchorDeserialize,
+};
+use anchor_spl::token::{Mint, Token};
+
+use crate::constant::*;
+use crate::errors::AuctionHouseError;
+use crate::state::AuctionHouse;
+
+/// Accounts for the [`withdraw_from_treasury` handler](auction_house/fn.withdraw_from_treasury.html).
+#[derive(Accounts)]
+pub struct WithdrawFromTreasury<'info> {
+    /// Auction House instance treasury mint account.
+    pub treasury_mint: Box<Account<'info, Mint>>,
+
+    /// Authority key for the Auction House.
+    pub authority: Signer<'info>,
+
+    /// CHECK: Validated by the `has_one` constraint on the auction house.
+    /// SOL or SPL token account to receive Auction House fees. If treasury mint is native this will be the same as the `treasury_withdrawl_destination_owner`.
+    #[account(mut)]
+    pub treasury_withdrawal_destination: UncheckedAccount<'info>,
+
+    /// CHECK: Not dangerous. Account seeds checked in constraint.
+    /// Auction House instance treasury PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.key().as_ref(),
+            TREASURY.as_bytes()
+        ],
+        bump = auction_house.treasury_bump
+    )]
+    pub auction_house_treasury: UncheckedAccount<'info>,
+
+    /// Auction House instance PDA account.
+    #[account(
+        mut,
+        seeds = [
+            PREFIX.as_bytes(),
+            auction_house.creator.as_ref(),
+            auction_house.treasury_mint.as_ref()
+        ],
+        bump = auction_house.bump,
+        has_one = authority,
+        has_one = treasury_mint,
+        has_one = treasury_withdrawal_destination,
+        has_one = auction_house_treasury
+    )]
+    pub auction_house: Box<Account<'info, AuctionHouse>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// NOTE: Transfer `amount` of the treasury mint from the auction house treasury to the configured treasury withdrawal destination.
+pub fn withdraw_from_treasury<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawFromTreasury<'info>>,
+    amount: u64,
+) -> Result<()> {
+    // NOTE: extract all variables from ctx (WithdrawFromTreasury struct)
+    let treasury_mint = &ctx.accounts.treasury_mint;
+    let treasury_withdrawal_destination = &ctx.accounts.treasury_withdrawal_destination;
+    let auction_house_treasury = &ctx.accounts.auction_house_treasury;
+    let auction_house = &ctx.accounts.auction_house;
+    let token_program = &ctx.accounts.token_program;
+    let system_program = &ctx.accounts.system_program;
+
+    // NOTE: a revenue split binds every treasury withdrawal to its recipients
+    if auction_house.has_revenue_split {
+        return Err(AuctionHouseError::MustUseRevenueSplit.into());
+    }
+
+    let is_native = treasury_mint.key() == spl_token::native_mint::id();
+
+    let auction_house_key = auction_house.key();
+    let auction_house_seeds = [
+        PREFIX.as_bytes(),
+        auction_house.creator.as_ref(),
+        auction_house.treasury_mint.as_ref(),
+        &[auction_house.bump],
+    ];
+
+    let auction_house_treasury_seeds = [
+        PREFIX.as_bytes(),
+        auction_house_key.as_ref(),
+        TREASURY.as_bytes(),
+        &[auction_house.treasury_bump],
+    ];
+
+    // NOTE: the SPL treasury token account is owned by the auction house,
+    // whereas the native treasury is a system account signed for by its own seeds.
+    if !is_native {
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                &auction_house_treasury.key(),
+                &treasury_withdrawal_destination.key(),
+                &auction_house.key(),
+                &[],
+                amount,
+            )?,
+            &[
+                auction_house_treasury.to_account_info(),
+                treasury_withdrawal_destination.to_account_info(),
+                token_program.to_account_info(),
+                auction_house.to_account_info(),
+            ],
+            &[&auction_house_seeds],
+        )?;
+    } else {
+        invoke_signed(
+            &system_instruction::transfer(
+                &auction_house_treasury.key(),
+                &treasury_withdrawal_destination.key(),
+                amount,
+            ),
+            &[
+                auction_house_treasury.to_account_info(),
+                treasury_withdrawal_destination.to_account_info(),
+                system_program.to_account_info(),
+            ],
+            &[&auction_house_treasury_seeds],
+        )?;
+    }
+
+    Ok(())
+}
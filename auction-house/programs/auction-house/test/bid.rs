@@ -0,0 +1,59 @@
+#![cfg(feature = "test-bpf")]
+pub mod utils;
+
+use auction_house::errors::AuctionHouseError;
+use solana_program_test::*;
+use solana_sdk::{
+    clock::Clock,
+    signature::{Keypair, Signer},
+};
+use utils::{helpers::assert_custom_error, setup_functions::*};
+
+const PRICE: u64 = ONE_SOL;
+
+#[tokio::test]
+async fn rebid_with_new_expiry_fails() {
+    let mut context = auction_house_program_test().start_with_context().await;
+    let auction_house = existing_auction_house_test_context(&mut context, 0).await;
+
+    let seller = Keypair::new();
+    let buyer = Keypair::new();
+    airdrop(&mut context, &seller.pubkey(), ONE_SOL).await;
+    airdrop(&mut context, &buyer.pubkey(), 10 * ONE_SOL).await;
+    let nft = create_nft(&mut context, &seller).await;
+
+    let now = context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+    buy_with_expiry(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &nft,
+        PRICE,
+        Some(now + 60),
+    )
+    .await
+    .unwrap();
+
+    // NOTE: the bid would keep expiring at its original time
+    let err = buy_with_expiry(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &nft,
+        PRICE,
+        Some(now + 3_600),
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::TradeStateAlreadyExists);
+
+    // NOTE: topping up the same bid without an expiry still works
+    buy(&mut context, &auction_house, &buyer, &nft, PRICE)
+        .await
+        .unwrap();
+}
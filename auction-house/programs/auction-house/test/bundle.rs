@@ -0,0 +1,223 @@
+#![cfg(feature = "test-bpf")]
+pub mod utils;
+
+use auction_house::errors::AuctionHouseError;
+use solana_program_test::*;
+use solana_sdk::{
+    clock::Clock,
+    signature::{Keypair, Signer},
+    transport::TransportError,
+};
+use spl_associated_token_account::get_associated_token_address;
+use utils::{helpers::assert_custom_error, setup_functions::*};
+
+const ALLOCATIONS: [u64; 2] = [ONE_SOL / 4, ONE_SOL / 2];
+
+async fn setup() -> (
+    ProgramTestContext,
+    TestAuctionHouse,
+    Keypair,
+    Keypair,
+    TestNft,
+    TestNft,
+) {
+    let mut context = auction_house_program_test().start_with_context().await;
+    let auction_house = existing_auction_house_test_context(&mut context, 0).await;
+
+    let seller = Keypair::new();
+    let buyer = Keypair::new();
+    airdrop(&mut context, &seller.pubkey(), 10 * ONE_SOL).await;
+    airdrop(&mut context, &buyer.pubkey(), 10 * ONE_SOL).await;
+    let first = create_nft(&mut context, &seller).await;
+    let second = create_nft(&mut context, &seller).await;
+
+    (context, auction_house, seller, buyer, first, second)
+}
+
+#[tokio::test]
+async fn buy_bundle_success() {
+    let (mut context, auction_house, seller, buyer, first, second) = setup().await;
+
+    let bundle = list_bundle(
+        &mut context,
+        &auction_house,
+        &seller,
+        &[&first, &second],
+        0,
+        ALLOCATIONS.to_vec(),
+        None,
+    )
+    .await
+    .unwrap();
+    advance_clock(&mut context, 1, 0).await;
+
+    let seller_before = get_balance(&mut context, &seller.pubkey()).await;
+    buy_bundle(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &seller.pubkey(),
+        &[&first, &second],
+        0,
+        &ALLOCATIONS,
+    )
+    .await
+    .unwrap();
+
+    for nft in [&first, &second] {
+        assert_eq!(
+            get_token_account(
+                &mut context,
+                &get_associated_token_address(&buyer.pubkey(), &nft.mint)
+            )
+            .await
+            .amount,
+            1
+        );
+    }
+
+    // NOTE: the seller is paid every allocation and gets the rent of the bundle account back
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert_eq!(
+        get_balance(&mut context, &seller.pubkey()).await - seller_before,
+        ALLOCATIONS.iter().sum::<u64>()
+            + rent.minimum_balance(auction_house::constant::BUNDLE_SIZE)
+    );
+    assert!(context
+        .banks_client
+        .get_account(bundle)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn buy_bundle_item_alone_fails() {
+    let (mut context, auction_house, seller, buyer, first, second) = setup().await;
+
+    list_bundle(
+        &mut context,
+        &auction_house,
+        &seller,
+        &[&first, &second],
+        0,
+        ALLOCATIONS.to_vec(),
+        None,
+    )
+    .await
+    .unwrap();
+    advance_clock(&mut context, 1, 0).await;
+
+    let buyer_trade_state = buy(&mut context, &auction_house, &buyer, &first, ALLOCATIONS[0])
+        .await
+        .unwrap();
+    let listing = find_listing(&auction_house, &seller.pubkey(), &first, ALLOCATIONS[0]);
+
+    let err = execute_sale(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &seller.pubkey(),
+        &first,
+        &listing,
+        &buyer_trade_state,
+        ALLOCATIONS[0],
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::BundledListing);
+}
+
+#[tokio::test]
+async fn buy_expired_bundle_fails() {
+    let (mut context, auction_house, seller, buyer, first, second) = setup().await;
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+
+    list_bundle(
+        &mut context,
+        &auction_house,
+        &seller,
+        &[&first, &second],
+        0,
+        ALLOCATIONS.to_vec(),
+        Some(clock.unix_timestamp + 60),
+    )
+    .await
+    .unwrap();
+    advance_clock(&mut context, 1, 60).await;
+
+    let err = buy_bundle(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &seller.pubkey(),
+        &[&first, &second],
+        0,
+        &ALLOCATIONS,
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::OrderExpired);
+}
+
+#[tokio::test]
+async fn cancel_bundle_success() {
+    let (mut context, auction_house, seller, buyer, first, second) = setup().await;
+
+    let bundle = list_bundle(
+        &mut context,
+        &auction_house,
+        &seller,
+        &[&first, &second],
+        0,
+        ALLOCATIONS.to_vec(),
+        None,
+    )
+    .await
+    .unwrap();
+
+    cancel_bundle(
+        &mut context,
+        &auction_house,
+        &seller,
+        &[&first, &second],
+        0,
+        &ALLOCATIONS,
+    )
+    .await
+    .unwrap();
+
+    for (nft, allocation) in [&first, &second].into_iter().zip(ALLOCATIONS) {
+        let listing = find_listing(&auction_house, &seller.pubkey(), nft, allocation);
+        assert!(context
+            .banks_client
+            .get_account(listing.seller_trade_state)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(get_token_account(&mut context, &nft.token_account)
+            .await
+            .delegate
+            .is_none());
+    }
+    assert!(context
+        .banks_client
+        .get_account(bundle)
+        .await
+        .unwrap()
+        .is_none());
+
+    let err = buy_bundle(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &seller.pubkey(),
+        &[&first, &second],
+        0,
+        &ALLOCATIONS,
+    )
+    .await
+    .unwrap_err();
+    // NOTE: the bundle account is gone, so the buy fails its account constraints
+    assert!(matches!(err, TransportError::TransactionError(_)));
+}
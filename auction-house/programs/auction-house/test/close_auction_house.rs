@@ -0,0 +1,118 @@
+#![cfg(feature = "test-bpf")]
+pub mod utils;
+
+use auction_house::errors::AuctionHouseError;
+use solana_program_test::*;
+use solana_sdk::signature::{Keypair, Signer};
+use utils::{
+    helpers::assert_custom_error,
+    setup_functions::{self, ONE_SOL},
+};
+
+#[tokio::test]
+async fn close_with_approved_referrer_fails() {
+    let mut context = setup_functions::auction_house_program_test()
+        .start_with_context()
+        .await;
+    let auction_house = setup_functions::existing_auction_house_test_context(&mut context, 0).await;
+
+    let referrer =
+        setup_functions::add_referrer(&mut context, &auction_house, &Keypair::new().pubkey(), 5000)
+            .await
+            .unwrap();
+
+    let err = setup_functions::close_auction_house(&mut context, &auction_house)
+        .await
+        .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::CannotCloseWithApprovedReferrers);
+
+    setup_functions::remove_referrer(&mut context, &auction_house, &referrer)
+        .await
+        .unwrap();
+
+    // NOTE: a new blockhash keeps the retried close from being deduplicated with the failed one
+    context.last_blockhash = context
+        .banks_client
+        .get_new_latest_blockhash(&context.last_blockhash)
+        .await
+        .unwrap();
+    setup_functions::close_auction_house(&mut context, &auction_house)
+        .await
+        .unwrap();
+
+    assert!(context
+        .banks_client
+        .get_account(auction_house.key)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn close_trade_state_of_closed_auction_house_success() {
+    let mut context = setup_functions::auction_house_program_test()
+        .start_with_context()
+        .await;
+    let auction_house = setup_functions::existing_auction_house_test_context(&mut context, 0).await;
+
+    let seller = Keypair::new();
+    let buyer = Keypair::new();
+    setup_functions::airdrop(&mut context, &seller.pubkey(), ONE_SOL).await;
+    setup_functions::airdrop(&mut context, &buyer.pubkey(), ONE_SOL).await;
+
+    let nft = setup_functions::create_nft(&mut context, &seller).await;
+    let buyer_trade_state =
+        setup_functions::buy(&mut context, &auction_house, &buyer, &nft, ONE_SOL / 10)
+            .await
+            .unwrap();
+
+    // NOTE: the house is still open, so the bid can still be canceled through it
+    let err = setup_functions::close_account_of_closed_auction_house(
+        &mut context,
+        &auction_house,
+        &buyer_trade_state,
+        &buyer.pubkey(),
+        &buyer,
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::AuctionHouseNotClosed);
+
+    setup_functions::close_auction_house(&mut context, &auction_house)
+        .await
+        .unwrap();
+
+    // NOTE: the rent only goes back to the buyer that paid for the bid
+    let err = setup_functions::close_account_of_closed_auction_house(
+        &mut context,
+        &auction_house,
+        &buyer_trade_state,
+        &seller.pubkey(),
+        &seller,
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::PublicKeyMismatch);
+
+    let rent = setup_functions::get_balance(&mut context, &buyer_trade_state).await;
+    let buyer_balance = setup_functions::get_balance(&mut context, &buyer.pubkey()).await;
+
+    setup_functions::close_account_of_closed_auction_house(
+        &mut context,
+        &auction_house,
+        &buyer_trade_state,
+        &buyer.pubkey(),
+        &seller,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        setup_functions::get_balance(&mut context, &buyer_trade_state).await,
+        0
+    );
+    assert_eq!(
+        setup_functions::get_balance(&mut context, &buyer.pubkey()).await,
+        buyer_balance + rent
+    );
+}
@@ -0,0 +1,358 @@
+#![cfg(feature = "test-bpf")]
+pub mod utils;
+
+use anchor_lang::{solana_program::keccak, AccountDeserialize};
+use auction_house::{errors::AuctionHouseError, state::CollectionOffer};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use spl_associated_token_account::get_associated_token_address;
+use utils::{
+    helpers::{assert_custom_error, find_escrow_payment_address},
+    setup_functions::*,
+};
+
+const PRICE: u64 = ONE_SOL;
+
+async fn setup() -> (
+    ProgramTestContext,
+    TestAuctionHouse,
+    Keypair,
+    Keypair,
+    TestNft,
+) {
+    let mut context = auction_house_program_test().start_with_context().await;
+    let auction_house = existing_auction_house_test_context(&mut context, 0).await;
+
+    let seller = Keypair::new();
+    let buyer = Keypair::new();
+    airdrop(&mut context, &seller.pubkey(), 10 * ONE_SOL).await;
+    airdrop(&mut context, &buyer.pubkey(), 10 * ONE_SOL).await;
+    let collection = create_collection(&mut context).await;
+
+    (context, auction_house, seller, buyer, collection)
+}
+
+async fn get_collection_offer(
+    context: &mut ProgramTestContext,
+    address: &Pubkey,
+) -> CollectionOffer {
+    let account = context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap();
+
+    CollectionOffer::try_deserialize(&mut account.data.as_ref()).unwrap()
+}
+
+/// Leaf of `mint` in a trait offer's merkle tree.
+fn merkle_leaf(mint: &Pubkey) -> [u8; 32] {
+    keccak::hashv(&[&[0x00], mint.as_ref()]).to_bytes()
+}
+
+/// Root of the merkle tree over two leaves.
+fn merkle_root(first: [u8; 32], second: [u8; 32]) -> [u8; 32] {
+    let (min, max) = if first <= second {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    keccak::hashv(&[&[0x01], &min, &max]).to_bytes()
+}
+
+#[tokio::test]
+async fn accept_collection_offer_until_filled() {
+    let (mut context, auction_house, seller, buyer, collection) = setup().await;
+    let first = create_collection_nft(&mut context, &seller, &collection).await;
+    let second = create_collection_nft(&mut context, &seller, &collection).await;
+
+    let collection_offer = make_collection_offer(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &collection.mint,
+        PRICE,
+        2,
+        [0; 32],
+    )
+    .await
+    .unwrap();
+
+    let (escrow_payment_account, _) =
+        find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let escrow_before = get_balance(&mut context, &escrow_payment_account).await;
+    accept_collection_offer(
+        &mut context,
+        &auction_house,
+        &seller,
+        &first,
+        &buyer.pubkey(),
+        &collection_offer,
+        PRICE,
+        vec![],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        escrow_before - get_balance(&mut context, &escrow_payment_account).await,
+        PRICE
+    );
+    assert_eq!(
+        get_token_account(
+            &mut context,
+            &get_associated_token_address(&buyer.pubkey(), &first.mint)
+        )
+        .await
+        .amount,
+        1
+    );
+    assert_eq!(
+        get_collection_offer(&mut context, &collection_offer)
+            .await
+            .quantity,
+        1
+    );
+
+    // NOTE: the last fill closes the offer back to the buyer
+    let rent = get_balance(&mut context, &collection_offer).await;
+    let buyer_before = get_balance(&mut context, &buyer.pubkey()).await;
+    accept_collection_offer(
+        &mut context,
+        &auction_house,
+        &seller,
+        &second,
+        &buyer.pubkey(),
+        &collection_offer,
+        PRICE,
+        vec![],
+    )
+    .await
+    .unwrap();
+
+    assert!(context
+        .banks_client
+        .get_account(collection_offer)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        get_balance(&mut context, &buyer.pubkey()).await,
+        buyer_before + rent
+    );
+    assert_eq!(
+        get_token_account(
+            &mut context,
+            &get_associated_token_address(&buyer.pubkey(), &second.mint)
+        )
+        .await
+        .amount,
+        1
+    );
+}
+
+#[tokio::test]
+async fn accept_collection_offer_outside_collection_fails() {
+    let (mut context, auction_house, seller, buyer, collection) = setup().await;
+    let nft = create_nft(&mut context, &seller).await;
+    let other_collection = create_collection(&mut context).await;
+    let other_nft = create_collection_nft(&mut context, &seller, &other_collection).await;
+
+    let collection_offer = make_collection_offer(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &collection.mint,
+        PRICE,
+        1,
+        [0; 32],
+    )
+    .await
+    .unwrap();
+
+    for nft in [&nft, &other_nft] {
+        let err = accept_collection_offer(
+            &mut context,
+            &auction_house,
+            &seller,
+            nft,
+            &buyer.pubkey(),
+            &collection_offer,
+            PRICE,
+            vec![],
+        )
+        .await
+        .unwrap_err();
+        assert_custom_error(err, AuctionHouseError::InvalidCollection);
+    }
+}
+
+#[tokio::test]
+async fn cancel_collection_offer_success() {
+    let (mut context, auction_house, _seller, buyer, collection) = setup().await;
+    let collection_offer = make_collection_offer(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &collection.mint,
+        PRICE,
+        1,
+        [0; 32],
+    )
+    .await
+    .unwrap();
+
+    let (escrow_payment_account, _) =
+        find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let escrow_before = get_balance(&mut context, &escrow_payment_account).await;
+    cancel_collection_offer(&mut context, &auction_house, &buyer, &collection_offer)
+        .await
+        .unwrap();
+
+    // NOTE: the offer funds stay in the escrow until withdrawn
+    assert_eq!(
+        get_balance(&mut context, &escrow_payment_account).await,
+        escrow_before
+    );
+    assert!(context
+        .banks_client
+        .get_account(collection_offer)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn accept_trait_offer_with_merkle_proof() {
+    let (mut context, auction_house, seller, buyer, collection) = setup().await;
+    let first = create_collection_nft(&mut context, &seller, &collection).await;
+    let second = create_collection_nft(&mut context, &seller, &collection).await;
+    let outside = create_collection_nft(&mut context, &seller, &collection).await;
+
+    // NOTE: the offer only covers the first two items of the collection, e.g. the ones with a given trait
+    let root = merkle_root(merkle_leaf(&first.mint), merkle_leaf(&second.mint));
+    let collection_offer = make_collection_offer(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &collection.mint,
+        PRICE,
+        2,
+        root,
+    )
+    .await
+    .unwrap();
+
+    let err = accept_collection_offer(
+        &mut context,
+        &auction_house,
+        &seller,
+        &outside,
+        &buyer.pubkey(),
+        &collection_offer,
+        PRICE,
+        vec![merkle_leaf(&first.mint)],
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::InvalidMerkleProof);
+
+    let err = accept_collection_offer(
+        &mut context,
+        &auction_house,
+        &seller,
+        &first,
+        &buyer.pubkey(),
+        &collection_offer,
+        PRICE,
+        vec![],
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::InvalidMerkleProof);
+
+    accept_collection_offer(
+        &mut context,
+        &auction_house,
+        &seller,
+        &first,
+        &buyer.pubkey(),
+        &collection_offer,
+        PRICE,
+        vec![merkle_leaf(&second.mint)],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        get_token_account(
+            &mut context,
+            &get_associated_token_address(&buyer.pubkey(), &first.mint)
+        )
+        .await
+        .amount,
+        1
+    );
+    assert_eq!(
+        get_collection_offer(&mut context, &collection_offer)
+            .await
+            .quantity,
+        1
+    );
+}
+
+#[tokio::test]
+async fn accept_trait_offer_needs_full_proof() {
+    let (mut context, auction_house, seller, buyer, collection) = setup().await;
+    let first = create_collection_nft(&mut context, &seller, &collection).await;
+    let second = create_collection_nft(&mut context, &seller, &collection).await;
+    let nft = create_collection_nft(&mut context, &seller, &collection).await;
+
+    let root = merkle_root(
+        merkle_root(merkle_leaf(&first.mint), merkle_leaf(&second.mint)),
+        merkle_leaf(&nft.mint),
+    );
+    let collection_offer = make_collection_offer(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &collection.mint,
+        PRICE,
+        1,
+        root,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: a proof skipping the sibling of the first mint does not reach the root
+    let err = accept_collection_offer(
+        &mut context,
+        &auction_house,
+        &seller,
+        &first,
+        &buyer.pubkey(),
+        &collection_offer,
+        PRICE,
+        vec![merkle_leaf(&nft.mint)],
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::InvalidMerkleProof);
+
+    accept_collection_offer(
+        &mut context,
+        &auction_house,
+        &seller,
+        &first,
+        &buyer.pubkey(),
+        &collection_offer,
+        PRICE,
+        vec![merkle_leaf(&second.mint), merkle_leaf(&nft.mint)],
+    )
+    .await
+    .unwrap();
+}
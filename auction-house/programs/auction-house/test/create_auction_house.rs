@@ -1,9 +1,22 @@
 #![cfg(feature = "test-bpf")]
 pub mod utils;
+
+use solana_program_test::*;
+use solana_sdk::signature::Signer;
 use utils::setup_functions;
+
 #[tokio::test]
 async fn init_native_success() {
     let mut context = setup_functions::auction_house_program_test()
         .start_with_context()
         .await;
+
+    let auction_house =
+        setup_functions::existing_auction_house_test_context(&mut context, 100).await;
+    let account = setup_functions::get_auction_house(&mut context, &auction_house.key).await;
+
+    assert_eq!(account.authority, auction_house.authority.pubkey());
+    assert_eq!(account.treasury_mint, spl_token::native_mint::id());
+    assert_eq!(account.seller_fee_basis_points, 100);
+    assert_eq!(account.open_auction_escrows, 0);
 }
@@ -0,0 +1,127 @@
+#![cfg(feature = "test-bpf")]
+pub mod utils;
+
+use auction_house::errors::AuctionHouseError;
+use solana_program_test::*;
+use solana_sdk::{
+    clock::Clock,
+    signature::{Keypair, Signer},
+};
+use spl_associated_token_account::get_associated_token_address;
+use utils::{
+    helpers::{assert_custom_error, find_dutch_auction_address, find_escrow_payment_address},
+    setup_functions::*,
+};
+
+const START_PRICE: u64 = 2 * ONE_SOL;
+const FLOOR_PRICE: u64 = ONE_SOL;
+
+async fn setup() -> (
+    ProgramTestContext,
+    TestAuctionHouse,
+    Keypair,
+    Keypair,
+    TestNft,
+    TestListing,
+) {
+    let mut context = auction_house_program_test().start_with_context().await;
+    let auction_house = existing_auction_house_test_context(&mut context, 200).await;
+
+    let seller = Keypair::new();
+    let buyer = Keypair::new();
+    airdrop(&mut context, &seller.pubkey(), 10 * ONE_SOL).await;
+    airdrop(&mut context, &buyer.pubkey(), 10 * ONE_SOL).await;
+    let nft = create_nft(&mut context, &seller).await;
+
+    // NOTE: the price drops by half the spread every 50 seconds
+    let now = context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+    let listing = sell_dutch_auction(
+        &mut context,
+        &auction_house,
+        &seller,
+        &nft,
+        now,
+        now + 100,
+        START_PRICE,
+        FLOOR_PRICE,
+        50,
+    )
+    .await
+    .unwrap();
+
+    (context, auction_house, seller, buyer, nft, listing)
+}
+
+#[tokio::test]
+async fn buy_dutch_auction_at_current_price() {
+    let (mut context, auction_house, seller, buyer, nft, listing) = setup().await;
+    advance_clock(&mut context, 1, 60).await;
+
+    let (dutch_auction, _) =
+        find_dutch_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let dutch_auction_rent = get_balance(&mut context, &dutch_auction).await;
+    let seller_before = get_balance(&mut context, &seller.pubkey()).await;
+
+    buy_dutch_auction(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        START_PRICE,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: the buyer pays the stepped price less the 2% house fee, keeping the rest of its bid in escrow
+    let price = (START_PRICE + FLOOR_PRICE) / 2;
+    assert_eq!(
+        get_balance(&mut context, &seller.pubkey()).await - seller_before,
+        price - price / 50 + dutch_auction_rent
+    );
+    let (escrow_payment_account, _) =
+        find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert_eq!(
+        get_balance(&mut context, &escrow_payment_account).await,
+        rent.minimum_balance(0) + START_PRICE - price
+    );
+
+    let buyer_token_account = get_associated_token_address(&buyer.pubkey(), &nft.mint);
+    assert_eq!(
+        get_token_account(&mut context, &buyer_token_account)
+            .await
+            .amount,
+        1
+    );
+    assert!(context
+        .banks_client
+        .get_account(dutch_auction)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn buy_dutch_auction_below_current_price_fails() {
+    let (mut context, auction_house, seller, buyer, nft, listing) = setup().await;
+
+    let err = buy_dutch_auction(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        FLOOR_PRICE,
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::AuctionPriceAboveBid);
+}
@@ -0,0 +1,474 @@
+#![cfg(feature = "test-bpf")]
+pub mod utils;
+
+use auction_house::errors::AuctionHouseError;
+use solana_program_test::*;
+use solana_sdk::{
+    clock::Clock,
+    signature::{Keypair, Signer},
+};
+use spl_associated_token_account::get_associated_token_address;
+use utils::{
+    helpers::{
+        assert_custom_error, find_auction_escrow_address, find_english_auction_address,
+        find_escrow_payment_address,
+    },
+    setup_functions::*,
+};
+
+const PRICE: u64 = ONE_SOL;
+
+async fn setup() -> (
+    ProgramTestContext,
+    TestAuctionHouse,
+    Keypair,
+    Keypair,
+    TestNft,
+    TestListing,
+) {
+    let mut context = auction_house_program_test().start_with_context().await;
+    let auction_house = existing_auction_house_test_context(&mut context, 200).await;
+
+    let seller = Keypair::new();
+    let buyer = Keypair::new();
+    airdrop(&mut context, &seller.pubkey(), 10 * ONE_SOL).await;
+    airdrop(&mut context, &buyer.pubkey(), 10 * ONE_SOL).await;
+    let nft = create_nft(&mut context, &seller).await;
+
+    let now = context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+    let listing = sell_english_auction(
+        &mut context,
+        &auction_house,
+        &seller,
+        &nft,
+        now,
+        now + 60,
+        PRICE / 2,
+        1_000,
+    )
+    .await
+    .unwrap();
+
+    (context, auction_house, seller, buyer, nft, listing)
+}
+
+#[tokio::test]
+async fn outbid_english_auction_refunds_previous_bidder() {
+    let (mut context, auction_house, _seller, buyer, nft, listing) = setup().await;
+    let (english_auction, _) =
+        find_english_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (auction_escrow, _) = find_auction_escrow_address(&english_auction);
+    let auction_escrow_rent = get_balance(&mut context, &auction_escrow).await;
+
+    let buyer_trade_state =
+        bid_english_auction(&mut context, &auction_house, &buyer, &nft, &listing, PRICE)
+            .await
+            .unwrap();
+    let trade_state_rent = get_balance(&mut context, &buyer_trade_state).await;
+    let buyer_before = get_balance(&mut context, &buyer.pubkey()).await;
+
+    let outbidder = Keypair::new();
+    airdrop(&mut context, &outbidder.pubkey(), 10 * ONE_SOL).await;
+    outbid_english_auction(
+        &mut context,
+        &auction_house,
+        &outbidder,
+        &nft,
+        &listing,
+        2 * PRICE,
+        &buyer.pubkey(),
+        &buyer_trade_state,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: the outbid funds return to the previous bidder's escrow and its trade state rent to its wallet
+    let (buyer_escrow, _) = find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert_eq!(
+        get_balance(&mut context, &buyer_escrow).await,
+        rent.minimum_balance(0) + PRICE
+    );
+    assert_eq!(
+        get_balance(&mut context, &buyer.pubkey()).await - buyer_before,
+        trade_state_rent
+    );
+    assert!(context
+        .banks_client
+        .get_account(buyer_trade_state)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        get_balance(&mut context, &auction_escrow).await,
+        auction_escrow_rent + 2 * PRICE
+    );
+}
+
+#[tokio::test]
+async fn outbid_own_english_auction_bid() {
+    let (mut context, auction_house, _seller, buyer, nft, listing) = setup().await;
+    let buyer_trade_state =
+        bid_english_auction(&mut context, &auction_house, &buyer, &nft, &listing, PRICE)
+            .await
+            .unwrap();
+
+    // NOTE: the bidder raising its own bid is both the refunded escrow and the payer of the new bid
+    outbid_english_auction(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &nft,
+        &listing,
+        2 * PRICE,
+        &buyer.pubkey(),
+        &buyer_trade_state,
+    )
+    .await
+    .unwrap();
+
+    let (buyer_escrow, _) = find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert_eq!(
+        get_balance(&mut context, &buyer_escrow).await,
+        rent.minimum_balance(0)
+    );
+    assert!(context
+        .banks_client
+        .get_account(buyer_trade_state)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn settle_english_auction_pays_seller_and_house() {
+    let (mut context, auction_house, seller, buyer, nft, listing) = setup().await;
+    let buyer_trade_state =
+        bid_english_auction(&mut context, &auction_house, &buyer, &nft, &listing, PRICE)
+            .await
+            .unwrap();
+
+    advance_clock(&mut context, 1, 60).await;
+
+    let settler = Keypair::new();
+    airdrop(&mut context, &settler.pubkey(), ONE_SOL).await;
+    let (english_auction, _) =
+        find_english_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (auction_escrow, _) = find_auction_escrow_address(&english_auction);
+    let english_auction_rent = get_balance(&mut context, &english_auction).await;
+    let auction_escrow_rent = get_balance(&mut context, &auction_escrow).await - PRICE;
+    let seller_before = get_balance(&mut context, &seller.pubkey()).await;
+    let treasury_before = get_balance(&mut context, &auction_house.treasury).await;
+
+    settle_english_auction(
+        &mut context,
+        &auction_house,
+        &settler,
+        &buyer.pubkey(),
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &buyer_trade_state,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: the seller gets the winning bid less the 2% house fee, plus the rent of the closed auction accounts
+    assert_eq!(
+        get_balance(&mut context, &seller.pubkey()).await - seller_before,
+        PRICE - 20_000_000 + english_auction_rent + auction_escrow_rent
+    );
+    assert_eq!(
+        get_balance(&mut context, &auction_house.treasury).await - treasury_before,
+        20_000_000
+    );
+
+    let buyer_token_account = get_associated_token_address(&buyer.pubkey(), &nft.mint);
+    assert_eq!(
+        get_token_account(&mut context, &buyer_token_account)
+            .await
+            .amount,
+        1
+    );
+    assert!(context
+        .banks_client
+        .get_account(english_auction)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        get_auction_house(&mut context, &auction_house.key)
+            .await
+            .open_auction_escrows,
+        0
+    );
+}
+
+#[tokio::test]
+async fn settle_english_auction_by_seller() {
+    let (mut context, auction_house, seller, buyer, nft, listing) = setup().await;
+    let buyer_trade_state =
+        bid_english_auction(&mut context, &auction_house, &buyer, &nft, &listing, PRICE)
+            .await
+            .unwrap();
+
+    advance_clock(&mut context, 1, 60).await;
+
+    // NOTE: the seller cranking its own settlement receives both the escrow rent and the trade state rent
+    settle_english_auction(
+        &mut context,
+        &auction_house,
+        &seller,
+        &buyer.pubkey(),
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &buyer_trade_state,
+    )
+    .await
+    .unwrap();
+
+    let buyer_token_account = get_associated_token_address(&buyer.pubkey(), &nft.mint);
+    assert_eq!(
+        get_token_account(&mut context, &buyer_token_account)
+            .await
+            .amount,
+        1
+    );
+    for trade_state in [listing.seller_trade_state, buyer_trade_state] {
+        assert!(context
+            .banks_client
+            .get_account(trade_state)
+            .await
+            .unwrap()
+            .is_none());
+    }
+    assert_eq!(
+        get_auction_house(&mut context, &auction_house.key)
+            .await
+            .open_auction_escrows,
+        0
+    );
+}
+
+#[tokio::test]
+async fn settle_english_auction_after_auctioneer_delegation() {
+    let (mut context, auction_house, seller, buyer, nft, listing) = setup().await;
+    let buyer_trade_state =
+        bid_english_auction(&mut context, &auction_house, &buyer, &nft, &listing, PRICE)
+            .await
+            .unwrap();
+
+    delegate_auctioneer(&mut context, &auction_house, &Keypair::new().pubkey())
+        .await
+        .unwrap();
+
+    // NOTE: the auction no longer takes bids, but still settles with the bid it already has
+    let outbidder = Keypair::new();
+    airdrop(&mut context, &outbidder.pubkey(), 10 * ONE_SOL).await;
+    let err = bid_english_auction(
+        &mut context,
+        &auction_house,
+        &outbidder,
+        &nft,
+        &listing,
+        2 * PRICE,
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::MustUseAuctioneerHandler);
+
+    advance_clock(&mut context, 1, 60).await;
+
+    settle_english_auction(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &buyer.pubkey(),
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &buyer_trade_state,
+    )
+    .await
+    .unwrap();
+
+    let buyer_token_account = get_associated_token_address(&buyer.pubkey(), &nft.mint);
+    assert_eq!(
+        get_token_account(&mut context, &buyer_token_account)
+            .await
+            .amount,
+        1
+    );
+    assert_eq!(
+        get_auction_house(&mut context, &auction_house.key)
+            .await
+            .open_auction_escrows,
+        0
+    );
+}
+
+#[tokio::test]
+async fn settle_english_auction_on_house_requiring_sign_off() {
+    let (mut context, auction_house, seller, buyer, nft, listing) = setup().await;
+    let buyer_trade_state =
+        bid_english_auction(&mut context, &auction_house, &buyer, &nft, &listing, PRICE)
+            .await
+            .unwrap();
+
+    // NOTE: settlement stays permissionless once the house requires sign off on its orders
+    update_requires_sign_off(&mut context, &auction_house, true)
+        .await
+        .unwrap();
+    advance_clock(&mut context, 1, 60).await;
+
+    let settler = Keypair::new();
+    airdrop(&mut context, &settler.pubkey(), ONE_SOL).await;
+    settle_english_auction(
+        &mut context,
+        &auction_house,
+        &settler,
+        &buyer.pubkey(),
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &buyer_trade_state,
+    )
+    .await
+    .unwrap();
+
+    let buyer_token_account = get_associated_token_address(&buyer.pubkey(), &nft.mint);
+    assert_eq!(
+        get_token_account(&mut context, &buyer_token_account)
+            .await
+            .amount,
+        1
+    );
+}
+
+#[tokio::test]
+async fn settle_english_auction_before_end_fails() {
+    let (mut context, auction_house, seller, buyer, nft, listing) = setup().await;
+    let buyer_trade_state =
+        bid_english_auction(&mut context, &auction_house, &buyer, &nft, &listing, PRICE)
+            .await
+            .unwrap();
+
+    let err = settle_english_auction(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &buyer.pubkey(),
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &buyer_trade_state,
+    )
+    .await
+    .unwrap_err();
+
+    assert_custom_error(err, AuctionHouseError::AuctionNotEnded);
+}
+
+#[tokio::test]
+async fn cancel_english_auction_without_bids() {
+    let (mut context, auction_house, seller, _buyer, nft, listing) = setup().await;
+    assert_eq!(
+        get_auction_house(&mut context, &auction_house.key)
+            .await
+            .open_auction_escrows,
+        1
+    );
+
+    cancel_english_auction(
+        &mut context,
+        &auction_house,
+        &seller,
+        &nft,
+        &listing,
+        &seller.pubkey(),
+        &listing.seller_trade_state,
+    )
+    .await
+    .unwrap();
+
+    let (english_auction, _) =
+        find_english_auction_address(&auction_house.key, &listing.seller_trade_state);
+    assert!(context
+        .banks_client
+        .get_account(english_auction)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(get_token_account(&mut context, &nft.token_account)
+        .await
+        .delegate
+        .is_none());
+    assert_eq!(
+        get_auction_house(&mut context, &auction_house.key)
+            .await
+            .open_auction_escrows,
+        0
+    );
+}
+
+#[tokio::test]
+async fn cancel_english_auction_after_auctioneer_delegation() {
+    let (mut context, auction_house, seller, _buyer, nft, listing) = setup().await;
+
+    delegate_auctioneer(&mut context, &auction_house, &Keypair::new().pubkey())
+        .await
+        .unwrap();
+
+    cancel_english_auction(
+        &mut context,
+        &auction_house,
+        &seller,
+        &nft,
+        &listing,
+        &seller.pubkey(),
+        &listing.seller_trade_state,
+    )
+    .await
+    .unwrap();
+
+    assert!(get_token_account(&mut context, &nft.token_account)
+        .await
+        .delegate
+        .is_none());
+    assert_eq!(
+        get_auction_house(&mut context, &auction_house.key)
+            .await
+            .open_auction_escrows,
+        0
+    );
+}
+
+#[tokio::test]
+async fn cancel_english_auction_with_bids_fails() {
+    let (mut context, auction_house, seller, buyer, nft, listing) = setup().await;
+    let buyer_trade_state =
+        bid_english_auction(&mut context, &auction_house, &buyer, &nft, &listing, PRICE)
+            .await
+            .unwrap();
+
+    let err = cancel_english_auction(
+        &mut context,
+        &auction_house,
+        &seller,
+        &nft,
+        &listing,
+        &buyer.pubkey(),
+        &buyer_trade_state,
+    )
+    .await
+    .unwrap_err();
+
+    assert_custom_error(err, AuctionHouseError::AuctionHasBids);
+}
@@ -0,0 +1,345 @@
+#![cfg(feature = "test-bpf")]
+pub mod utils;
+
+use anchor_lang::AccountDeserialize;
+use auction_house::state::WalletStats;
+use solana_program_test::*;
+use solana_sdk::signature::{Keypair, Signer};
+use spl_associated_token_account::get_associated_token_address;
+use utils::{helpers::find_escrow_payment_address, setup_functions::*};
+
+const PRICE: u64 = ONE_SOL;
+
+async fn setup(
+    maker_fee_basis_points: i16,
+    taker_fee_basis_points: u16,
+) -> (
+    ProgramTestContext,
+    TestAuctionHouse,
+    Keypair,
+    Keypair,
+    TestNft,
+) {
+    let mut context = auction_house_program_test().start_with_context().await;
+    let auction_house = existing_auction_house_test_context(&mut context, 100).await;
+    update_maker_taker_fees(
+        &mut context,
+        &auction_house,
+        maker_fee_basis_points,
+        taker_fee_basis_points,
+    )
+    .await
+    .unwrap();
+
+    let seller = Keypair::new();
+    let buyer = Keypair::new();
+    airdrop(&mut context, &seller.pubkey(), 10 * ONE_SOL).await;
+    airdrop(&mut context, &buyer.pubkey(), 10 * ONE_SOL).await;
+    let nft = create_nft(&mut context, &seller).await;
+
+    (context, auction_house, seller, buyer, nft)
+}
+
+#[tokio::test]
+async fn execute_sale_charges_maker_fee_to_resting_listing() {
+    let (mut context, auction_house, seller, buyer, nft) = setup(50, 200).await;
+
+    let listing = sell(&mut context, &auction_house, &seller, &nft, PRICE)
+        .await
+        .unwrap();
+    advance_clock(&mut context, 1, 0).await;
+    let buyer_trade_state = buy(&mut context, &auction_house, &buyer, &nft, PRICE)
+        .await
+        .unwrap();
+
+    let seller_before = get_balance(&mut context, &seller.pubkey()).await;
+    let treasury_before = get_balance(&mut context, &auction_house.treasury).await;
+
+    execute_sale(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &buyer_trade_state,
+        PRICE,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: the buyer takes the resting listing, paying the 2% taker fee on top of the price while the seller
+    // pays the 0.5% maker fee
+    let (escrow_payment_account, _) =
+        find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert_eq!(
+        get_balance(&mut context, &escrow_payment_account).await,
+        rent.minimum_balance(0)
+    );
+    assert_eq!(
+        get_balance(&mut context, &seller.pubkey()).await - seller_before,
+        PRICE - 5_000_000
+    );
+    assert_eq!(
+        get_balance(&mut context, &auction_house.treasury).await - treasury_before,
+        25_000_000
+    );
+
+    let buyer_token_account = get_associated_token_address(&buyer.pubkey(), &nft.mint);
+    assert_eq!(
+        get_token_account(&mut context, &buyer_token_account)
+            .await
+            .amount,
+        1
+    );
+}
+
+#[tokio::test]
+async fn execute_sale_pays_maker_rebate_to_resting_listing() {
+    let (mut context, auction_house, seller, buyer, nft) = setup(-50, 200).await;
+
+    let listing = sell(&mut context, &auction_house, &seller, &nft, PRICE)
+        .await
+        .unwrap();
+    advance_clock(&mut context, 1, 0).await;
+    let buyer_trade_state = buy(&mut context, &auction_house, &buyer, &nft, PRICE)
+        .await
+        .unwrap();
+
+    let seller_before = get_balance(&mut context, &seller.pubkey()).await;
+    let treasury_before = get_balance(&mut context, &auction_house.treasury).await;
+
+    execute_sale(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &buyer_trade_state,
+        PRICE,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: the 0.5% rebate comes out of the 2% taker fee
+    assert_eq!(
+        get_balance(&mut context, &seller.pubkey()).await - seller_before,
+        PRICE + 5_000_000
+    );
+    assert_eq!(
+        get_balance(&mut context, &auction_house.treasury).await - treasury_before,
+        15_000_000
+    );
+}
+
+#[tokio::test]
+async fn execute_sale_charges_taker_fee_to_seller_hitting_resting_bid() {
+    let (mut context, auction_house, seller, buyer, nft) = setup(50, 200).await;
+
+    let buyer_trade_state = buy(&mut context, &auction_house, &buyer, &nft, PRICE)
+        .await
+        .unwrap();
+    advance_clock(&mut context, 1, 0).await;
+    let listing = sell(&mut context, &auction_house, &seller, &nft, PRICE)
+        .await
+        .unwrap();
+
+    let seller_before = get_balance(&mut context, &seller.pubkey()).await;
+    let treasury_before = get_balance(&mut context, &auction_house.treasury).await;
+
+    execute_sale(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &buyer_trade_state,
+        PRICE,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: the bid escrowed the larger taker fee, so the difference with the maker fee stays in escrow
+    let (escrow_payment_account, _) =
+        find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert_eq!(
+        get_balance(&mut context, &escrow_payment_account).await,
+        rent.minimum_balance(0) + 15_000_000
+    );
+    assert_eq!(
+        get_balance(&mut context, &seller.pubkey()).await - seller_before,
+        PRICE - 20_000_000
+    );
+    assert_eq!(
+        get_balance(&mut context, &auction_house.treasury).await - treasury_before,
+        25_000_000
+    );
+}
+
+#[tokio::test]
+async fn execute_sale_records_volume_of_existing_wallet_stats() {
+    let (mut context, auction_house, seller, buyer, nft) = setup(0, 0).await;
+
+    // NOTE: only the seller tracks its volume, the buyer's wallet stats address stays empty
+    let seller_stats = create_wallet_stats(&mut context, &auction_house, &seller, &seller.pubkey())
+        .await
+        .unwrap();
+
+    let listing = sell(&mut context, &auction_house, &seller, &nft, PRICE)
+        .await
+        .unwrap();
+    let buyer_trade_state = buy(&mut context, &auction_house, &buyer, &nft, PRICE)
+        .await
+        .unwrap();
+
+    execute_sale(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &buyer_trade_state,
+        PRICE,
+    )
+    .await
+    .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(seller_stats)
+        .await
+        .unwrap()
+        .unwrap();
+    let stats = WalletStats::try_deserialize(&mut account.data.as_ref()).unwrap();
+    assert_eq!(stats.current_volume, PRICE);
+}
+
+#[tokio::test]
+async fn execute_sale_without_wallet_stats_accounts() {
+    let (mut context, auction_house, seller, buyer, nft) = setup(0, 0).await;
+
+    // NOTE: the seller tracks its volume, but a client passing no wallet stats accounts leaves it untouched
+    let seller_stats = create_wallet_stats(&mut context, &auction_house, &seller, &seller.pubkey())
+        .await
+        .unwrap();
+
+    let listing = sell(&mut context, &auction_house, &seller, &nft, PRICE)
+        .await
+        .unwrap();
+    let buyer_trade_state = buy(&mut context, &auction_house, &buyer, &nft, PRICE)
+        .await
+        .unwrap();
+
+    execute_sale_without_wallet_stats(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &buyer_trade_state,
+        PRICE,
+    )
+    .await
+    .unwrap();
+
+    let buyer_token_account = get_associated_token_address(&buyer.pubkey(), &nft.mint);
+    assert_eq!(
+        get_token_account(&mut context, &buyer_token_account)
+            .await
+            .amount,
+        1
+    );
+
+    let account = context
+        .banks_client
+        .get_account(seller_stats)
+        .await
+        .unwrap()
+        .unwrap();
+    let stats = WalletStats::try_deserialize(&mut account.data.as_ref()).unwrap();
+    assert_eq!(stats.current_volume, 0);
+}
+
+#[tokio::test]
+async fn execute_partial_sale_relists_remaining_units() {
+    let (mut context, auction_house, seller, buyer, _nft) = setup(50, 200).await;
+    let sft = create_sft(&mut context, &seller, 4).await;
+
+    let listing = sell_units(&mut context, &auction_house, &seller, &sft, 4 * PRICE, 4)
+        .await
+        .unwrap();
+    advance_clock(&mut context, 1, 0).await;
+    let buyer_trade_state = buy_units(&mut context, &auction_house, &buyer, &sft, PRICE, 1)
+        .await
+        .unwrap();
+
+    let seller_before = get_balance(&mut context, &seller.pubkey()).await;
+    let treasury_before = get_balance(&mut context, &auction_house.treasury).await;
+
+    let remainder = execute_partial_sale(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &seller.pubkey(),
+        &sft,
+        &listing,
+        &buyer_trade_state,
+        4 * PRICE,
+        4,
+        1,
+        PRICE,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: the unit taken is charged like a full sale at the unit price
+    let (escrow_payment_account, _) =
+        find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert_eq!(
+        get_balance(&mut context, &escrow_payment_account).await,
+        rent.minimum_balance(0)
+    );
+    assert_eq!(
+        get_balance(&mut context, &seller.pubkey()).await - seller_before,
+        PRICE - 5_000_000
+    );
+    assert_eq!(
+        get_balance(&mut context, &auction_house.treasury).await - treasury_before,
+        25_000_000
+    );
+
+    let buyer_token_account = get_associated_token_address(&buyer.pubkey(), &sft.mint);
+    assert_eq!(
+        get_token_account(&mut context, &buyer_token_account)
+            .await
+            .amount,
+        1
+    );
+    let seller_token_account = get_token_account(&mut context, &sft.token_account).await;
+    assert_eq!(seller_token_account.amount, 3);
+    assert_eq!(seller_token_account.delegated_amount, 3);
+
+    // NOTE: the remaining units are listed at the same unit price, while the filled orders are closed
+    for (trade_state, open) in [
+        (listing.seller_trade_state, false),
+        (buyer_trade_state, false),
+        (remainder.seller_trade_state, true),
+    ] {
+        assert_eq!(
+            context
+                .banks_client
+                .get_account(trade_state)
+                .await
+                .unwrap()
+                .is_some(),
+            open
+        );
+    }
+}
@@ -0,0 +1,207 @@
+#![cfg(feature = "test-bpf")]
+pub mod utils;
+
+use auction_house::errors::AuctionHouseError;
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use utils::{
+    helpers::{assert_custom_error, find_referrer_address},
+    setup_functions::*,
+};
+
+const PRICE: u64 = ONE_SOL;
+const HOUSE_FEE: u64 = PRICE / 50;
+const SHARE_BASIS_POINTS: u16 = 2500;
+
+async fn setup() -> (
+    ProgramTestContext,
+    TestAuctionHouse,
+    Keypair,
+    Keypair,
+    TestNft,
+    Pubkey,
+) {
+    let mut context = auction_house_program_test().start_with_context().await;
+    let auction_house = existing_auction_house_test_context(&mut context, 200).await;
+
+    let seller = Keypair::new();
+    let buyer = Keypair::new();
+    airdrop(&mut context, &seller.pubkey(), 10 * ONE_SOL).await;
+    airdrop(&mut context, &buyer.pubkey(), 10 * ONE_SOL).await;
+    let nft = create_nft(&mut context, &seller).await;
+
+    let referrer_wallet = Pubkey::new_unique();
+    add_referrer(
+        &mut context,
+        &auction_house,
+        &referrer_wallet,
+        SHARE_BASIS_POINTS,
+    )
+    .await
+    .unwrap();
+
+    (context, auction_house, seller, buyer, nft, referrer_wallet)
+}
+
+#[tokio::test]
+async fn execute_sale_pays_recorded_referrer() {
+    let (mut context, auction_house, seller, buyer, nft, referrer_wallet) = setup().await;
+
+    let listing = sell(&mut context, &auction_house, &seller, &nft, PRICE)
+        .await
+        .unwrap();
+    let buyer_trade_state = buy_with_referrer(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &nft,
+        PRICE,
+        &referrer_wallet,
+    )
+    .await
+    .unwrap();
+
+    let treasury_before = get_balance(&mut context, &auction_house.treasury).await;
+    execute_sale_with_referral(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &buyer_trade_state,
+        PRICE,
+        &referrer_wallet,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: the referrer's quarter comes out of the 2% house fee
+    assert_eq!(
+        get_balance(&mut context, &referrer_wallet).await,
+        HOUSE_FEE / 4
+    );
+    assert_eq!(
+        get_balance(&mut context, &auction_house.treasury).await - treasury_before,
+        HOUSE_FEE - HOUSE_FEE / 4
+    );
+}
+
+#[tokio::test]
+async fn execute_sale_after_referrer_removed_pays_house() {
+    let (mut context, auction_house, seller, buyer, nft, referrer_wallet) = setup().await;
+
+    let listing = sell(&mut context, &auction_house, &seller, &nft, PRICE)
+        .await
+        .unwrap();
+    let buyer_trade_state = buy_with_referrer(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &nft,
+        PRICE,
+        &referrer_wallet,
+    )
+    .await
+    .unwrap();
+
+    let (referrer, _) = find_referrer_address(&auction_house.key, &referrer_wallet);
+    remove_referrer(&mut context, &auction_house, &referrer)
+        .await
+        .unwrap();
+
+    let treasury_before = get_balance(&mut context, &auction_house.treasury).await;
+    execute_sale_with_referral(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &buyer_trade_state,
+        PRICE,
+        &referrer_wallet,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(get_balance(&mut context, &referrer_wallet).await, 0);
+    assert_eq!(
+        get_balance(&mut context, &auction_house.treasury).await - treasury_before,
+        HOUSE_FEE
+    );
+}
+
+#[tokio::test]
+async fn execute_sale_without_referral_accounts_fails() {
+    let (mut context, auction_house, seller, buyer, nft, referrer_wallet) = setup().await;
+
+    let listing = sell(&mut context, &auction_house, &seller, &nft, PRICE)
+        .await
+        .unwrap();
+    let buyer_trade_state = buy_with_referrer(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &nft,
+        PRICE,
+        &referrer_wallet,
+    )
+    .await
+    .unwrap();
+
+    let err = execute_sale(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &buyer_trade_state,
+        PRICE,
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::ReferrerMismatch);
+}
+
+#[tokio::test]
+async fn buy_with_referrer_on_existing_bid_fails() {
+    let (mut context, auction_house, _seller, buyer, nft, referrer_wallet) = setup().await;
+
+    buy(&mut context, &auction_house, &buyer, &nft, PRICE)
+        .await
+        .unwrap();
+
+    let err = buy_with_referrer(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &nft,
+        PRICE,
+        &referrer_wallet,
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::TradeStateAlreadyExists);
+}
+
+#[tokio::test]
+async fn buy_with_unapproved_referrer_fails() {
+    let (mut context, auction_house, _seller, buyer, nft, _referrer_wallet) = setup().await;
+
+    let err = buy_with_referrer(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &nft,
+        PRICE,
+        &Pubkey::new_unique(),
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::InvalidReferrer);
+}
@@ -0,0 +1,124 @@
+#![cfg(feature = "test-bpf")]
+pub mod utils;
+
+use auction_house::{errors::AuctionHouseError, state::RevenueRecipient};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use utils::{
+    helpers::{assert_custom_error, find_revenue_split_address},
+    setup_functions::*,
+};
+
+async fn setup() -> (ProgramTestContext, TestAuctionHouse, [Pubkey; 2]) {
+    let mut context = auction_house_program_test().start_with_context().await;
+    let auction_house = existing_auction_house_test_context(&mut context, 0).await;
+
+    let recipients = [Pubkey::new_unique(), Pubkey::new_unique()];
+    create_revenue_split(
+        &mut context,
+        &auction_house,
+        vec![
+            RevenueRecipient {
+                wallet: recipients[0],
+                share: 60,
+            },
+            RevenueRecipient {
+                wallet: recipients[1],
+                share: 40,
+            },
+        ],
+    )
+    .await
+    .unwrap();
+
+    // NOTE: stands in for the house fees collected by sales
+    airdrop(&mut context, &auction_house.treasury, 2 * ONE_SOL).await;
+
+    (context, auction_house, recipients)
+}
+
+#[tokio::test]
+async fn withdraw_from_treasury_split_pays_recipients() {
+    let (mut context, auction_house, recipients) = setup().await;
+    let cranker = Keypair::new();
+    airdrop(&mut context, &cranker.pubkey(), ONE_SOL).await;
+
+    withdraw_from_treasury_split(
+        &mut context,
+        &auction_house,
+        &cranker,
+        &recipients,
+        2 * ONE_SOL,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        get_balance(&mut context, &recipients[0]).await,
+        ONE_SOL * 6 / 5
+    );
+    assert_eq!(
+        get_balance(&mut context, &recipients[1]).await,
+        ONE_SOL * 4 / 5
+    );
+    assert_eq!(get_balance(&mut context, &auction_house.treasury).await, 0);
+}
+
+#[tokio::test]
+async fn close_with_revenue_split_fails() {
+    let (mut context, auction_house, recipients) = setup().await;
+
+    let err = close_auction_house(&mut context, &auction_house)
+        .await
+        .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::CannotCloseWithRevenueSplit);
+
+    // NOTE: what the treasury collected under the split is paid out through it before the split can go
+    let err = remove_revenue_split(&mut context, &auction_house)
+        .await
+        .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::MustUseRevenueSplit);
+
+    let treasury = get_balance(&mut context, &auction_house.treasury).await;
+    withdraw_from_treasury_split(
+        &mut context,
+        &auction_house,
+        &auction_house.authority,
+        &recipients,
+        treasury,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: a new blockhash keeps the retried removal from being deduplicated with the failed one
+    context.last_blockhash = context
+        .banks_client
+        .get_new_latest_blockhash(&context.last_blockhash)
+        .await
+        .unwrap();
+    remove_revenue_split(&mut context, &auction_house)
+        .await
+        .unwrap();
+
+    let (revenue_split, _) = find_revenue_split_address(&auction_house.key);
+    assert!(context
+        .banks_client
+        .get_account(revenue_split)
+        .await
+        .unwrap()
+        .is_none());
+
+    close_auction_house(&mut context, &auction_house)
+        .await
+        .unwrap();
+
+    assert!(context
+        .banks_client
+        .get_account(auction_house.key)
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,468 @@
+#![cfg(feature = "test-bpf")]
+pub mod utils;
+
+use auction_house::{errors::AuctionHouseError, state::SealedBid};
+use solana_program_test::*;
+use solana_sdk::{
+    clock::Clock,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use spl_associated_token_account::get_associated_token_address;
+use utils::{
+    helpers::{
+        assert_custom_error, find_auction_escrow_address, find_escrow_payment_address,
+        find_sealed_auction_address, find_sealed_bid_address,
+    },
+    setup_functions::*,
+};
+
+const COLLATERAL: u64 = 3 * ONE_SOL;
+
+async fn setup(
+    vickrey: bool,
+) -> (
+    ProgramTestContext,
+    TestAuctionHouse,
+    Keypair,
+    TestNft,
+    TestListing,
+) {
+    let mut context = auction_house_program_test().start_with_context().await;
+    let auction_house = existing_auction_house_test_context(&mut context, 200).await;
+
+    let seller = Keypair::new();
+    airdrop(&mut context, &seller.pubkey(), 10 * ONE_SOL).await;
+    let nft = create_nft(&mut context, &seller).await;
+
+    let now = context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+    let listing = sell_sealed_auction(
+        &mut context,
+        &auction_house,
+        &seller,
+        &nft,
+        now + 60,
+        now + 120,
+        ONE_SOL / 2,
+        vickrey,
+    )
+    .await
+    .unwrap();
+
+    (context, auction_house, seller, nft, listing)
+}
+
+/// Funds a new bidder and commits `price` with a fixed collateral, returning the bidder and its salt.
+async fn commit(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    listing: &TestListing,
+    price: u64,
+) -> (Keypair, [u8; 32]) {
+    let bidder = Keypair::new();
+    airdrop(context, &bidder.pubkey(), 10 * ONE_SOL).await;
+
+    let salt = Pubkey::new_unique().to_bytes();
+    let (sealed_auction, _) =
+        find_sealed_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let commitment = SealedBid::commitment_of(price, &salt, &bidder.pubkey(), &sealed_auction);
+
+    commit_sealed_bid(
+        context,
+        auction_house,
+        &bidder,
+        listing,
+        commitment,
+        COLLATERAL,
+    )
+    .await
+    .unwrap();
+
+    (bidder, salt)
+}
+
+async fn escrow_balance(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    bidder: &Pubkey,
+) -> u64 {
+    let (escrow_payment_account, _) = find_escrow_payment_address(&auction_house.key, bidder);
+    get_balance(context, &escrow_payment_account).await
+}
+
+/// Commits and reveals a single winning bid of `price`, then ends the reveal window.
+async fn win(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    nft: &TestNft,
+    listing: &TestListing,
+    price: u64,
+) -> (Keypair, Pubkey) {
+    let (winner, salt) = commit(context, auction_house, listing, price).await;
+    advance_clock(context, 1, 60).await;
+
+    let trade_state = reveal_sealed_bid(
+        context,
+        auction_house,
+        &winner,
+        nft,
+        listing,
+        price,
+        salt,
+        &winner.pubkey(),
+        &listing.seller_trade_state,
+    )
+    .await
+    .unwrap();
+    advance_clock(context, 1, 60).await;
+
+    (winner, trade_state)
+}
+
+#[tokio::test]
+async fn settle_vickrey_auction_at_second_price() {
+    let (mut context, auction_house, seller, nft, listing) = setup(true).await;
+
+    let (first, first_salt) = commit(&mut context, &auction_house, &listing, ONE_SOL).await;
+    let (winner, winner_salt) = commit(&mut context, &auction_house, &listing, 2 * ONE_SOL).await;
+    let (third, third_salt) = commit(&mut context, &auction_house, &listing, 3 * ONE_SOL / 2).await;
+    assert_eq!(
+        get_auction_house(&mut context, &auction_house.key)
+            .await
+            .open_auction_escrows,
+        4
+    );
+
+    let first_before = escrow_balance(&mut context, &auction_house, &first.pubkey()).await;
+    let winner_before = escrow_balance(&mut context, &auction_house, &winner.pubkey()).await;
+    let third_before = escrow_balance(&mut context, &auction_house, &third.pubkey()).await;
+
+    advance_clock(&mut context, 1, 60).await;
+
+    let first_trade_state = reveal_sealed_bid(
+        &mut context,
+        &auction_house,
+        &first,
+        &nft,
+        &listing,
+        ONE_SOL,
+        first_salt,
+        &first.pubkey(),
+        &listing.seller_trade_state,
+    )
+    .await
+    .unwrap();
+    let winner_trade_state = reveal_sealed_bid(
+        &mut context,
+        &auction_house,
+        &winner,
+        &nft,
+        &listing,
+        2 * ONE_SOL,
+        winner_salt,
+        &first.pubkey(),
+        &first_trade_state,
+    )
+    .await
+    .unwrap();
+    reveal_sealed_bid(
+        &mut context,
+        &auction_house,
+        &third,
+        &nft,
+        &listing,
+        3 * ONE_SOL / 2,
+        third_salt,
+        &winner.pubkey(),
+        &winner_trade_state,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: the outbid and the losing bid are refunded their whole collateral
+    assert_eq!(
+        escrow_balance(&mut context, &auction_house, &first.pubkey()).await,
+        first_before + COLLATERAL
+    );
+    assert_eq!(
+        escrow_balance(&mut context, &auction_house, &third.pubkey()).await,
+        third_before + COLLATERAL
+    );
+
+    advance_clock(&mut context, 1, 60).await;
+
+    let (sealed_auction, _) =
+        find_sealed_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (auction_escrow, _) = find_auction_escrow_address(&sealed_auction);
+    let sealed_auction_rent = get_balance(&mut context, &sealed_auction).await;
+    let auction_escrow_rent = get_balance(&mut context, &auction_escrow).await - 2 * ONE_SOL;
+    let seller_before = get_balance(&mut context, &seller.pubkey()).await;
+
+    let settler = Keypair::new();
+    airdrop(&mut context, &settler.pubkey(), ONE_SOL).await;
+    settle_sealed_auction(
+        &mut context,
+        &auction_house,
+        &settler,
+        &winner.pubkey(),
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &winner_trade_state,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: the winner pays the second highest price, less the 2% house fee for the seller
+    let second_price = 3 * ONE_SOL / 2;
+    assert_eq!(
+        get_balance(&mut context, &seller.pubkey()).await - seller_before,
+        second_price - second_price / 50 + sealed_auction_rent + auction_escrow_rent
+    );
+    assert_eq!(
+        escrow_balance(&mut context, &auction_house, &winner.pubkey()).await,
+        winner_before + COLLATERAL - second_price
+    );
+
+    let winner_token_account = get_associated_token_address(&winner.pubkey(), &nft.mint);
+    assert_eq!(
+        get_token_account(&mut context, &winner_token_account)
+            .await
+            .amount,
+        1
+    );
+    assert_eq!(
+        get_auction_house(&mut context, &auction_house.key)
+            .await
+            .open_auction_escrows,
+        0
+    );
+}
+
+#[tokio::test]
+async fn settle_sealed_auction_by_seller() {
+    let (mut context, auction_house, seller, nft, listing) = setup(false).await;
+    let (winner, winner_trade_state) =
+        win(&mut context, &auction_house, &nft, &listing, ONE_SOL).await;
+
+    // NOTE: the seller cranking its own settlement receives both the escrow rent and the trade state rent
+    settle_sealed_auction(
+        &mut context,
+        &auction_house,
+        &seller,
+        &winner.pubkey(),
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &winner_trade_state,
+    )
+    .await
+    .unwrap();
+
+    let winner_token_account = get_associated_token_address(&winner.pubkey(), &nft.mint);
+    assert_eq!(
+        get_token_account(&mut context, &winner_token_account)
+            .await
+            .amount,
+        1
+    );
+    for trade_state in [listing.seller_trade_state, winner_trade_state] {
+        assert!(context
+            .banks_client
+            .get_account(trade_state)
+            .await
+            .unwrap()
+            .is_none());
+    }
+    assert_eq!(
+        get_auction_house(&mut context, &auction_house.key)
+            .await
+            .open_auction_escrows,
+        0
+    );
+}
+
+#[tokio::test]
+async fn settle_sealed_auction_on_house_requiring_sign_off() {
+    let (mut context, auction_house, seller, nft, listing) = setup(false).await;
+    let (winner, winner_trade_state) =
+        win(&mut context, &auction_house, &nft, &listing, ONE_SOL).await;
+
+    // NOTE: settlement stays permissionless once the house requires sign off on its orders
+    update_requires_sign_off(&mut context, &auction_house, true)
+        .await
+        .unwrap();
+
+    let settler = Keypair::new();
+    airdrop(&mut context, &settler.pubkey(), ONE_SOL).await;
+    settle_sealed_auction(
+        &mut context,
+        &auction_house,
+        &settler,
+        &winner.pubkey(),
+        &seller.pubkey(),
+        &nft,
+        &listing,
+        &winner_trade_state,
+    )
+    .await
+    .unwrap();
+
+    let winner_token_account = get_associated_token_address(&winner.pubkey(), &nft.mint);
+    assert_eq!(
+        get_token_account(&mut context, &winner_token_account)
+            .await
+            .amount,
+        1
+    );
+}
+
+#[tokio::test]
+async fn reveal_copied_commitment_fails() {
+    let (mut context, auction_house, _seller, nft, listing) = setup(false).await;
+
+    let (bidder, salt) = commit(&mut context, &auction_house, &listing, ONE_SOL).await;
+
+    // NOTE: the commitment is public once committed, but only opens for the wallet that made it
+    let (sealed_auction, _) =
+        find_sealed_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (sealed_bid, _) = find_sealed_bid_address(&sealed_auction, &bidder.pubkey());
+    let commitment = get_sealed_bid(&mut context, &sealed_bid).await.commitment;
+
+    let copier = Keypair::new();
+    airdrop(&mut context, &copier.pubkey(), 10 * ONE_SOL).await;
+    commit_sealed_bid(
+        &mut context,
+        &auction_house,
+        &copier,
+        &listing,
+        commitment,
+        COLLATERAL,
+    )
+    .await
+    .unwrap();
+
+    advance_clock(&mut context, 1, 60).await;
+
+    let err = reveal_sealed_bid(
+        &mut context,
+        &auction_house,
+        &copier,
+        &nft,
+        &listing,
+        ONE_SOL,
+        salt,
+        &copier.pubkey(),
+        &listing.seller_trade_state,
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::InvalidCommitment);
+
+    reveal_sealed_bid(
+        &mut context,
+        &auction_house,
+        &bidder,
+        &nft,
+        &listing,
+        ONE_SOL,
+        salt,
+        &bidder.pubkey(),
+        &listing.seller_trade_state,
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn close_unrevealed_sealed_bid_refunds_collateral() {
+    let (mut context, auction_house, _seller, _nft, listing) = setup(false).await;
+
+    let (bidder, _salt) = commit(&mut context, &auction_house, &listing, ONE_SOL).await;
+    let escrow_before = escrow_balance(&mut context, &auction_house, &bidder.pubkey()).await;
+
+    // NOTE: the bidder can still reveal until the reveal window is over
+    let err = close_sealed_bid(
+        &mut context,
+        &auction_house,
+        &bidder,
+        &bidder.pubkey(),
+        &listing,
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::AuctionNotEnded);
+
+    advance_clock(&mut context, 1, 120).await;
+
+    let cranker = Keypair::new();
+    airdrop(&mut context, &cranker.pubkey(), ONE_SOL).await;
+    close_sealed_bid(
+        &mut context,
+        &auction_house,
+        &cranker,
+        &bidder.pubkey(),
+        &listing,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        escrow_balance(&mut context, &auction_house, &bidder.pubkey()).await,
+        escrow_before + COLLATERAL
+    );
+    let (sealed_auction, _) =
+        find_sealed_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (sealed_bid, _) = find_sealed_bid_address(&sealed_auction, &bidder.pubkey());
+    assert!(context
+        .banks_client
+        .get_account(sealed_bid)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn cancel_sealed_auction_before_bidding_closes() {
+    let (mut context, auction_house, seller, nft, listing) = setup(false).await;
+
+    cancel_sealed_auction(&mut context, &auction_house, &seller, &nft, &listing)
+        .await
+        .unwrap();
+
+    let (sealed_auction, _) =
+        find_sealed_auction_address(&auction_house.key, &listing.seller_trade_state);
+    assert!(context
+        .banks_client
+        .get_account(sealed_auction)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(get_token_account(&mut context, &nft.token_account)
+        .await
+        .delegate
+        .is_none());
+    assert_eq!(
+        get_auction_house(&mut context, &auction_house.key)
+            .await
+            .open_auction_escrows,
+        0
+    );
+}
+
+#[tokio::test]
+async fn cancel_sealed_auction_during_reveal_fails() {
+    let (mut context, auction_house, seller, nft, listing) = setup(false).await;
+
+    advance_clock(&mut context, 1, 60).await;
+
+    let err = cancel_sealed_auction(&mut context, &auction_house, &seller, &nft, &listing)
+        .await
+        .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::AuctionNotEnded);
+}
@@ -0,0 +1,207 @@
+#![cfg(feature = "test-bpf")]
+pub mod utils;
+
+use auction_house::errors::AuctionHouseError;
+use solana_program_test::*;
+use solana_sdk::signature::{Keypair, Signer};
+use spl_associated_token_account::get_associated_token_address;
+use utils::{
+    helpers::{assert_custom_error, find_escrow_payment_address},
+    setup_functions::*,
+};
+
+const CASH_AMOUNT: u64 = ONE_SOL;
+const SELLER_FEE_BASIS_POINTS: u16 = 200;
+
+async fn setup() -> (
+    ProgramTestContext,
+    TestAuctionHouse,
+    Keypair,
+    Keypair,
+    TestNft,
+    TestNft,
+) {
+    let mut context = auction_house_program_test().start_with_context().await;
+    let auction_house =
+        existing_auction_house_test_context(&mut context, SELLER_FEE_BASIS_POINTS).await;
+
+    let maker = Keypair::new();
+    let taker = Keypair::new();
+    airdrop(&mut context, &maker.pubkey(), 10 * ONE_SOL).await;
+    airdrop(&mut context, &taker.pubkey(), 10 * ONE_SOL).await;
+    let maker_nft = create_nft(&mut context, &maker).await;
+    let taker_nft = create_nft(&mut context, &taker).await;
+
+    (context, auction_house, maker, taker, maker_nft, taker_nft)
+}
+
+#[tokio::test]
+async fn accept_swap_offer_success() {
+    let (mut context, auction_house, maker, taker, maker_nft, taker_nft) = setup().await;
+
+    let swap_offer = make_swap_offer(
+        &mut context,
+        &auction_house,
+        &maker,
+        &maker_nft,
+        &taker_nft.mint,
+        CASH_AMOUNT,
+    )
+    .await
+    .unwrap();
+
+    let (escrow_payment_account, _) =
+        find_escrow_payment_address(&auction_house.key, &maker.pubkey());
+    let escrow_rent = context
+        .banks_client
+        .get_rent()
+        .await
+        .unwrap()
+        .minimum_balance(0);
+    assert_eq!(
+        get_balance(&mut context, &escrow_payment_account).await,
+        escrow_rent + CASH_AMOUNT
+    );
+
+    let treasury_before = get_balance(&mut context, &auction_house.treasury).await;
+    accept_swap_offer(
+        &mut context,
+        &auction_house,
+        &taker,
+        &taker_nft,
+        &maker.pubkey(),
+        &maker_nft,
+        &swap_offer,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        get_token_account(
+            &mut context,
+            &get_associated_token_address(&maker.pubkey(), &taker_nft.mint)
+        )
+        .await
+        .amount,
+        1
+    );
+    assert_eq!(
+        get_token_account(
+            &mut context,
+            &get_associated_token_address(&taker.pubkey(), &maker_nft.mint)
+        )
+        .await
+        .amount,
+        1
+    );
+
+    // NOTE: the house fee only applies to the cash leg, the rest of which went to the taker
+    assert_eq!(
+        get_balance(&mut context, &auction_house.treasury).await - treasury_before,
+        CASH_AMOUNT * SELLER_FEE_BASIS_POINTS as u64 / 10000
+    );
+    assert_eq!(
+        get_balance(&mut context, &escrow_payment_account).await,
+        escrow_rent
+    );
+    assert!(context
+        .banks_client
+        .get_account(swap_offer)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn accept_swap_offer_without_delegation_fails() {
+    let (mut context, auction_house, maker, taker, maker_nft, taker_nft) = setup().await;
+
+    let swap_offer = make_swap_offer(
+        &mut context,
+        &auction_house,
+        &maker,
+        &maker_nft,
+        &taker_nft.mint,
+        0,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: the maker revokes the program signer outside of the auction house
+    process_instruction(
+        &mut context,
+        spl_token::instruction::revoke(
+            &spl_token::id(),
+            &maker_nft.token_account,
+            &maker.pubkey(),
+            &[],
+        )
+        .unwrap(),
+        &maker,
+    )
+    .await
+    .unwrap();
+
+    let err = accept_swap_offer(
+        &mut context,
+        &auction_house,
+        &taker,
+        &taker_nft,
+        &maker.pubkey(),
+        &maker_nft,
+        &swap_offer,
+    )
+    .await
+    .unwrap_err();
+    assert_custom_error(err, AuctionHouseError::BothPartiesNeedToAgreeToSale);
+}
+
+#[tokio::test]
+async fn cancel_swap_offer_success() {
+    let (mut context, auction_house, maker, _taker, maker_nft, taker_nft) = setup().await;
+
+    let swap_offer = make_swap_offer(
+        &mut context,
+        &auction_house,
+        &maker,
+        &maker_nft,
+        &taker_nft.mint,
+        CASH_AMOUNT,
+    )
+    .await
+    .unwrap();
+
+    cancel_swap_offer(
+        &mut context,
+        &auction_house,
+        &maker,
+        &maker_nft,
+        &swap_offer,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: the cash leg stays in the escrow, on top of its rent exemption, until withdrawn
+    let (escrow_payment_account, _) =
+        find_escrow_payment_address(&auction_house.key, &maker.pubkey());
+    let escrow_rent = context
+        .banks_client
+        .get_rent()
+        .await
+        .unwrap()
+        .minimum_balance(0);
+    assert_eq!(
+        get_balance(&mut context, &escrow_payment_account).await,
+        escrow_rent + CASH_AMOUNT
+    );
+    assert!(get_token_account(&mut context, &maker_nft.token_account)
+        .await
+        .delegate
+        .is_none());
+    assert!(context
+        .banks_client
+        .get_account(swap_offer)
+        .await
+        .unwrap()
+        .is_none());
+}
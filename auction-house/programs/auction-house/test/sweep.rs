@@ -0,0 +1,131 @@
+#![cfg(feature = "test-bpf")]
+pub mod utils;
+
+use solana_program_test::*;
+use solana_sdk::signature::{Keypair, Signer};
+use spl_associated_token_account::get_associated_token_address;
+use utils::setup_functions::*;
+
+const PRICE: u64 = ONE_SOL;
+
+async fn setup() -> (
+    ProgramTestContext,
+    TestAuctionHouse,
+    Keypair,
+    Keypair,
+    TestNft,
+    TestNft,
+) {
+    let mut context = auction_house_program_test().start_with_context().await;
+    let auction_house = existing_auction_house_test_context(&mut context, 0).await;
+
+    let seller = Keypair::new();
+    let buyer = Keypair::new();
+    airdrop(&mut context, &seller.pubkey(), 10 * ONE_SOL).await;
+    airdrop(&mut context, &buyer.pubkey(), 10 * ONE_SOL).await;
+
+    let first = create_nft(&mut context, &seller).await;
+    let second = create_nft(&mut context, &seller).await;
+    sell(&mut context, &auction_house, &seller, &first, PRICE)
+        .await
+        .unwrap();
+    sell(&mut context, &auction_house, &seller, &second, PRICE)
+        .await
+        .unwrap();
+
+    (context, auction_house, seller, buyer, first, second)
+}
+
+async fn buyer_amount(context: &mut ProgramTestContext, buyer: &Keypair, nft: &TestNft) -> u64 {
+    let receipt = get_associated_token_address(&buyer.pubkey(), &nft.mint);
+    match context.banks_client.get_account(receipt).await.unwrap() {
+        Some(_) => get_token_account(context, &receipt).await.amount,
+        None => 0,
+    }
+}
+
+#[tokio::test]
+async fn sweep_listings_success() {
+    let (mut context, auction_house, seller, buyer, first, second) = setup().await;
+    let seller_key = seller.pubkey();
+
+    sweep(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &[(&seller_key, &first, PRICE), (&seller_key, &second, PRICE)],
+        2 * PRICE,
+    )
+    .await
+    .unwrap();
+
+    for nft in [&first, &second] {
+        assert_eq!(buyer_amount(&mut context, &buyer, nft).await, 1);
+
+        let listing = find_listing(&auction_house, &seller_key, nft, PRICE);
+        assert!(context
+            .banks_client
+            .get_account(listing.seller_trade_state)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
+
+#[tokio::test]
+async fn sweep_skips_listings_over_budget() {
+    let (mut context, auction_house, seller, buyer, first, second) = setup().await;
+    let seller_key = seller.pubkey();
+
+    sweep(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &[(&seller_key, &first, PRICE), (&seller_key, &second, PRICE)],
+        PRICE + PRICE / 2,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: the second listing no longer fits the budget, so it stays open
+    assert_eq!(buyer_amount(&mut context, &buyer, &first).await, 1);
+    assert_eq!(buyer_amount(&mut context, &buyer, &second).await, 0);
+
+    let listing = find_listing(&auction_house, &seller_key, &second, PRICE);
+    assert!(context
+        .banks_client
+        .get_account(listing.seller_trade_state)
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn sweep_skips_filled_listings() {
+    let (mut context, auction_house, seller, buyer, first, second) = setup().await;
+    let seller_key = seller.pubkey();
+
+    sweep(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &[(&seller_key, &first, PRICE)],
+        PRICE,
+    )
+    .await
+    .unwrap();
+
+    // NOTE: the first listing was filled by the previous sweep, so the budget only covers the second one
+    sweep(
+        &mut context,
+        &auction_house,
+        &buyer,
+        &[(&seller_key, &first, PRICE), (&seller_key, &second, PRICE)],
+        PRICE,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(buyer_amount(&mut context, &buyer, &first).await, 1);
+    assert_eq!(buyer_amount(&mut context, &buyer, &second).await, 1);
+}
@@ -1,4 +1,16 @@
-use auction_house::{constant::MAX_NUM_SCOPES, AuthorityScope};
+use anchor_lang::prelude::Pubkey;
+use auction_house::{
+    constant::{
+        AUCTIONEER, AUCTION_ESCROW, BUNDLE, COLLECTION_OFFER, DUTCH_AUCTION, ENGLISH_AUCTION,
+        FEE_PAYER, MAX_NUM_SCOPES, PREFIX, REFERRER, REVENUE_SPLIT, SEALED_AUCTION, SEALED_BID,
+        SIGNER, SWAP_OFFER, TREASURY, WALLET_STATS,
+    },
+    errors::AuctionHouseError,
+    state::AuthorityScope,
+};
+use solana_sdk::{
+    instruction::InstructionError, transaction::TransactionError, transport::TransportError,
+};
 
 pub fn default_scopes() -> Vec<AuthorityScope> {
     vec![
@@ -19,3 +31,269 @@ pub fn assert_scopes_eq(scopes: Vec<AuthorityScope>, scopes_array: [bool; MAX_NU
         }
     }
 }
+
+pub fn find_auction_house_address(authority: &Pubkey, treasury_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            authority.as_ref(),
+            treasury_mint.as_ref(),
+        ],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_auction_house_fee_account_address(auction_house: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            auction_house.as_ref(),
+            FEE_PAYER.as_bytes(),
+        ],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_auction_house_treasury_address(auction_house: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            auction_house.as_ref(),
+            TREASURY.as_bytes(),
+        ],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_escrow_payment_address(auction_house: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PREFIX.as_bytes(), auction_house.as_ref(), wallet.as_ref()],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_trade_state_address(
+    wallet: &Pubkey,
+    auction_house: &Pubkey,
+    token_account: &Pubkey,
+    treasury_mint: &Pubkey,
+    token_mint: &Pubkey,
+    price: u64,
+    token_size: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            wallet.as_ref(),
+            auction_house.as_ref(),
+            token_account.as_ref(),
+            treasury_mint.as_ref(),
+            token_mint.as_ref(),
+            &price.to_le_bytes(),
+            &token_size.to_le_bytes(),
+        ],
+        &auction_house::id(),
+    )
+}
+
+/// Trade state of a public bid, which is not tied to a token account.
+pub fn find_public_bid_trade_state_address(
+    wallet: &Pubkey,
+    auction_house: &Pubkey,
+    treasury_mint: &Pubkey,
+    token_mint: &Pubkey,
+    price: u64,
+    token_size: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            wallet.as_ref(),
+            auction_house.as_ref(),
+            treasury_mint.as_ref(),
+            token_mint.as_ref(),
+            &price.to_le_bytes(),
+            &token_size.to_le_bytes(),
+        ],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_program_as_signer_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PREFIX.as_bytes(), SIGNER.as_bytes()],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_metadata_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            mpl_token_metadata::state::PREFIX.as_bytes(),
+            mpl_token_metadata::id().as_ref(),
+            mint.as_ref(),
+        ],
+        &mpl_token_metadata::id(),
+    )
+}
+
+pub fn find_english_auction_address(
+    auction_house: &Pubkey,
+    seller_trade_state: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            ENGLISH_AUCTION.as_bytes(),
+            auction_house.as_ref(),
+            seller_trade_state.as_ref(),
+        ],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_dutch_auction_address(
+    auction_house: &Pubkey,
+    seller_trade_state: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            DUTCH_AUCTION.as_bytes(),
+            auction_house.as_ref(),
+            seller_trade_state.as_ref(),
+        ],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_sealed_auction_address(
+    auction_house: &Pubkey,
+    seller_trade_state: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            SEALED_AUCTION.as_bytes(),
+            auction_house.as_ref(),
+            seller_trade_state.as_ref(),
+        ],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_sealed_bid_address(sealed_auction: &Pubkey, bidder: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            SEALED_BID.as_bytes(),
+            sealed_auction.as_ref(),
+            bidder.as_ref(),
+        ],
+        &auction_house::id(),
+    )
+}
+
+/// Auction escrow of an English or sealed auction, or collateral escrow of a sealed bid.
+pub fn find_auction_escrow_address(english_auction: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[AUCTION_ESCROW.as_bytes(), english_auction.as_ref()],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_auctioneer_pda(auction_house: &Pubkey, auctioneer_authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            AUCTIONEER.as_bytes(),
+            auction_house.as_ref(),
+            auctioneer_authority.as_ref(),
+        ],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_referrer_address(auction_house: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[REFERRER.as_bytes(), auction_house.as_ref(), wallet.as_ref()],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_revenue_split_address(auction_house: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[REVENUE_SPLIT.as_bytes(), auction_house.as_ref()],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_collection_offer_address(
+    auction_house: &Pubkey,
+    buyer: &Pubkey,
+    collection: &Pubkey,
+    price: u64,
+    merkle_root: &[u8; 32],
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            COLLECTION_OFFER.as_bytes(),
+            auction_house.as_ref(),
+            buyer.as_ref(),
+            collection.as_ref(),
+            &price.to_le_bytes(),
+            merkle_root,
+        ],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_bundle_address(
+    auction_house: &Pubkey,
+    seller: &Pubkey,
+    bundle_id: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            BUNDLE.as_bytes(),
+            auction_house.as_ref(),
+            seller.as_ref(),
+            &bundle_id.to_le_bytes(),
+        ],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_swap_offer_address(
+    auction_house: &Pubkey,
+    maker: &Pubkey,
+    token_account: &Pubkey,
+    requested_mint: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            SWAP_OFFER.as_bytes(),
+            auction_house.as_ref(),
+            maker.as_ref(),
+            token_account.as_ref(),
+            requested_mint.as_ref(),
+        ],
+        &auction_house::id(),
+    )
+}
+
+pub fn find_wallet_stats_address(auction_house: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            WALLET_STATS.as_bytes(),
+            auction_house.as_ref(),
+            wallet.as_ref(),
+        ],
+        &auction_house::id(),
+    )
+}
+
+pub fn assert_custom_error(err: TransportError, expected: AuctionHouseError) {
+    match err {
+        TransportError::TransactionError(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(code),
+        )) => assert_eq!(code, u32::from(expected)),
+        _ => panic!("unexpected error: {:?}", err),
+    }
+}
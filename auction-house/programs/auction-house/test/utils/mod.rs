@@ -1,2 +1,3 @@
+#![allow(dead_code, clippy::too_many_arguments)]
 pub mod helpers;
 pub mod setup_functions;
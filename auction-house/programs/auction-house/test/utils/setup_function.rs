@@ -1,69 +0,0 @@
-pub fn auction_house_program_test() -> ProgramTest {
-    let mut program = ProgramTest::new("mpl_auction_house", mpl_auction_house::id(), None);
-    program.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
-    program
-}
-
-pub async fn create_auction_house(
-    context: &mut ProgramTestContext,
-    payer_wallet: &Keypair,
-    twd_key: &Pubkey,
-    fwd_key: &Pubkey,
-    t_mint_key: &Pubkey,
-    tdw_ata: &Pubkey,
-    auction_house_key: &Pubkey,
-    auction_house_key_bump: u8,
-    auction_fee_account_key: &Pubkey,
-    auction_fee_account_key_bump: u8,
-    auction_house_treasury_key: &Pubkey,
-    auction_house_treasury_key_bump: u8,
-    seller_fee_basis_points: u16,
-    requires_sign_off: bool,
-    can_change_sale_price: bool,
-) -> StdResult<Pubkey, TransportError> {
-    let accounts = mpl_auction_house::accounts::CreateAuctionHouse {
-        treasury_mint: *t_mint_key,
-        payer: payer_wallet.pubkey(),
-        authority: payer_wallet.pubkey(),
-        fee_withdrawal_destination: *fwd_key,
-        treasury_withdrawal_destination: *tdw_ata,
-        treasury_withdrawal_destination_owner: *twd_key,
-        auction_house: *auction_house_key,
-        auction_house_fee_account: *auction_fee_account_key,
-        auction_house_treasury: *auction_house_treasury_key,
-        token_program: spl_token::id(),
-        system_program: system_program::id(),
-        ata_program: spl_associated_token_account::id(),
-        rent: sysvar::rent::id(),
-    }
-    .to_account_metas(None);
-
-    let data = mpl_auction_house::instruction::CreateAuctionHouse {
-        _bump: auction_house_key_bump,
-        fee_payer_bump: auction_fee_account_key_bump,
-        treasury_bump: auction_house_treasury_key_bump,
-        seller_fee_basis_points,
-        requires_sign_off,
-        can_change_sale_price,
-    }
-    .data();
-
-    let instruction = Instruction {
-        program_id: mpl_auction_house::id(),
-        data,
-        accounts,
-    };
-
-    let tx = Transaction::new_signed_with_payer(
-        &[instruction],
-        Some(&payer_wallet.pubkey()),
-        &[payer_wallet],
-        context.last_blockhash,
-    );
-
-    context
-        .banks_client
-        .process_transaction(tx)
-        .await
-        .map(|_| *auction_house_key)
-}
@@ -0,0 +1,2469 @@
+use std::result::Result as StdResult;
+
+use anchor_lang::{AccountDeserialize, AnchorSerialize, InstructionData, ToAccountMetas};
+use auction_house::{
+    constant::AUCTION_LISTING_PRICE,
+    state::{AuctionHouse, RevenueRecipient, SealedBid, SweepListing},
+};
+use mpl_token_metadata::state::{Collection, Data, Key, Metadata};
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::Transaction,
+    transport::TransportError,
+};
+use spl_associated_token_account::get_associated_token_address;
+
+use super::helpers::*;
+
+/// Lamports a test wallet is funded with.
+pub const ONE_SOL: u64 = 1_000_000_000;
+
+/// Accounts of a native Auction House created by `existing_auction_house_test_context`.
+pub struct TestAuctionHouse {
+    pub authority: Keypair,
+    pub key: Pubkey,
+    pub fee_account: Pubkey,
+    pub treasury: Pubkey,
+}
+
+/// A single token minted to `owner`, with the metadata the Auction House requires.
+pub struct TestNft {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub metadata: Pubkey,
+}
+
+/// Accounts of a listing, at a fixed price or at the auction sentinel price.
+pub struct TestListing {
+    pub seller_trade_state: Pubkey,
+    pub seller_trade_state_bump: u8,
+    pub free_trade_state: Pubkey,
+    pub free_trade_state_bump: u8,
+}
+
+pub fn auction_house_program_test() -> ProgramTest {
+    let mut program = ProgramTest::new("auction_house", auction_house::id(), None);
+    program.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
+    program
+}
+
+pub async fn create_auction_house(
+    context: &mut ProgramTestContext,
+    payer_wallet: &Keypair,
+    twd_key: &Pubkey,
+    fwd_key: &Pubkey,
+    t_mint_key: &Pubkey,
+    tdw_ata: &Pubkey,
+    auction_house_key: &Pubkey,
+    auction_house_key_bump: u8,
+    auction_fee_account_key: &Pubkey,
+    auction_fee_account_key_bump: u8,
+    auction_house_treasury_key: &Pubkey,
+    auction_house_treasury_key_bump: u8,
+    seller_fee_basis_points: u16,
+    requires_sign_off: bool,
+    can_change_sale_price: bool,
+) -> StdResult<Pubkey, TransportError> {
+    let accounts = auction_house::accounts::CreateAuctionHouse {
+        treasury_mint: *t_mint_key,
+        payer: payer_wallet.pubkey(),
+        authority: payer_wallet.pubkey(),
+        fee_withdrawal_destination: *fwd_key,
+        treasury_withdrawal_destination: *tdw_ata,
+        treasury_withdrawal_destination_owner: *twd_key,
+        auction_house: *auction_house_key,
+        auction_house_fee_account: *auction_fee_account_key,
+        auction_house_treasury: *auction_house_treasury_key,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        ata_program: spl_associated_token_account::id(),
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::CreateAuctionHouse {
+        _bump: auction_house_key_bump,
+        fee_payer_bump: auction_fee_account_key_bump,
+        treasury_bump: auction_house_treasury_key_bump,
+        seller_fee_basis_points,
+        requires_sign_off,
+        can_change_sale_price,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer_wallet.pubkey()),
+        &[payer_wallet],
+        context.last_blockhash,
+    );
+
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .map(|_| *auction_house_key)
+}
+
+/// Sends `instruction` paid for and signed by `signer`, so no other account of it signs by accident.
+pub async fn process_instruction(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signer: &Keypair,
+) -> StdResult<(), TransportError> {
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&signer.pubkey()),
+        &[signer],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await
+}
+
+pub async fn airdrop(context: &mut ProgramTestContext, receiver: &Pubkey, amount: u64) {
+    let tx = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &context.payer.pubkey(),
+            receiver,
+            amount,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await.unwrap();
+}
+
+pub async fn get_balance(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    context.banks_client.get_balance(*address).await.unwrap()
+}
+
+pub async fn get_token_account(
+    context: &mut ProgramTestContext,
+    address: &Pubkey,
+) -> spl_token::state::Account {
+    let account = context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap();
+
+    spl_token::state::Account::unpack(&account.data).unwrap()
+}
+
+pub async fn get_auction_house(context: &mut ProgramTestContext, address: &Pubkey) -> AuctionHouse {
+    let account = context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap();
+
+    AuctionHouse::try_deserialize(&mut account.data.as_ref()).unwrap()
+}
+
+pub async fn get_sealed_bid(context: &mut ProgramTestContext, address: &Pubkey) -> SealedBid {
+    let account = context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap();
+
+    SealedBid::try_deserialize(&mut account.data.as_ref()).unwrap()
+}
+
+/// Moves the clock forward, so later orders rest in a later slot and timed auctions can end.
+pub async fn advance_clock(context: &mut ProgramTestContext, slots: u64, seconds: i64) {
+    let mut clock = context
+        .banks_client
+        .get_sysvar::<sysvar::clock::Clock>()
+        .await
+        .unwrap();
+    clock.slot += slots;
+    clock.unix_timestamp += seconds;
+    context.set_sysvar(&clock);
+}
+
+/// Creates a native Auction House with a funded authority distinct from the test payer.
+pub async fn existing_auction_house_test_context(
+    context: &mut ProgramTestContext,
+    seller_fee_basis_points: u16,
+) -> TestAuctionHouse {
+    let authority = Keypair::new();
+    airdrop(context, &authority.pubkey(), 10 * ONE_SOL).await;
+
+    let treasury_mint = spl_token::native_mint::id();
+    let (key, bump) = find_auction_house_address(&authority.pubkey(), &treasury_mint);
+    let (fee_account, fee_account_bump) = find_auction_house_fee_account_address(&key);
+    let (treasury, treasury_bump) = find_auction_house_treasury_address(&key);
+
+    create_auction_house(
+        context,
+        &authority,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &treasury_mint,
+        &authority.pubkey(),
+        &key,
+        bump,
+        &fee_account,
+        fee_account_bump,
+        &treasury,
+        treasury_bump,
+        seller_fee_basis_points,
+        false,
+        false,
+    )
+    .await
+    .unwrap();
+
+    TestAuctionHouse {
+        authority,
+        key,
+        fee_account,
+        treasury,
+    }
+}
+
+/// Delegates `auctioneer_authority` with the default scopes, so trades must go through the `auctioneer_*` handlers.
+pub async fn delegate_auctioneer(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    auctioneer_authority: &Pubkey,
+) -> StdResult<(), TransportError> {
+    let (ah_auctioneer_pda, _) = find_auctioneer_pda(&auction_house.key, auctioneer_authority);
+
+    let accounts = auction_house::accounts::DelegateAuctioneer {
+        auction_house: auction_house.key,
+        authority: auction_house.authority.pubkey(),
+        auctioneer_authority: *auctioneer_authority,
+        ah_auctioneer_pda,
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::DelegateAuctioneer {
+        scopes: default_scopes(),
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, &auction_house.authority).await
+}
+
+pub async fn update_maker_taker_fees(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    maker_fee_basis_points: i16,
+    taker_fee_basis_points: u16,
+) -> StdResult<(), TransportError> {
+    let accounts = auction_house::accounts::UpdateAuctionHouse {
+        treasury_mint: spl_token::native_mint::id(),
+        payer: auction_house.authority.pubkey(),
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        ata_program: spl_associated_token_account::id(),
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::UpdateAuctionHouse {
+        seller_fee_basis_points: None,
+        requires_sign_off: None,
+        can_change_sale_price: None,
+        maker_fee_basis_points: Some(maker_fee_basis_points),
+        taker_fee_basis_points: Some(taker_fee_basis_points),
+        new_authority: None,
+        fee_withdrawal_destination: None,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, &auction_house.authority).await
+}
+
+/// Sets whether every order of the house needs the authority's sign off.
+pub async fn update_requires_sign_off(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    requires_sign_off: bool,
+) -> StdResult<(), TransportError> {
+    let accounts = auction_house::accounts::UpdateAuctionHouse {
+        treasury_mint: spl_token::native_mint::id(),
+        payer: auction_house.authority.pubkey(),
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        ata_program: spl_associated_token_account::id(),
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::UpdateAuctionHouse {
+        seller_fee_basis_points: None,
+        requires_sign_off: Some(requires_sign_off),
+        can_change_sale_price: None,
+        maker_fee_basis_points: None,
+        taker_fee_basis_points: None,
+        new_authority: None,
+        fee_withdrawal_destination: None,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, &auction_house.authority).await
+}
+
+/// Mints a single token to `owner` and creates its metadata without creators or royalties, so sale proceeds
+/// only depend on the Auction House fees.
+pub async fn create_nft(context: &mut ProgramTestContext, owner: &Keypair) -> TestNft {
+    mint_nft(context, owner, 1).await
+}
+
+/// Mints `amount` units of a token like `create_nft`, to be listed and filled in parts.
+pub async fn create_sft(context: &mut ProgramTestContext, owner: &Keypair, amount: u64) -> TestNft {
+    mint_nft(context, owner, amount).await
+}
+
+/// Mints an NFT like `create_nft` to a fresh wallet, for use as a collection.
+pub async fn create_collection(context: &mut ProgramTestContext) -> TestNft {
+    mint_nft(context, &Keypair::new(), 1).await
+}
+
+/// Mints an NFT like `create_nft` as a verified item of `collection`. The verified collection is written into
+/// the metadata directly, which spares the collection a master edition.
+pub async fn create_collection_nft(
+    context: &mut ProgramTestContext,
+    owner: &Keypair,
+    collection: &TestNft,
+) -> TestNft {
+    let nft = mint_nft(context, owner, 1).await;
+    let mut account = context
+        .banks_client
+        .get_account(nft.metadata)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let metadata = Metadata {
+        key: Key::MetadataV1,
+        update_authority: context.payer.pubkey(),
+        mint: nft.mint,
+        data: Data {
+            name: "Test".to_string(),
+            symbol: "TST".to_string(),
+            uri: "https://example.com".to_string(),
+            seller_fee_basis_points: 0,
+            creators: None,
+        },
+        primary_sale_happened: false,
+        is_mutable: true,
+        edition_nonce: None,
+        token_standard: None,
+        collection: Some(Collection {
+            verified: true,
+            key: collection.mint,
+        }),
+        uses: None,
+    };
+    let data = metadata.try_to_vec().unwrap();
+    account.data[..data.len()].copy_from_slice(&data);
+    context.set_account(&nft.metadata, &account.into());
+
+    nft
+}
+
+async fn mint_nft(context: &mut ProgramTestContext, owner: &Keypair, amount: u64) -> TestNft {
+    let mint = Keypair::new();
+    let token_account = get_associated_token_address(&owner.pubkey(), &mint.pubkey());
+    let (metadata, _) = find_metadata_address(&mint.pubkey());
+    let payer = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+
+    let instructions = [
+        system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        spl_token::instruction::initialize_mint(&spl_token::id(), &mint.pubkey(), &payer, None, 0)
+            .unwrap(),
+        spl_associated_token_account::create_associated_token_account(
+            &payer,
+            &owner.pubkey(),
+            &mint.pubkey(),
+        ),
+        spl_token::instruction::mint_to(
+            &spl_token::id(),
+            &mint.pubkey(),
+            &token_account,
+            &payer,
+            &[],
+            amount,
+        )
+        .unwrap(),
+        mpl_token_metadata::instruction::create_metadata_accounts_v2(
+            mpl_token_metadata::id(),
+            metadata,
+            mint.pubkey(),
+            payer,
+            payer,
+            payer,
+            "Test".to_string(),
+            "TST".to_string(),
+            "https://example.com".to_string(),
+            None,
+            0,
+            true,
+            true,
+            None,
+            None,
+        ),
+    ];
+
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer),
+        &[&context.payer, &mint],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    TestNft {
+        mint: mint.pubkey(),
+        token_account,
+        metadata,
+    }
+}
+
+pub async fn sell(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    seller: &Keypair,
+    nft: &TestNft,
+    price: u64,
+) -> StdResult<TestListing, TransportError> {
+    sell_units(context, auction_house, seller, nft, price, 1).await
+}
+
+/// Lists `token_size` units of `nft` for `price` in total.
+pub async fn sell_units(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    seller: &Keypair,
+    nft: &TestNft,
+    price: u64,
+    token_size: u64,
+) -> StdResult<TestListing, TransportError> {
+    let listing = find_listing_of_size(auction_house, &seller.pubkey(), nft, price, token_size);
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+
+    let accounts = auction_house::accounts::Sell {
+        wallet: seller.pubkey(),
+        token_account: nft.token_account,
+        metadata: nft.metadata,
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        seller_trade_state: listing.seller_trade_state,
+        free_seller_trade_state: listing.free_trade_state,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::Sell {
+        trade_state_bump: listing.seller_trade_state_bump,
+        free_trade_state_bump: listing.free_trade_state_bump,
+        program_as_signer_bump,
+        buyer_price: price,
+        token_size,
+        expiry: None,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, seller)
+        .await
+        .map(|_| listing)
+}
+
+/// Bids `price` on the token `nft` held by its seller, funding the buyer escrow from the buyer wallet.
+pub async fn buy(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    nft: &TestNft,
+    price: u64,
+) -> StdResult<Pubkey, TransportError> {
+    buy_with_expiry(context, auction_house, buyer, nft, price, None).await
+}
+
+/// Bids like `buy`, with the bid expiring at `expiry` if given.
+pub async fn buy_with_expiry(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    nft: &TestNft,
+    price: u64,
+    expiry: Option<i64>,
+) -> StdResult<Pubkey, TransportError> {
+    place_bid(context, auction_house, buyer, nft, price, 1, expiry, None).await
+}
+
+/// Bids like `buy` on `token_size` units of `nft` for `price` in total.
+pub async fn buy_units(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    nft: &TestNft,
+    price: u64,
+    token_size: u64,
+) -> StdResult<Pubkey, TransportError> {
+    place_bid(
+        context,
+        auction_house,
+        buyer,
+        nft,
+        price,
+        token_size,
+        None,
+        None,
+    )
+    .await
+}
+
+/// Bids like `buy`, recording the approved referrer of `referrer_wallet`.
+pub async fn buy_with_referrer(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    nft: &TestNft,
+    price: u64,
+    referrer_wallet: &Pubkey,
+) -> StdResult<Pubkey, TransportError> {
+    place_bid(
+        context,
+        auction_house,
+        buyer,
+        nft,
+        price,
+        1,
+        None,
+        Some(referrer_wallet),
+    )
+    .await
+}
+
+async fn place_bid(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    nft: &TestNft,
+    price: u64,
+    token_size: u64,
+    expiry: Option<i64>,
+    referrer_wallet: Option<&Pubkey>,
+) -> StdResult<Pubkey, TransportError> {
+    let treasury_mint = spl_token::native_mint::id();
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let (buyer_trade_state, trade_state_bump) = find_trade_state_address(
+        &buyer.pubkey(),
+        &auction_house.key,
+        &nft.token_account,
+        &treasury_mint,
+        &nft.mint,
+        price,
+        token_size,
+    );
+
+    let mut accounts = auction_house::accounts::Buy {
+        wallet: buyer.pubkey(),
+        payment_account: buyer.pubkey(),
+        transfer_authority: buyer.pubkey(),
+        treasury_mint,
+        token_account: nft.token_account,
+        metadata: nft.metadata,
+        escrow_payment_account,
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        buyer_trade_state,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+    if let Some(referrer_wallet) = referrer_wallet {
+        accounts.push(AccountMeta::new_readonly(
+            find_referrer_address(&auction_house.key, referrer_wallet).0,
+            false,
+        ));
+    }
+
+    let data = auction_house::instruction::Buy {
+        trade_state_bump,
+        escrow_payment_bump,
+        buyer_price: price,
+        token_size,
+        expiry,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, buyer)
+        .await
+        .map(|_| buyer_trade_state)
+}
+
+/// Executes the sale of `nft` between a listing and a bid at `price`, signed and paid for by the buyer.
+pub async fn execute_sale(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    seller: &Pubkey,
+    nft: &TestNft,
+    listing: &TestListing,
+    buyer_trade_state: &Pubkey,
+    price: u64,
+) -> StdResult<(), TransportError> {
+    fill_bid(
+        context,
+        auction_house,
+        buyer,
+        seller,
+        nft,
+        listing,
+        buyer_trade_state,
+        price,
+        true,
+        None,
+    )
+    .await
+}
+
+/// Executes a sale like `execute_sale` without the wallet stats accounts, as clients predating them do.
+pub async fn execute_sale_without_wallet_stats(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    seller: &Pubkey,
+    nft: &TestNft,
+    listing: &TestListing,
+    buyer_trade_state: &Pubkey,
+    price: u64,
+) -> StdResult<(), TransportError> {
+    fill_bid(
+        context,
+        auction_house,
+        buyer,
+        seller,
+        nft,
+        listing,
+        buyer_trade_state,
+        price,
+        false,
+        None,
+    )
+    .await
+}
+
+/// Executes a sale like `execute_sale` for a bid recording the referrer of `referrer_wallet`.
+pub async fn execute_sale_with_referral(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    seller: &Pubkey,
+    nft: &TestNft,
+    listing: &TestListing,
+    buyer_trade_state: &Pubkey,
+    price: u64,
+    referrer_wallet: &Pubkey,
+) -> StdResult<(), TransportError> {
+    fill_bid(
+        context,
+        auction_house,
+        buyer,
+        seller,
+        nft,
+        listing,
+        buyer_trade_state,
+        price,
+        true,
+        Some(referrer_wallet),
+    )
+    .await
+}
+
+async fn fill_bid(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    seller: &Pubkey,
+    nft: &TestNft,
+    listing: &TestListing,
+    buyer_trade_state: &Pubkey,
+    price: u64,
+    track_volume: bool,
+    referrer_wallet: Option<&Pubkey>,
+) -> StdResult<(), TransportError> {
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+
+    let mut accounts = auction_house::accounts::ExecuteSale {
+        buyer: buyer.pubkey(),
+        seller: *seller,
+        token_account: nft.token_account,
+        token_mint: nft.mint,
+        metadata: nft.metadata,
+        treasury_mint: spl_token::native_mint::id(),
+        escrow_payment_account,
+        seller_payment_receipt_account: *seller,
+        buyer_receipt_token_account: get_associated_token_address(&buyer.pubkey(), &nft.mint),
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        auction_house_treasury: auction_house.treasury,
+        buyer_trade_state: *buyer_trade_state,
+        seller_trade_state: listing.seller_trade_state,
+        free_trade_state: listing.free_trade_state,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        ata_program: spl_associated_token_account::id(),
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+    if track_volume {
+        accounts.extend(wallet_stats_accounts(
+            &auction_house.key,
+            &buyer.pubkey(),
+            seller,
+        ));
+    }
+    if let Some(referrer_wallet) = referrer_wallet {
+        // NOTE: native payouts go straight to the referrer wallet
+        accounts.extend([
+            AccountMeta::new_readonly(
+                find_referrer_address(&auction_house.key, referrer_wallet).0,
+                false,
+            ),
+            AccountMeta::new(*referrer_wallet, false),
+            AccountMeta::new(*referrer_wallet, false),
+        ]);
+    }
+
+    let data = auction_house::instruction::ExecuteSale {
+        escrow_payment_bump,
+        _free_trade_state_bump: listing.free_trade_state_bump,
+        program_as_signer_bump,
+        seller_trade_state_bump: listing.seller_trade_state_bump,
+        buyer_price: price,
+        token_size: 1,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, buyer).await
+}
+
+/// Fills `partial_order_size` units of a listing of `token_size` units at `price`, signed and paid for by the
+/// buyer. Returns the listing of the remaining units.
+pub async fn execute_partial_sale(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    seller: &Pubkey,
+    nft: &TestNft,
+    listing: &TestListing,
+    buyer_trade_state: &Pubkey,
+    price: u64,
+    token_size: u64,
+    partial_order_size: u64,
+    partial_order_price: u64,
+) -> StdResult<TestListing, TransportError> {
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+    let remainder = find_listing_of_size(
+        auction_house,
+        seller,
+        nft,
+        price - partial_order_price,
+        token_size - partial_order_size,
+    );
+
+    let mut accounts = auction_house::accounts::ExecutePartialSale {
+        buyer: buyer.pubkey(),
+        seller: *seller,
+        token_account: nft.token_account,
+        token_mint: nft.mint,
+        metadata: nft.metadata,
+        treasury_mint: spl_token::native_mint::id(),
+        escrow_payment_account,
+        seller_payment_receipt_account: *seller,
+        buyer_receipt_token_account: get_associated_token_address(&buyer.pubkey(), &nft.mint),
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        auction_house_treasury: auction_house.treasury,
+        buyer_trade_state: *buyer_trade_state,
+        seller_trade_state: listing.seller_trade_state,
+        free_trade_state: listing.free_trade_state,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        ata_program: spl_associated_token_account::id(),
+        program_as_signer,
+        rent: sysvar::rent::id(),
+        new_seller_trade_state: remainder.seller_trade_state,
+    }
+    .to_account_metas(None);
+    accounts.extend(wallet_stats_accounts(
+        &auction_house.key,
+        &buyer.pubkey(),
+        seller,
+    ));
+
+    let data = auction_house::instruction::ExecutePartialSale {
+        escrow_payment_bump,
+        free_trade_state_bump: listing.free_trade_state_bump,
+        program_as_signer_bump,
+        seller_trade_state_bump: listing.seller_trade_state_bump,
+        buyer_price: price,
+        token_size,
+        partial_order_size,
+        partial_order_price,
+        new_seller_trade_state_bump: remainder.seller_trade_state_bump,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, buyer)
+        .await
+        .map(|_| remainder)
+}
+
+pub async fn sell_english_auction(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    seller: &Keypair,
+    nft: &TestNft,
+    start_time: i64,
+    end_time: i64,
+    reserve_price: u64,
+    min_bid_increment: u64,
+) -> StdResult<TestListing, TransportError> {
+    let listing = find_listing(auction_house, &seller.pubkey(), nft, AUCTION_LISTING_PRICE);
+    let (english_auction, _) =
+        find_english_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (auction_escrow, _) = find_auction_escrow_address(&english_auction);
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+
+    let accounts = auction_house::accounts::SellEnglishAuction {
+        wallet: seller.pubkey(),
+        token_account: nft.token_account,
+        metadata: nft.metadata,
+        authority: auction_house.authority.pubkey(),
+        treasury_mint: spl_token::native_mint::id(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        seller_trade_state: listing.seller_trade_state,
+        free_seller_trade_state: listing.free_trade_state,
+        english_auction,
+        auction_escrow,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::SellEnglishAuction {
+        trade_state_bump: listing.seller_trade_state_bump,
+        free_trade_state_bump: listing.free_trade_state_bump,
+        program_as_signer_bump,
+        token_size: 1,
+        start_time,
+        end_time,
+        reserve_price,
+        min_bid_increment,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, seller)
+        .await
+        .map(|_| listing)
+}
+
+/// Places the first bid of an English auction, so there is no previous bid to release.
+pub async fn bid_english_auction(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    nft: &TestNft,
+    listing: &TestListing,
+    price: u64,
+) -> StdResult<Pubkey, TransportError> {
+    place_english_bid(context, auction_house, buyer, nft, listing, price, None).await
+}
+
+/// Outbids the highest bid of `previous_bidder`, whose trade state rent was paid by its own wallet.
+pub async fn outbid_english_auction(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    nft: &TestNft,
+    listing: &TestListing,
+    price: u64,
+    previous_bidder: &Pubkey,
+    previous_trade_state: &Pubkey,
+) -> StdResult<Pubkey, TransportError> {
+    place_english_bid(
+        context,
+        auction_house,
+        buyer,
+        nft,
+        listing,
+        price,
+        Some((previous_bidder, previous_trade_state)),
+    )
+    .await
+}
+
+async fn place_english_bid(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    nft: &TestNft,
+    listing: &TestListing,
+    price: u64,
+    previous_bid: Option<(&Pubkey, &Pubkey)>,
+) -> StdResult<Pubkey, TransportError> {
+    let treasury_mint = spl_token::native_mint::id();
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let (buyer_trade_state, trade_state_bump) = find_trade_state_address(
+        &buyer.pubkey(),
+        &auction_house.key,
+        &nft.token_account,
+        &treasury_mint,
+        &nft.mint,
+        price,
+        1,
+    );
+    let (english_auction, _) =
+        find_english_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (auction_escrow, _) = find_auction_escrow_address(&english_auction);
+    // NOTE: the previous bid accounts are ignored by a first bid
+    let buyer_key = buyer.pubkey();
+    let (previous_bidder, previous_trade_state) =
+        previous_bid.unwrap_or((&buyer_key, &buyer_trade_state));
+    let (previous_escrow_payment_account, _) =
+        find_escrow_payment_address(&auction_house.key, previous_bidder);
+
+    let accounts = auction_house::accounts::BidEnglishAuction {
+        wallet: buyer.pubkey(),
+        payment_account: buyer.pubkey(),
+        transfer_authority: buyer.pubkey(),
+        treasury_mint,
+        token_account: nft.token_account,
+        metadata: nft.metadata,
+        authority: auction_house.authority.pubkey(),
+        escrow_payment_account,
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        buyer_trade_state,
+        english_auction,
+        auction_escrow,
+        previous_bidder_escrow_payment_account: previous_escrow_payment_account,
+        previous_bidder_trade_state: *previous_trade_state,
+        previous_trade_state_fee_payer: *previous_bidder,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::BidEnglishAuction {
+        trade_state_bump,
+        escrow_payment_bump,
+        buyer_price: price,
+        token_size: 1,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, buyer)
+        .await
+        .map(|_| buyer_trade_state)
+}
+
+/// Settles an ended English auction, cranked and paid for by `settler`.
+pub async fn settle_english_auction(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    settler: &Keypair,
+    buyer: &Pubkey,
+    seller: &Pubkey,
+    nft: &TestNft,
+    listing: &TestListing,
+    buyer_trade_state: &Pubkey,
+) -> StdResult<(), TransportError> {
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, buyer);
+    let (english_auction, _) =
+        find_english_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (auction_escrow, _) = find_auction_escrow_address(&english_auction);
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+
+    let mut accounts = auction_house::accounts::SettleEnglishAuction {
+        buyer: *buyer,
+        seller: *seller,
+        token_account: nft.token_account,
+        token_mint: nft.mint,
+        metadata: nft.metadata,
+        treasury_mint: spl_token::native_mint::id(),
+        escrow_payment_account,
+        seller_payment_receipt_account: *seller,
+        buyer_receipt_token_account: get_associated_token_address(buyer, &nft.mint),
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        auction_house_treasury: auction_house.treasury,
+        buyer_trade_state: *buyer_trade_state,
+        seller_trade_state: listing.seller_trade_state,
+        free_trade_state: listing.free_trade_state,
+        english_auction,
+        auction_escrow,
+        settler: settler.pubkey(),
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        ata_program: spl_associated_token_account::id(),
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+    accounts.extend(wallet_stats_accounts(&auction_house.key, buyer, seller));
+
+    let data = auction_house::instruction::SettleEnglishAuction {
+        escrow_payment_bump,
+        free_trade_state_bump: listing.free_trade_state_bump,
+        program_as_signer_bump,
+        token_size: 1,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, settler).await
+}
+
+/// Cancels an English auction as its seller. `highest_bidder` is only read when the auction has a bid.
+pub async fn cancel_english_auction(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    seller: &Keypair,
+    nft: &TestNft,
+    listing: &TestListing,
+    highest_bidder: &Pubkey,
+    highest_bidder_trade_state: &Pubkey,
+) -> StdResult<(), TransportError> {
+    let (highest_bidder_escrow_payment_account, _) =
+        find_escrow_payment_address(&auction_house.key, highest_bidder);
+    let (english_auction, _) =
+        find_english_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (auction_escrow, _) = find_auction_escrow_address(&english_auction);
+
+    let accounts = auction_house::accounts::CancelEnglishAuction {
+        wallet: seller.pubkey(),
+        token_account: nft.token_account,
+        token_mint: nft.mint,
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        trade_state: listing.seller_trade_state,
+        english_auction,
+        auction_escrow,
+        highest_bidder_escrow_payment_account,
+        highest_bidder_trade_state: *highest_bidder_trade_state,
+        highest_bid_trade_state_fee_payer: *highest_bidder,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::CancelEnglishAuction {}.data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, seller).await
+}
+
+/// Lists `nft` in a Dutch auction decaying from `start_price` to `floor_price` between `start_time` and `end_time`.
+pub async fn sell_dutch_auction(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    seller: &Keypair,
+    nft: &TestNft,
+    start_time: i64,
+    end_time: i64,
+    start_price: u64,
+    floor_price: u64,
+    step_interval: i64,
+) -> StdResult<TestListing, TransportError> {
+    let listing = find_listing(auction_house, &seller.pubkey(), nft, AUCTION_LISTING_PRICE);
+    let (dutch_auction, _) =
+        find_dutch_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+
+    let accounts = auction_house::accounts::SellDutchAuction {
+        wallet: seller.pubkey(),
+        token_account: nft.token_account,
+        metadata: nft.metadata,
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        seller_trade_state: listing.seller_trade_state,
+        free_seller_trade_state: listing.free_trade_state,
+        dutch_auction,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::SellDutchAuction {
+        trade_state_bump: listing.seller_trade_state_bump,
+        free_trade_state_bump: listing.free_trade_state_bump,
+        program_as_signer_bump,
+        token_size: 1,
+        start_time,
+        end_time,
+        start_price,
+        floor_price,
+        step_interval,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, seller)
+        .await
+        .map(|_| listing)
+}
+
+/// Buys a Dutch auction listing at its current price, paying at most `price`.
+pub async fn buy_dutch_auction(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    seller: &Pubkey,
+    nft: &TestNft,
+    listing: &TestListing,
+    price: u64,
+) -> StdResult<(), TransportError> {
+    let treasury_mint = spl_token::native_mint::id();
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let (buyer_trade_state, trade_state_bump) = find_trade_state_address(
+        &buyer.pubkey(),
+        &auction_house.key,
+        &nft.token_account,
+        &treasury_mint,
+        &nft.mint,
+        price,
+        1,
+    );
+    let (dutch_auction, _) =
+        find_dutch_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+
+    let accounts = auction_house::accounts::BuyDutchAuction {
+        wallet: buyer.pubkey(),
+        payment_account: buyer.pubkey(),
+        transfer_authority: buyer.pubkey(),
+        seller: *seller,
+        token_account: nft.token_account,
+        token_mint: nft.mint,
+        metadata: nft.metadata,
+        treasury_mint,
+        escrow_payment_account,
+        seller_payment_receipt_account: *seller,
+        buyer_receipt_token_account: get_associated_token_address(&buyer.pubkey(), &nft.mint),
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        auction_house_treasury: auction_house.treasury,
+        buyer_trade_state,
+        seller_trade_state: listing.seller_trade_state,
+        free_trade_state: listing.free_trade_state,
+        dutch_auction,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        ata_program: spl_associated_token_account::id(),
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::BuyDutchAuction {
+        trade_state_bump,
+        escrow_payment_bump,
+        free_trade_state_bump: listing.free_trade_state_bump,
+        program_as_signer_bump,
+        buyer_price: price,
+        token_size: 1,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, buyer).await
+}
+
+pub async fn sell_sealed_auction(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    seller: &Keypair,
+    nft: &TestNft,
+    bid_end_time: i64,
+    reveal_end_time: i64,
+    reserve_price: u64,
+    vickrey: bool,
+) -> StdResult<TestListing, TransportError> {
+    let listing = find_listing(auction_house, &seller.pubkey(), nft, AUCTION_LISTING_PRICE);
+    let (sealed_auction, _) =
+        find_sealed_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (auction_escrow, _) = find_auction_escrow_address(&sealed_auction);
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+
+    let accounts = auction_house::accounts::SellSealedAuction {
+        wallet: seller.pubkey(),
+        token_account: nft.token_account,
+        metadata: nft.metadata,
+        authority: auction_house.authority.pubkey(),
+        treasury_mint: spl_token::native_mint::id(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        seller_trade_state: listing.seller_trade_state,
+        free_seller_trade_state: listing.free_trade_state,
+        sealed_auction,
+        auction_escrow,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::SellSealedAuction {
+        trade_state_bump: listing.seller_trade_state_bump,
+        free_trade_state_bump: listing.free_trade_state_bump,
+        program_as_signer_bump,
+        token_size: 1,
+        bid_end_time,
+        reveal_end_time,
+        reserve_price,
+        vickrey,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, seller)
+        .await
+        .map(|_| listing)
+}
+
+/// Commits `commitment` to a sealed auction, depositing `collateral` from the bidder wallet.
+pub async fn commit_sealed_bid(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    bidder: &Keypair,
+    listing: &TestListing,
+    commitment: [u8; 32],
+    collateral: u64,
+) -> StdResult<(), TransportError> {
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, &bidder.pubkey());
+    let (sealed_auction, _) =
+        find_sealed_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (sealed_bid, _) = find_sealed_bid_address(&sealed_auction, &bidder.pubkey());
+    let (bid_escrow, _) = find_auction_escrow_address(&sealed_bid);
+
+    let accounts = auction_house::accounts::CommitSealedBid {
+        wallet: bidder.pubkey(),
+        payment_account: bidder.pubkey(),
+        transfer_authority: bidder.pubkey(),
+        escrow_payment_account,
+        treasury_mint: spl_token::native_mint::id(),
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        sealed_auction,
+        sealed_bid,
+        bid_escrow,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::CommitSealedBid {
+        escrow_payment_bump,
+        commitment,
+        collateral,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, bidder).await
+}
+
+/// Reveals the sealed bid of `bidder`. `previous_bidder` is only read when the reveal outbids a leading bid.
+pub async fn reveal_sealed_bid(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    bidder: &Keypair,
+    nft: &TestNft,
+    listing: &TestListing,
+    price: u64,
+    salt: [u8; 32],
+    previous_bidder: &Pubkey,
+    previous_bidder_trade_state: &Pubkey,
+) -> StdResult<Pubkey, TransportError> {
+    let treasury_mint = spl_token::native_mint::id();
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, &bidder.pubkey());
+    let (buyer_trade_state, trade_state_bump) = find_trade_state_address(
+        &bidder.pubkey(),
+        &auction_house.key,
+        &nft.token_account,
+        &treasury_mint,
+        &nft.mint,
+        price,
+        1,
+    );
+    let (sealed_auction, _) =
+        find_sealed_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (sealed_bid, _) = find_sealed_bid_address(&sealed_auction, &bidder.pubkey());
+    let (bid_escrow, _) = find_auction_escrow_address(&sealed_bid);
+    let (auction_escrow, _) = find_auction_escrow_address(&sealed_auction);
+    let (previous_bidder_escrow_payment_account, _) =
+        find_escrow_payment_address(&auction_house.key, previous_bidder);
+
+    let accounts = auction_house::accounts::RevealSealedBid {
+        wallet: bidder.pubkey(),
+        payment_account: bidder.pubkey(),
+        transfer_authority: bidder.pubkey(),
+        treasury_mint,
+        token_account: nft.token_account,
+        metadata: nft.metadata,
+        authority: auction_house.authority.pubkey(),
+        escrow_payment_account,
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        buyer_trade_state,
+        sealed_auction,
+        sealed_bid,
+        bid_escrow,
+        auction_escrow,
+        previous_bidder_escrow_payment_account,
+        previous_bidder_trade_state: *previous_bidder_trade_state,
+        previous_trade_state_fee_payer: *previous_bidder,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::RevealSealedBid {
+        trade_state_bump,
+        escrow_payment_bump,
+        price,
+        salt,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, bidder)
+        .await
+        .map(|_| buyer_trade_state)
+}
+
+/// Settles a sealed auction after its reveal window, cranked and paid for by `settler`.
+pub async fn settle_sealed_auction(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    settler: &Keypair,
+    buyer: &Pubkey,
+    seller: &Pubkey,
+    nft: &TestNft,
+    listing: &TestListing,
+    buyer_trade_state: &Pubkey,
+) -> StdResult<(), TransportError> {
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, buyer);
+    let (sealed_auction, _) =
+        find_sealed_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (auction_escrow, _) = find_auction_escrow_address(&sealed_auction);
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+
+    let mut accounts = auction_house::accounts::SettleSealedAuction {
+        buyer: *buyer,
+        seller: *seller,
+        token_account: nft.token_account,
+        token_mint: nft.mint,
+        metadata: nft.metadata,
+        treasury_mint: spl_token::native_mint::id(),
+        escrow_payment_account,
+        seller_payment_receipt_account: *seller,
+        buyer_receipt_token_account: get_associated_token_address(buyer, &nft.mint),
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        auction_house_treasury: auction_house.treasury,
+        buyer_trade_state: *buyer_trade_state,
+        seller_trade_state: listing.seller_trade_state,
+        free_trade_state: listing.free_trade_state,
+        sealed_auction,
+        auction_escrow,
+        settler: settler.pubkey(),
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        ata_program: spl_associated_token_account::id(),
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+    accounts.extend(wallet_stats_accounts(&auction_house.key, buyer, seller));
+
+    let data = auction_house::instruction::SettleSealedAuction {
+        escrow_payment_bump,
+        free_trade_state_bump: listing.free_trade_state_bump,
+        program_as_signer_bump,
+        token_size: 1,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, settler).await
+}
+
+/// Cancels a sealed auction without a revealed bid as its seller.
+pub async fn cancel_sealed_auction(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    seller: &Keypair,
+    nft: &TestNft,
+    listing: &TestListing,
+) -> StdResult<(), TransportError> {
+    let (highest_bidder_escrow_payment_account, _) =
+        find_escrow_payment_address(&auction_house.key, &seller.pubkey());
+    let (sealed_auction, _) =
+        find_sealed_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (auction_escrow, _) = find_auction_escrow_address(&sealed_auction);
+
+    let accounts = auction_house::accounts::CancelSealedAuction {
+        wallet: seller.pubkey(),
+        token_account: nft.token_account,
+        token_mint: nft.mint,
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        trade_state: listing.seller_trade_state,
+        sealed_auction,
+        auction_escrow,
+        highest_bidder_escrow_payment_account,
+        highest_bidder_trade_state: listing.seller_trade_state,
+        highest_bid_trade_state_fee_payer: seller.pubkey(),
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::CancelSealedAuction {}.data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, seller).await
+}
+
+/// Closes the unrevealed sealed bid of `bidder`, cranked and paid for by `signer`.
+pub async fn close_sealed_bid(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    signer: &Keypair,
+    bidder: &Pubkey,
+    listing: &TestListing,
+) -> StdResult<(), TransportError> {
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, bidder);
+    let (sealed_auction, _) =
+        find_sealed_auction_address(&auction_house.key, &listing.seller_trade_state);
+    let (sealed_bid, _) = find_sealed_bid_address(&sealed_auction, bidder);
+    let (bid_escrow, _) = find_auction_escrow_address(&sealed_bid);
+
+    let accounts = auction_house::accounts::CloseSealedBid {
+        bidder: *bidder,
+        escrow_payment_account,
+        auction_house: auction_house.key,
+        sealed_auction,
+        sealed_bid,
+        bid_escrow,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::CloseSealedBid {
+        escrow_payment_bump,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, signer).await
+}
+
+/// Buyer and seller wallet stats addresses, passed to a sale tracking volume after the creator accounts.
+fn wallet_stats_accounts(
+    auction_house: &Pubkey,
+    buyer: &Pubkey,
+    seller: &Pubkey,
+) -> [AccountMeta; 2] {
+    [
+        AccountMeta::new(find_wallet_stats_address(auction_house, buyer).0, false),
+        AccountMeta::new(find_wallet_stats_address(auction_house, seller).0, false),
+    ]
+}
+
+pub fn find_listing(
+    auction_house: &TestAuctionHouse,
+    seller: &Pubkey,
+    nft: &TestNft,
+    price: u64,
+) -> TestListing {
+    find_listing_of_size(auction_house, seller, nft, price, 1)
+}
+
+/// Listing addresses of `token_size` units of `nft` for `price` in total.
+pub fn find_listing_of_size(
+    auction_house: &TestAuctionHouse,
+    seller: &Pubkey,
+    nft: &TestNft,
+    price: u64,
+    token_size: u64,
+) -> TestListing {
+    let treasury_mint = spl_token::native_mint::id();
+    let (seller_trade_state, seller_trade_state_bump) = find_trade_state_address(
+        seller,
+        &auction_house.key,
+        &nft.token_account,
+        &treasury_mint,
+        &nft.mint,
+        price,
+        token_size,
+    );
+    let (free_trade_state, free_trade_state_bump) = find_trade_state_address(
+        seller,
+        &auction_house.key,
+        &nft.token_account,
+        &treasury_mint,
+        &nft.mint,
+        0,
+        token_size,
+    );
+
+    TestListing {
+        seller_trade_state,
+        seller_trade_state_bump,
+        free_trade_state,
+        free_trade_state_bump,
+    }
+}
+
+pub async fn close_auction_house(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+) -> StdResult<(), TransportError> {
+    let accounts = auction_house::accounts::CloseAuctionHouse {
+        treasury_mint: spl_token::native_mint::id(),
+        authority: auction_house.authority.pubkey(),
+        fee_withdrawal_destination: auction_house.authority.pubkey(),
+        treasury_withdrawal_destination: auction_house.authority.pubkey(),
+        auction_house_fee_account: auction_house.fee_account,
+        auction_house_treasury: auction_house.treasury,
+        auction_house: auction_house.key,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data: auction_house::instruction::CloseAuctionHouse {}.data(),
+        accounts,
+    };
+
+    process_instruction(context, instruction, &auction_house.authority).await
+}
+
+/// Closes `account` of the closed `auction_house`, signed and paid for by `signer`.
+pub async fn close_account_of_closed_auction_house(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    account: &Pubkey,
+    rent_destination: &Pubkey,
+    signer: &Keypair,
+) -> StdResult<(), TransportError> {
+    let accounts = auction_house::accounts::CloseAccountOfClosedAuctionHouse {
+        treasury_mint: spl_token::native_mint::id(),
+        creator: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        account: *account,
+        rent_destination: *rent_destination,
+    }
+    .to_account_metas(None);
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data: auction_house::instruction::CloseAccountOfClosedAuctionHouse {}.data(),
+        accounts,
+    };
+
+    process_instruction(context, instruction, signer).await
+}
+
+/// Approves `wallet` as a referrer paid `share_basis_points` of the house fee.
+pub async fn add_referrer(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    wallet: &Pubkey,
+    share_basis_points: u16,
+) -> StdResult<Pubkey, TransportError> {
+    let (referrer, _) = find_referrer_address(&auction_house.key, wallet);
+
+    let accounts = auction_house::accounts::AddReferrer {
+        payer: auction_house.authority.pubkey(),
+        authority: auction_house.authority.pubkey(),
+        wallet: *wallet,
+        auction_house: auction_house.key,
+        referrer,
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data: auction_house::instruction::AddReferrer { share_basis_points }.data(),
+        accounts,
+    };
+
+    process_instruction(context, instruction, &auction_house.authority)
+        .await
+        .map(|_| referrer)
+}
+
+pub async fn remove_referrer(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    referrer: &Pubkey,
+) -> StdResult<(), TransportError> {
+    let accounts = auction_house::accounts::RemoveReferrer {
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        referrer: *referrer,
+    }
+    .to_account_metas(None);
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data: auction_house::instruction::RemoveReferrer {}.data(),
+        accounts,
+    };
+
+    process_instruction(context, instruction, &auction_house.authority).await
+}
+
+/// Splits the treasury withdrawals of `auction_house` across `recipients`.
+pub async fn create_revenue_split(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    recipients: Vec<RevenueRecipient>,
+) -> StdResult<Pubkey, TransportError> {
+    let (revenue_split, _) = find_revenue_split_address(&auction_house.key);
+
+    let accounts = auction_house::accounts::CreateRevenueSplit {
+        payer: auction_house.authority.pubkey(),
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        revenue_split,
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data: auction_house::instruction::CreateRevenueSplit { recipients }.data(),
+        accounts,
+    };
+
+    process_instruction(context, instruction, &auction_house.authority)
+        .await
+        .map(|_| revenue_split)
+}
+
+/// Pays `amount` of the native treasury out to the revenue split `recipients`, cranked by `payer`.
+pub async fn withdraw_from_treasury_split(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    payer: &Keypair,
+    recipients: &[Pubkey],
+    amount: u64,
+) -> StdResult<(), TransportError> {
+    let mut accounts = auction_house::accounts::WithdrawFromTreasurySplit {
+        payer: payer.pubkey(),
+        treasury_mint: spl_token::native_mint::id(),
+        auction_house_treasury: auction_house.treasury,
+        auction_house: auction_house.key,
+        revenue_split: find_revenue_split_address(&auction_house.key).0,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        ata_program: spl_associated_token_account::id(),
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        recipients
+            .iter()
+            .map(|recipient| AccountMeta::new(*recipient, false)),
+    );
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data: auction_house::instruction::WithdrawFromTreasurySplit { amount }.data(),
+        accounts,
+    };
+
+    process_instruction(context, instruction, payer).await
+}
+
+pub async fn remove_revenue_split(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+) -> StdResult<(), TransportError> {
+    let accounts = auction_house::accounts::RemoveRevenueSplit {
+        authority: auction_house.authority.pubkey(),
+        treasury_mint: spl_token::native_mint::id(),
+        auction_house_treasury: auction_house.treasury,
+        auction_house: auction_house.key,
+        revenue_split: find_revenue_split_address(&auction_house.key).0,
+    }
+    .to_account_metas(None);
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data: auction_house::instruction::RemoveRevenueSplit {}.data(),
+        accounts,
+    };
+
+    process_instruction(context, instruction, &auction_house.authority).await
+}
+
+/// Offers `price` for each of up to `quantity` items of `collection`, funding the escrow from the buyer wallet.
+pub async fn make_collection_offer(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    collection: &Pubkey,
+    price: u64,
+    quantity: u64,
+    merkle_root: [u8; 32],
+) -> StdResult<Pubkey, TransportError> {
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let (collection_offer, _) = find_collection_offer_address(
+        &auction_house.key,
+        &buyer.pubkey(),
+        collection,
+        price,
+        &merkle_root,
+    );
+
+    let accounts = auction_house::accounts::MakeCollectionOffer {
+        wallet: buyer.pubkey(),
+        payment_account: buyer.pubkey(),
+        transfer_authority: buyer.pubkey(),
+        escrow_payment_account,
+        treasury_mint: spl_token::native_mint::id(),
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        collection_offer,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::MakeCollectionOffer {
+        escrow_payment_bump,
+        collection: *collection,
+        price,
+        quantity,
+        merkle_root,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, buyer)
+        .await
+        .map(|_| collection_offer)
+}
+
+/// Sells `nft` into the collection offer of `buyer` at `price`, proving its mint with `proof` for trait offers.
+pub async fn accept_collection_offer(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    seller: &Keypair,
+    nft: &TestNft,
+    buyer: &Pubkey,
+    collection_offer: &Pubkey,
+    price: u64,
+    proof: Vec<[u8; 32]>,
+) -> StdResult<(), TransportError> {
+    let treasury_mint = spl_token::native_mint::id();
+    let listing = find_listing(auction_house, &seller.pubkey(), nft, price);
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, buyer);
+    let (buyer_trade_state, buyer_trade_state_bump) = find_public_bid_trade_state_address(
+        buyer,
+        &auction_house.key,
+        &treasury_mint,
+        &nft.mint,
+        price,
+        1,
+    );
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+
+    let mut accounts = auction_house::accounts::AcceptCollectionOffer {
+        wallet: seller.pubkey(),
+        token_account: nft.token_account,
+        token_mint: nft.mint,
+        metadata: nft.metadata,
+        buyer: *buyer,
+        treasury_mint,
+        escrow_payment_account,
+        seller_payment_receipt_account: seller.pubkey(),
+        buyer_receipt_token_account: get_associated_token_address(buyer, &nft.mint),
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        auction_house_treasury: auction_house.treasury,
+        buyer_trade_state,
+        seller_trade_state: listing.seller_trade_state,
+        free_seller_trade_state: listing.free_trade_state,
+        collection_offer: *collection_offer,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        ata_program: spl_associated_token_account::id(),
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+    accounts.extend(wallet_stats_accounts(
+        &auction_house.key,
+        buyer,
+        &seller.pubkey(),
+    ));
+
+    let data = auction_house::instruction::AcceptCollectionOffer {
+        trade_state_bump: listing.seller_trade_state_bump,
+        free_trade_state_bump: listing.free_trade_state_bump,
+        buyer_trade_state_bump,
+        escrow_payment_bump,
+        program_as_signer_bump,
+        proof,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, seller).await
+}
+
+pub async fn cancel_collection_offer(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    collection_offer: &Pubkey,
+) -> StdResult<(), TransportError> {
+    let accounts = auction_house::accounts::CancelCollectionOffer {
+        wallet: buyer.pubkey(),
+        auction_house: auction_house.key,
+        collection_offer: *collection_offer,
+    }
+    .to_account_metas(None);
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data: auction_house::instruction::CancelCollectionOffer {}.data(),
+        accounts,
+    };
+
+    process_instruction(context, instruction, buyer).await
+}
+
+/// Lists `nfts` together as bundle `bundle_id`, each item at its entry of `allocations`.
+pub async fn list_bundle(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    seller: &Keypair,
+    nfts: &[&TestNft],
+    bundle_id: u64,
+    allocations: Vec<u64>,
+    expiry: Option<i64>,
+) -> StdResult<Pubkey, TransportError> {
+    let (bundle, _) = find_bundle_address(&auction_house.key, &seller.pubkey(), bundle_id);
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+
+    let mut accounts = auction_house::accounts::ListBundle {
+        wallet: seller.pubkey(),
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        bundle,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    for (nft, allocation) in nfts.iter().zip(allocations.iter()) {
+        let listing = find_listing(auction_house, &seller.pubkey(), nft, *allocation);
+        accounts.extend([
+            AccountMeta::new(nft.token_account, false),
+            AccountMeta::new_readonly(nft.metadata, false),
+            AccountMeta::new(listing.seller_trade_state, false),
+            AccountMeta::new(listing.free_trade_state, false),
+        ]);
+    }
+
+    let data = auction_house::instruction::ListBundle {
+        program_as_signer_bump,
+        bundle_id,
+        price: allocations.iter().sum(),
+        allocations,
+        expiry,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, seller)
+        .await
+        .map(|_| bundle)
+}
+
+/// Buys bundle `bundle_id` of `seller`, whose items are `nfts` at `allocations`, funding the escrow from the buyer
+/// wallet.
+pub async fn buy_bundle(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    seller: &Pubkey,
+    nfts: &[&TestNft],
+    bundle_id: u64,
+    allocations: &[u64],
+) -> StdResult<(), TransportError> {
+    let treasury_mint = spl_token::native_mint::id();
+    let (bundle, _) = find_bundle_address(&auction_house.key, seller, bundle_id);
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+
+    let mut accounts = auction_house::accounts::BuyBundle {
+        wallet: buyer.pubkey(),
+        payment_account: buyer.pubkey(),
+        transfer_authority: buyer.pubkey(),
+        seller: *seller,
+        treasury_mint,
+        escrow_payment_account,
+        seller_payment_receipt_account: *seller,
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        auction_house_treasury: auction_house.treasury,
+        bundle,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        ata_program: spl_associated_token_account::id(),
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    for (nft, allocation) in nfts.iter().zip(allocations.iter()) {
+        let listing = find_listing(auction_house, seller, nft, *allocation);
+        let (buyer_trade_state, _) = find_trade_state_address(
+            &buyer.pubkey(),
+            &auction_house.key,
+            &nft.token_account,
+            &treasury_mint,
+            &nft.mint,
+            *allocation,
+            1,
+        );
+        accounts.extend([
+            AccountMeta::new(nft.token_account, false),
+            AccountMeta::new_readonly(nft.mint, false),
+            AccountMeta::new_readonly(nft.metadata, false),
+            AccountMeta::new(listing.seller_trade_state, false),
+            AccountMeta::new(listing.free_trade_state, false),
+            AccountMeta::new(buyer_trade_state, false),
+            AccountMeta::new(
+                get_associated_token_address(&buyer.pubkey(), &nft.mint),
+                false,
+            ),
+        ]);
+        accounts.extend(wallet_stats_accounts(
+            &auction_house.key,
+            &buyer.pubkey(),
+            seller,
+        ));
+    }
+
+    let data = auction_house::instruction::BuyBundle {
+        escrow_payment_bump,
+        program_as_signer_bump,
+        bundle_id,
+        referrer: None,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, buyer).await
+}
+
+pub async fn cancel_bundle(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    seller: &Keypair,
+    nfts: &[&TestNft],
+    bundle_id: u64,
+    allocations: &[u64],
+) -> StdResult<(), TransportError> {
+    let (bundle, _) = find_bundle_address(&auction_house.key, &seller.pubkey(), bundle_id);
+
+    let mut accounts = auction_house::accounts::CancelBundle {
+        wallet: seller.pubkey(),
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        bundle,
+        token_program: spl_token::id(),
+    }
+    .to_account_metas(None);
+
+    for (nft, allocation) in nfts.iter().zip(allocations.iter()) {
+        let listing = find_listing(auction_house, &seller.pubkey(), nft, *allocation);
+        accounts.extend([
+            AccountMeta::new(nft.token_account, false),
+            AccountMeta::new(nft.mint, false),
+            AccountMeta::new(listing.seller_trade_state, false),
+        ]);
+    }
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data: auction_house::instruction::CancelBundle { bundle_id }.data(),
+        accounts,
+    };
+
+    process_instruction(context, instruction, seller).await
+}
+
+/// Offers `nft` plus `cash_amount` for a token of `requested_mint`.
+pub async fn make_swap_offer(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    maker: &Keypair,
+    nft: &TestNft,
+    requested_mint: &Pubkey,
+    cash_amount: u64,
+) -> StdResult<Pubkey, TransportError> {
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, &maker.pubkey());
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+    let (swap_offer, _) = find_swap_offer_address(
+        &auction_house.key,
+        &maker.pubkey(),
+        &nft.token_account,
+        requested_mint,
+    );
+
+    let accounts = auction_house::accounts::MakeSwapOffer {
+        wallet: maker.pubkey(),
+        payment_account: maker.pubkey(),
+        transfer_authority: maker.pubkey(),
+        token_account: nft.token_account,
+        metadata: nft.metadata,
+        requested_mint: *requested_mint,
+        escrow_payment_account,
+        treasury_mint: spl_token::native_mint::id(),
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        swap_offer,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::MakeSwapOffer {
+        escrow_payment_bump,
+        program_as_signer_bump,
+        cash_amount,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, maker)
+        .await
+        .map(|_| swap_offer)
+}
+
+/// Accepts the swap offer of `maker` for `maker_nft`, giving `taker_nft` in exchange.
+pub async fn accept_swap_offer(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    taker: &Keypair,
+    taker_nft: &TestNft,
+    maker: &Pubkey,
+    maker_nft: &TestNft,
+    swap_offer: &Pubkey,
+) -> StdResult<(), TransportError> {
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, maker);
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+
+    let accounts = auction_house::accounts::AcceptSwapOffer {
+        wallet: taker.pubkey(),
+        token_account: taker_nft.token_account,
+        maker: *maker,
+        maker_token_account: maker_nft.token_account,
+        offered_mint: maker_nft.mint,
+        requested_mint: taker_nft.mint,
+        maker_receipt_token_account: get_associated_token_address(maker, &taker_nft.mint),
+        taker_receipt_token_account: get_associated_token_address(&taker.pubkey(), &maker_nft.mint),
+        treasury_mint: spl_token::native_mint::id(),
+        escrow_payment_account,
+        taker_payment_receipt_account: taker.pubkey(),
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        auction_house_treasury: auction_house.treasury,
+        swap_offer: *swap_offer,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        ata_program: spl_associated_token_account::id(),
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    let data = auction_house::instruction::AcceptSwapOffer {
+        escrow_payment_bump,
+        program_as_signer_bump,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, taker).await
+}
+
+pub async fn cancel_swap_offer(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    maker: &Keypair,
+    nft: &TestNft,
+    swap_offer: &Pubkey,
+) -> StdResult<(), TransportError> {
+    let accounts = auction_house::accounts::CancelSwapOffer {
+        wallet: maker.pubkey(),
+        token_account: nft.token_account,
+        auction_house: auction_house.key,
+        swap_offer: *swap_offer,
+        token_program: spl_token::id(),
+    }
+    .to_account_metas(None);
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data: auction_house::instruction::CancelSwapOffer {}.data(),
+        accounts,
+    };
+
+    process_instruction(context, instruction, maker).await
+}
+
+/// Sweeps `listings` of `(seller, nft, price)` for at most `max_total`.
+pub async fn sweep(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    buyer: &Keypair,
+    listings: &[(&Pubkey, &TestNft, u64)],
+    max_total: u64,
+) -> StdResult<(), TransportError> {
+    let treasury_mint = spl_token::native_mint::id();
+    let (escrow_payment_account, escrow_payment_bump) =
+        find_escrow_payment_address(&auction_house.key, &buyer.pubkey());
+    let (program_as_signer, program_as_signer_bump) = find_program_as_signer_address();
+
+    let mut accounts = auction_house::accounts::Sweep {
+        wallet: buyer.pubkey(),
+        payment_account: buyer.pubkey(),
+        transfer_authority: buyer.pubkey(),
+        treasury_mint,
+        escrow_payment_account,
+        authority: auction_house.authority.pubkey(),
+        auction_house: auction_house.key,
+        auction_house_fee_account: auction_house.fee_account,
+        auction_house_treasury: auction_house.treasury,
+        token_program: spl_token::id(),
+        system_program: system_program::id(),
+        ata_program: spl_associated_token_account::id(),
+        program_as_signer,
+        rent: sysvar::rent::id(),
+    }
+    .to_account_metas(None);
+
+    let mut sweep_listings = Vec::new();
+    for (seller, nft, price) in listings.iter() {
+        let listing = find_listing(auction_house, seller, nft, *price);
+        let (buyer_trade_state, _) = find_trade_state_address(
+            &buyer.pubkey(),
+            &auction_house.key,
+            &nft.token_account,
+            &treasury_mint,
+            &nft.mint,
+            *price,
+            1,
+        );
+        accounts.extend([
+            AccountMeta::new(**seller, false),
+            AccountMeta::new(nft.token_account, false),
+            AccountMeta::new_readonly(nft.mint, false),
+            AccountMeta::new_readonly(nft.metadata, false),
+            AccountMeta::new(listing.seller_trade_state, false),
+            AccountMeta::new(listing.free_trade_state, false),
+            AccountMeta::new(buyer_trade_state, false),
+            AccountMeta::new(**seller, false),
+            AccountMeta::new(
+                get_associated_token_address(&buyer.pubkey(), &nft.mint),
+                false,
+            ),
+        ]);
+        accounts.extend(wallet_stats_accounts(
+            &auction_house.key,
+            &buyer.pubkey(),
+            seller,
+        ));
+        sweep_listings.push(SweepListing {
+            price: *price,
+            token_size: 1,
+        });
+    }
+
+    let data = auction_house::instruction::Sweep {
+        escrow_payment_bump,
+        program_as_signer_bump,
+        max_total,
+        listings: sweep_listings,
+        referrer: None,
+    }
+    .data();
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data,
+        accounts,
+    };
+
+    process_instruction(context, instruction, buyer).await
+}
+
+/// Starts tracking the volume of `wallet`, paid for by `payer`.
+pub async fn create_wallet_stats(
+    context: &mut ProgramTestContext,
+    auction_house: &TestAuctionHouse,
+    payer: &Keypair,
+    wallet: &Pubkey,
+) -> StdResult<Pubkey, TransportError> {
+    let (wallet_stats, _) = find_wallet_stats_address(&auction_house.key, wallet);
+
+    let accounts = auction_house::accounts::CreateWalletStats {
+        payer: payer.pubkey(),
+        wallet: *wallet,
+        auction_house: auction_house.key,
+        wallet_stats,
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+
+    let instruction = Instruction {
+        program_id: auction_house::id(),
+        data: auction_house::instruction::CreateWalletStats {}.data(),
+        accounts,
+    };
+
+    process_instruction(context, instruction, payer)
+        .await
+        .map(|_| wallet_stats)
+}